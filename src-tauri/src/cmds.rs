@@ -17,7 +17,9 @@ use std::fs;
 
 #[tauri::command]
 pub fn copy_clash_env() -> CmdResult {
-    feat::copy_clash_env();
+    if !feat::copy_clash_env() {
+        ret_err!("failed to copy the proxy env");
+    }
     Ok(())
 }
 
@@ -27,18 +29,25 @@ pub fn get_profiles() -> CmdResult<IProfiles> {
     Ok(Config::profiles().data().clone())
 }
 
+/// `force` 为真时即使配置没有变化也推送给内核
 #[tauri::command]
-pub async fn enhance_profiles() -> CmdResult {
-    match CoreManager::global().update_config().await {
-        Ok((true, _)) => {
+pub async fn enhance_profiles(force: Option<bool>) -> CmdResult {
+    let manager = CoreManager::global();
+    let res = if force.unwrap_or(false) {
+        manager.update_config_force().await
+    } else {
+        manager.update_config().await
+    };
+    match res {
+        Ok(Ok(())) => {
             println!("[enhance_profiles] 配置更新成功");
             log_err!(tray::Tray::global().update_tooltip());
             handle::Handle::refresh_clash();
             Ok(())
         }
-        Ok((false, error_msg)) => {
-            println!("[enhance_profiles] 配置验证失败: {}", error_msg);
-            handle::Handle::notice_message("config_validate::error", &error_msg);
+        Ok(Err(error)) => {
+            println!("[enhance_profiles] 配置验证失败: {}", error);
+            handle::Handle::notice_error("config_validate::error", &error);
             Ok(())
         }
         Err(e) => {
@@ -51,10 +60,23 @@ pub async fn enhance_profiles() -> CmdResult {
 
 #[tauri::command]
 pub async fn import_profile(url: String, option: Option<PrfOption>) -> CmdResult {
+    if let Some(option) = option.as_ref() {
+        wrap_err!(option.check_tls())?;
+    }
     let item = wrap_err!(PrfItem::from_url(&url, None, None, option).await)?;
     wrap_err!(Config::profiles().data().append_item(item))
 }
 
+/// 导入base64编码的配置，返回新订阅的uid
+#[tauri::command]
+pub async fn import_config_base64(b64: String, profile_name: String) -> CmdResult<String> {
+    wrap_err!(
+        CoreManager::global()
+            .import_clash_config_from_base64(b64, profile_name)
+            .await
+    )
+}
+
 #[tauri::command]
 pub async fn reorder_profile(active_id: String, over_id: String) -> CmdResult {
     wrap_err!(Config::profiles().data().reorder(active_id, over_id))
@@ -62,12 +84,18 @@ pub async fn reorder_profile(active_id: String, over_id: String) -> CmdResult {
 
 #[tauri::command]
 pub async fn create_profile(item: PrfItem, file_data: Option<String>) -> CmdResult {
+    if let Some(option) = item.option.as_ref() {
+        wrap_err!(option.check_tls())?;
+    }
     let item = wrap_err!(PrfItem::from(item, file_data).await)?;
     wrap_err!(Config::profiles().data().append_item(item))
 }
 
 #[tauri::command]
 pub async fn update_profile(index: String, option: Option<PrfOption>) -> CmdResult {
+    if let Some(option) = option.as_ref() {
+        wrap_err!(option.check_tls())?;
+    }
     wrap_err!(feat::update_profile(index, option).await)
 }
 
@@ -98,7 +126,7 @@ pub async fn patch_profiles_config(
     
     // 更新配置并进行验证
     match CoreManager::global().update_config().await {
-        Ok((true, _)) => {
+        Ok(Ok(())) => {
             println!("[cmd配置patch] 配置更新成功");
             handle::Handle::refresh_clash();
             let _ = tray::Tray::global().update_tooltip();
@@ -106,8 +134,8 @@ pub async fn patch_profiles_config(
             wrap_err!(Config::profiles().data().save_file())?;
             Ok(true)
         }
-        Ok((false, error_msg)) => {
-            println!("[cmd配置patch] 配置验证失败: {}", error_msg);
+        Ok(Err(error)) => {
+            println!("[cmd配置patch] 配置验证失败: {}", error);
             Config::profiles().discard();
             
             // 如果验证失败，恢复到之前的配置
@@ -125,7 +153,7 @@ pub async fn patch_profiles_config(
             }
 
             // 发送验证错误通知
-            handle::Handle::notice_message("config_validate::error", &error_msg);
+            handle::Handle::notice_error("config_validate::error", &error);
             Ok(false)
         }
         Err(e) => {
@@ -150,6 +178,9 @@ pub async fn patch_profiles_config_by_profile_index(
 /// 修改某个profile item的
 #[tauri::command]
 pub fn patch_profile(index: String, profile: PrfItem) -> CmdResult {
+    if let Some(option) = profile.option.as_ref() {
+        wrap_err!(option.check_tls())?;
+    }
     wrap_err!(Config::profiles().data().patch_item(index, profile))?;
     Ok(())
 }
@@ -197,7 +228,8 @@ pub async fn save_profile_file(index: String, file_data: Option<String>) -> CmdR
         (profiles_dir.join(path), content)
     };
 
-    // 保存新的配置文件
+    // 保存新的配置文件，由前端重新加载，不需要再由热重载触发
+    profile_watcher::ProfileWatcher::global().note_own_write(&file_path);
     wrap_err!(fs::write(&file_path, file_data.clone().unwrap()))?;
     
     let file_path_str = file_path.to_string_lossy();
@@ -205,35 +237,22 @@ pub async fn save_profile_file(index: String, file_data: Option<String>) -> CmdR
     
     // 验证配置文件
     match CoreManager::global().validate_config_file(&file_path_str).await {
-        Ok((true, _)) => {
+        Ok(Ok(())) => {
             println!("[cmd配置save] 验证成功");
             Ok(())
         }
-        Ok((false, error_msg)) => {
-            println!("[cmd配置save] 验证失败: {}", error_msg);
+        Ok(result) => {
             // 恢复原始配置文件
+            profile_watcher::ProfileWatcher::global().note_own_write(&file_path);
             wrap_err!(fs::write(&file_path, original_content))?;
-            
-            // 智能判断是否为脚本错误
-            let is_script_error = file_path_str.ends_with(".js") || 
-                                error_msg.contains("Script syntax error") || 
-                                error_msg.contains("Script must contain a main function") ||
-                                error_msg.contains("Failed to read script file");
-            
-            if is_script_error {
-                // 脚本错误使用专门的通知处理
-                let result = (false, error_msg.clone());
-                handle_script_validation_notice(&result, "脚本文件");
-            } else {
-                // 普通配置错误使用一般通知
-                handle::Handle::notice_message("config_validate::error", &error_msg);
-            }
-            
+            // 按错误码区分脚本错误和普通配置错误
+            handle_validation_notice(&result, "配置文件");
             Ok(())
         }
         Err(e) => {
             println!("[cmd配置save] 验证过程发生错误: {}", e);
             // 恢复原始配置文件
+            profile_watcher::ProfileWatcher::global().note_own_write(&file_path);
             wrap_err!(fs::write(&file_path, original_content))?;
             Err(e.to_string())
         }
@@ -242,24 +261,22 @@ pub async fn save_profile_file(index: String, file_data: Option<String>) -> CmdR
 
 #[tauri::command]
 pub fn get_clash_info() -> CmdResult<ClashInfo> {
-    Ok(Config::clash().latest().get_client_info())
+    let mut info = Config::clash().latest().get_client_info();
+    // 前端只能通过这里拿到本次启动内核使用的密钥
+    info.secret = controller::secret();
+    Ok(info)
 }
 
 #[tauri::command]
 pub fn get_runtime_config() -> CmdResult<Option<Mapping>> {
-    Ok(Config::runtime().latest().config.clone())
+    Ok(Config::runtime().latest().redacted_config())
 }
 
 #[tauri::command]
 pub fn get_runtime_yaml() -> CmdResult<String> {
-    let runtime = Config::runtime();
-    let runtime = runtime.latest();
-    let config = runtime.config.as_ref();
-    wrap_err!(config
-        .ok_or(anyhow::anyhow!("failed to parse config to yaml file"))
-        .and_then(
-            |config| serde_yaml::to_string(config).context("failed to convert config to yaml")
-        ))
+    let config = { Config::runtime().latest().redacted_config() };
+    let config = wrap_err!(config.ok_or(anyhow::anyhow!("failed to parse config to yaml file")))?;
+    wrap_err!(serde_yaml::to_string(&config).context("failed to convert config to yaml"))
 }
 
 #[tauri::command]
@@ -282,6 +299,12 @@ pub async fn patch_clash_mode(payload: String) -> CmdResult {
     Ok(feat::change_clash_mode(payload))
 }
 
+/// 修改 allow-lan、ipv6、log-level、mode、unified-delay 等选项，立即生效并保存
+#[tauri::command]
+pub async fn patch_clash_runtime_option(key: String, value: serde_yaml::Value) -> CmdResult {
+    wrap_err!(feat::patch_clash_runtime_option(&key, value).await)
+}
+
 
 #[tauri::command]
 pub fn get_verge_config() -> CmdResult<IVergeResponse> {
@@ -295,11 +318,31 @@ pub async fn patch_verge_config(payload: IVerge) -> CmdResult {
     wrap_err!(feat::patch_verge(payload).await)
 }
 
+/// 逐项验证当前订阅的增强链
+#[tauri::command]
+pub async fn validate_chain() -> CmdResult<Vec<ChainItemReport>> {
+    wrap_err!(CoreManager::global().validate_chain().await)
+}
+
+/// 切换内核前用当前内核和目标内核分别验证运行时配置
+#[tauri::command]
+pub async fn compare_core_validation(clash_core: String) -> CmdResult<CoreComparison> {
+    wrap_err!(CoreManager::global().compare_core_validation(&clash_core).await)
+}
+
+/// 目标内核验证配置失败时，只有 `use_default_config` 为真才会使用默认配置切换
 #[tauri::command]
-pub async fn change_clash_core(clash_core: String) -> CmdResult<Option<String>> {
+pub async fn change_clash_core(
+    clash_core: String,
+    use_default_config: Option<bool>,
+) -> CmdResult<Option<String>> {
     log::info!(target: "app", "changing core to {clash_core}");
     
-    match CoreManager::global().change_core(Some(clash_core.clone())).await {
+    let use_default_config = use_default_config.unwrap_or(false);
+    match CoreManager::global()
+        .change_core(Some(clash_core.clone()), use_default_config)
+        .await
+    {
         Ok(_) => {
             log::info!(target: "app", "core changed to {clash_core}");
             handle::Handle::notice_message("config_core::change_success", &clash_core);
@@ -383,18 +426,366 @@ pub mod uwp {
     }
 }
 
+/// 切换策略组的节点并保存到当前订阅，返回自动关闭的连接数
+#[tauri::command]
+pub async fn select_proxy(group: String, name: String) -> CmdResult<usize> {
+    wrap_err!(feat::change_proxy(group, name).await)
+}
+
+/// 测试节点延迟，地址和超时时间为空时使用 verge 配置中的默认值
 #[tauri::command]
-pub async fn clash_api_get_proxy_delay(
+pub async fn test_proxy_delay(
     name: String,
     url: Option<String>,
-    timeout: i32,
-) -> CmdResult<clash_api::DelayRes> {
-    match clash_api::get_proxy_delay(name, url, timeout).await {
-        Ok(res) => Ok(res),
-        Err(err) => Err(err.to_string()),
+    timeout: Option<u32>,
+) -> CmdResult<clash_api::ProxyDelay> {
+    wrap_err!(clash_api::test_delay(&name, url, timeout).await)
+}
+
+/// 在后台测试策略组中所有节点的延迟，返回任务id
+/// 结果通过 `verge://delay-result` 事件逐个发送，汇总在 `operation::finished` 中
+#[tauri::command]
+pub fn test_group_delay(
+    group: String,
+    url: Option<String>,
+    timeout: Option<u32>,
+) -> CmdResult<String> {
+    Ok(clash_api::test_group_delay(group, url, timeout))
+}
+
+/// 内核的 /proxies，短时间内的重复请求使用缓存，`fresh` 为 true 时重新获取
+#[tauri::command]
+pub async fn get_proxies(fresh: Option<bool>) -> CmdResult<serde_json::Value> {
+    let proxies = wrap_err!(clash_api::get_proxies(fresh.unwrap_or(false)).await)?;
+    Ok(proxies.as_ref().clone())
+}
+
+/// 内核的 /providers/proxies，缓存同 `get_proxies`
+#[tauri::command]
+pub async fn get_providers_proxies(fresh: Option<bool>) -> CmdResult<serde_json::Value> {
+    let providers = wrap_err!(clash_api::get_providers_proxies(fresh.unwrap_or(false)).await)?;
+    Ok(providers.as_ref().clone())
+}
+
+/// 获取来自订阅或文件的 proxy provider
+#[tauri::command]
+pub async fn get_proxy_providers() -> CmdResult<Vec<clash_api::ProxyProvider>> {
+    wrap_err!(clash_api::get_proxy_providers().await)
+}
+
+/// 当前订阅和所有 proxy provider 的流量、到期信息
+#[tauri::command]
+pub async fn get_subscription_usage() -> CmdResult<Vec<feat::SubscriptionUsage>> {
+    wrap_err!(feat::get_subscription_usage().await)
+}
+
+#[tauri::command]
+pub async fn update_proxy_provider(name: String) -> CmdResult {
+    wrap_err!(clash_api::update_proxy_provider(&name).await)
+}
+
+#[tauri::command]
+pub async fn healthcheck_proxy_provider(name: String) -> CmdResult {
+    wrap_err!(clash_api::healthcheck_proxy_provider(&name).await)
+}
+
+/// 依次更新所有 proxy provider，返回每个 provider 的结果
+#[tauri::command]
+pub async fn update_all_proxy_providers() -> CmdResult<Vec<clash_api::ProviderUpdateResult>> {
+    wrap_err!(clash_api::update_all_proxy_providers().await)
+}
+
+/// 获取内核的版本，前端据此判断是否为 Meta 内核
+#[tauri::command]
+pub async fn get_clash_version() -> CmdResult<clash_api::CoreApiVersion> {
+    wrap_err!(clash_api::get_api_version().await)
+}
+
+/// 获取内核当前的基础配置
+#[tauri::command]
+pub async fn get_clash_configs() -> CmdResult<serde_json::Value> {
+    wrap_err!(clash_api::get_configs().await)
+}
+
+#[tauri::command]
+pub async fn is_core_debug_enabled() -> CmdResult<bool> {
+    Ok(clash_api::is_debug_enabled().await)
+}
+
+#[tauri::command]
+pub async fn core_gc() -> CmdResult {
+    wrap_err!(clash_api::gc().await)
+}
+
+/// 获取来自订阅或文件的 rule provider
+#[tauri::command]
+pub async fn get_rule_providers() -> CmdResult<Vec<clash_api::RuleProvider>> {
+    wrap_err!(clash_api::get_rule_providers().await)
+}
+
+#[tauri::command]
+pub async fn update_rule_provider(name: String) -> CmdResult {
+    wrap_err!(clash_api::update_rule_provider(&name).await)
+}
+
+/// 依次更新所有 rule provider，返回每个 provider 的结果
+#[tauri::command]
+pub async fn update_all_rule_providers() -> CmdResult<Vec<clash_api::ProviderUpdateResult>> {
+    wrap_err!(clash_api::update_all_rule_providers().await)
+}
+
+/// 开始按等级订阅内核日志，已经订阅时切换等级
+#[tauri::command]
+pub fn start_core_logs(level: String) -> CmdResult {
+    wrap_err!(clash_api::start_core_log_subscription(&level))
+}
+
+#[tauri::command]
+pub fn stop_core_logs() -> CmdResult {
+    clash_api::stop_core_log_subscription();
+    Ok(())
+}
+
+/// traffic、memory、logs 订阅当前的连接状态，没有订阅时为空
+#[tauri::command]
+pub fn get_subscription_state(name: String) -> CmdResult<Option<clash_api::SubscriptionState>> {
+    Ok(clash_api::subscription_state(&name))
+}
+
+/// 订阅内核的内存占用，内核不支持时返回 false
+#[tauri::command]
+pub fn start_memory_usage() -> CmdResult<bool> {
+    Ok(clash_api::start_memory_subscription())
+}
+
+#[tauri::command]
+pub fn stop_memory_usage() -> CmdResult {
+    clash_api::stop_memory_subscription();
+    Ok(())
+}
+
+/// 清空内核的 fake-ip 缓存
+#[tauri::command]
+pub async fn flush_fakeip() -> CmdResult {
+    wrap_err!(clash_api::flush_fakeip().await)
+}
+
+/// 通过内核的 DNS 解析域名，记录类型默认为 A
+#[tauri::command]
+pub async fn dns_query(
+    name: String,
+    qtype: Option<String>,
+) -> CmdResult<clash_api::DnsQueryResult> {
+    let qtype = qtype.unwrap_or("A".into());
+    wrap_err!(clash_api::dns_query(&name, &qtype).await)
+}
+
+/// 关闭并重连所有连接
+#[tauri::command]
+pub async fn force_reconnect_all_connections() -> CmdResult<usize> {
+    wrap_err!(feat::force_reconnect_all_connections().await)
+}
+
+/// 默认最多返回的连接数，TUN 模式下可能有上千个连接
+const CONNECTIONS_LIMIT: usize = 500;
+
+/// 当前的连接，可以按域名筛选、按流量排序，超过 `limit`（默认 500）时截断
+#[tauri::command]
+pub async fn get_connections(
+    host: Option<String>,
+    sort_by_traffic: Option<bool>,
+    limit: Option<usize>,
+) -> CmdResult<clash_api::ConnectionsPage> {
+    let snapshot = wrap_err!(clash_api::get_connections().await)?;
+    let limit = limit.unwrap_or(CONNECTIONS_LIMIT);
+    Ok(snapshot.query(host.as_deref(), sort_by_traffic.unwrap_or(false), limit))
+}
+
+/// 关闭所有活动连接，指定 `filter` 时只关闭域名或代理链包含它的连接，返回关闭的数量
+#[tauri::command]
+pub async fn clear_connections(filter: Option<String>) -> CmdResult<usize> {
+    wrap_err!(feat::close_connections(filter).await)
+}
+
+/// 关闭一个连接，连接已经不存在时也返回成功
+#[tauri::command]
+pub async fn close_connection(id: String) -> CmdResult {
+    wrap_err!(clash_api::close_connection(&id).await)
+}
+
+/// 切换代理的多路复用，不指定代理时切换全部代理
+#[tauri::command]
+pub async fn toggle_proxy_mux(proxy_name: Option<String>) -> CmdResult<bool> {
+    wrap_err!(feat::toggle_proxy_mux(proxy_name).await)
+}
+
+/// 检查并重新加载订阅的脚本
+#[tauri::command]
+pub async fn reload_profile_script(uid: String) -> CmdResult {
+    wrap_err!(feat::reload_profile_script(uid).await)
+}
+
+/// 导出诊断信息，返回任务id，结果为保存的路径
+#[tauri::command]
+pub fn export_diagnostics_dialog(operation_id: Option<String>) -> CmdResult<String> {
+    Ok(feat::export_diagnostics_operation(operation_id))
+}
+
+/// 获取内核版本，不传名称时为当前使用的内核
+#[tauri::command]
+pub async fn get_core_version(clash_core: Option<String>) -> CmdResult<CoreVersion> {
+    wrap_err!(CoreManager::global().get_core_version(clash_core).await)
+}
+
+/// 验证配置文件，返回解析后的错误位置
+#[tauri::command]
+pub async fn validate_config_detailed(
+    config_path: String,
+) -> CmdResult<validation::ValidationReport> {
+    wrap_err!(CoreManager::global().validate_config_report(&config_path).await)
+}
+
+/// 获取内核当前的运行状态
+#[tauri::command]
+pub fn get_core_state() -> CmdResult<CoreStateInfo> {
+    Ok(CoreManager::global().get_core_state())
+}
+
+/// 在后台通过内核的 /upgrade 升级当前内核，返回任务id，结果为 `CoreUpgradeResult`
+#[tauri::command]
+pub fn upgrade_core(operation_id: Option<String>) -> CmdResult<String> {
+    Ok(operation::spawn(operation_id, |_id, token| async move {
+        CoreManager::global().upgrade_core(&token).await
+    }))
+}
+
+/// 获取内核的运行信息（运行模式、PID、启动时间、重启次数等）
+#[tauri::command]
+pub fn get_core_status() -> CmdResult<CoreStatus> {
+    Ok(CoreManager::global().get_core_status())
+}
+
+/// 内核进程最近一次采样的 CPU 和内存占用
+#[tauri::command]
+pub fn get_core_usage() -> CmdResult<Option<CoreUsage>> {
+    Ok(CoreManager::global().get_core_usage())
+}
+
+/// 已配置热键的注册状态，注册失败时附带原因
+#[tauri::command]
+pub fn get_hotkey_status() -> CmdResult<Vec<hotkey::HotkeyStatus>> {
+    Ok(hotkey::Hotkey::global().get_status())
+}
+
+/// 编辑器获得焦点时暂停热键，避免输入时触发，失去焦点时调用 `resume_hotkeys`
+#[tauri::command]
+pub fn suspend_hotkeys() -> CmdResult {
+    hotkey::Hotkey::global().suspend();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn resume_hotkeys() -> CmdResult {
+    hotkey::Hotkey::global().resume();
+    Ok(())
+}
+
+/// 保存前检查热键，功能无效、快捷键无法解析或已被占用时返回原因
+///
+/// `pending` 为设置窗口中还没有保存的全部热键，用于检查冲突
+#[tauri::command]
+pub fn validate_hotkey(
+    key: String,
+    func: String,
+    pending: Vec<String>,
+) -> CmdResult<hotkey::HotkeyVerdict> {
+    Ok(hotkey::Hotkey::global().validate(&key, &func, &pending))
+}
+
+/// 最近的内核启动、停止、重启、崩溃等事件
+#[tauri::command]
+pub fn get_core_history() -> CmdResult<Vec<history::CoreEvent>> {
+    Ok(CoreManager::global().get_core_history())
+}
+
+/// 轮转内核日志，返回新的日志文件路径
+#[tauri::command]
+pub async fn rotate_core_log() -> CmdResult<String> {
+    let path = wrap_err!(CoreManager::global().rotate_core_log().await)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// 按订阅格式严格验证配置文件
+#[tauri::command]
+pub async fn validate_subscription_yaml(
+    path: String,
+) -> CmdResult<Option<validation::ValidateErrorCode>> {
+    let result = wrap_err!(CoreManager::global().validate_subscription_yaml(&path).await)?;
+    Ok(result.err())
+}
+
+/// 立即测试连通性，返回延迟(ms)
+#[tauri::command]
+pub async fn check_connectivity_now() -> CmdResult<u32> {
+    wrap_err!(feat::check_connectivity().await)
+}
+
+/// 设置内核自身使用的上游代理
+#[tauri::command]
+pub async fn set_upstream_proxy(upstream_url: Option<String>) -> CmdResult {
+    wrap_err!(CoreManager::global().configure_upstream_proxy(upstream_url).await)
+}
+
+/// 切换模拟代理模式，仅在调试构建或开启 `mock-mode` 时可用
+#[tauri::command]
+pub async fn toggle_mock_proxy_mode(port: Option<u16>) -> CmdResult<bool> {
+    #[cfg(any(debug_assertions, feature = "mock-mode"))]
+    return wrap_err!(mock::toggle_mock_mode(port).await);
+
+    #[cfg(not(any(debug_assertions, feature = "mock-mode")))]
+    {
+        let _ = port;
+        Err("mock mode is not available in this build".into())
     }
 }
 
+/// 保存前端未提交的修改并重新加载配置
+#[tauri::command]
+pub async fn save_and_reload_config() -> CmdResult {
+    wrap_err!(feat::save_and_reload_config().await)
+}
+
+/// 前端响应 `config::request_save`，保存完成后调用
+#[tauri::command]
+pub fn complete_config_save(error: Option<String>) -> CmdResult {
+    feat::complete_config_save(error);
+    Ok(())
+}
+
+/// 更新GEO数据库，返回任务id
+#[tauri::command]
+pub fn update_geo_data(operation_id: Option<String>) -> CmdResult<String> {
+    Ok(feat::update_geo_data(operation_id))
+}
+
+/// 由内核更新GEO数据库，返回任务id
+#[tauri::command]
+pub fn update_geo_by_core(operation_id: Option<String>) -> CmdResult<String> {
+    Ok(feat::update_geo_by_core(operation_id))
+}
+
+/// 取消后台任务
+#[tauri::command]
+pub fn cancel_operation(id: String) -> CmdResult<bool> {
+    Ok(operation::OperationRegistry::global().cancel(&id))
+}
+
+/// 切换IPv6路由，返回切换后的状态
+#[tauri::command]
+pub async fn toggle_ipv6_routing() -> CmdResult<bool> {
+    wrap_err!(feat::toggle_ipv6_routing_config().await)
+}
+
 #[tauri::command]
 pub fn get_portable_flag() -> CmdResult<bool> {
     Ok(*dirs::PORTABLE_FLAG.get().unwrap_or(&false))
@@ -468,6 +859,12 @@ pub fn get_network_interfaces() -> Vec<String> {
     result
 }
 
+/// 获取TUN网卡的收发统计
+#[tauri::command]
+pub fn get_tun_stats() -> CmdResult<TunInterfaceStats> {
+    wrap_err!(CoreManager::global().get_tun_interface_stats())
+}
+
 #[tauri::command]
 pub fn get_network_interfaces_info() -> CmdResult<Vec<NetworkInterface>> {
     use network_interface::NetworkInterface;
@@ -522,8 +919,8 @@ pub async fn save_webdav_config(url: String, username: String, password: String)
 }
 
 #[tauri::command]
-pub async fn create_webdav_backup() -> CmdResult<()> {
-    wrap_err!(feat::create_backup_and_upload_webdav().await)
+pub async fn create_webdav_backup(include_tls_options: Option<bool>) -> CmdResult<()> {
+    wrap_err!(feat::create_backup_and_upload_webdav(include_tls_options.unwrap_or(false)).await)
 }
 
 #[tauri::command]
@@ -563,28 +960,12 @@ pub async fn script_validate_notice(status: String, msg: String) -> CmdResult {
     Ok(())
 }
 
-/// 处理脚本验证相关的所有消息通知
-/// 统一通知接口，保持消息类型一致性
-pub fn handle_script_validation_notice(result: &(bool, String), file_type: &str) {
-    if !result.0 {
-        let error_msg = &result.1;
-        
-        // 根据错误消息内容判断错误类型
-        let status = if error_msg.starts_with("File not found:") {
-            "config_validate::file_not_found"
-        } else if error_msg.starts_with("Failed to read script file:") {
-            "config_validate::script_error"
-        } else if error_msg.starts_with("Script syntax error:") {
-            "config_validate::script_syntax_error"
-        } else if error_msg == "Script must contain a main function" {
-            "config_validate::script_missing_main"
-        } else {
-            // 如果是其他类型错误，作为一般脚本错误处理
-            "config_validate::script_error"
-        };
-        
-        log::warn!(target: "app", "{} 验证失败: {}", file_type, error_msg);
-        handle::Handle::notice_message(status, error_msg);
+/// 处理配置和脚本验证相关的所有消息通知
+/// 统一通知接口，通知类型由错误码决定
+pub fn handle_validation_notice(result: &validation::ValidateResult, file_type: &str) {
+    if let Err(error) = result {
+        log::warn!(target: "app", "{} 验证失败: {}", file_type, error);
+        handle::Handle::notice_error(error.notice_status(), error);
     }
 }
 
@@ -595,8 +976,8 @@ pub async fn validate_script_file(file_path: String) -> CmdResult<bool> {
     
     match CoreManager::global().validate_config_file(&file_path).await {
         Ok(result) => {
-            handle_script_validation_notice(&result, "脚本文件");
-            Ok(result.0)  // 返回验证结果布尔值
+            handle_validation_notice(&result, "脚本文件");
+            Ok(result.is_ok())  // 返回验证结果布尔值
         },
         Err(e) => {
             let error_msg = e.to_string();
@@ -606,3 +987,72 @@ pub async fn validate_script_file(file_path: String) -> CmdResult<bool> {
         }
     }
 }
+
+/// 每次最多返回的规则数
+const RULES_PAGE_LIMIT: usize = 1000;
+
+/// 当前生效的规则，按 `query` 筛选后返回从 `offset` 开始的一页（默认 1000 条）
+#[tauri::command]
+pub async fn get_rules(
+    query: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> CmdResult<clash_api::RulesPage> {
+    let rules = wrap_err!(clash_api::get_rules_cached().await)?;
+    let limit = limit.unwrap_or(RULES_PAGE_LIMIT);
+    Ok(clash_api::query_rules(&rules, query.as_deref(), offset.unwrap_or(0), limit))
+}
+
+/// 获取会把流量发往指定代理的规则
+#[tauri::command]
+pub async fn get_rules_for_proxy(proxy_name: String) -> CmdResult<Vec<clash_api::RuleEntry>> {
+    wrap_err!(
+        CoreManager::global()
+            .get_effective_rules_for_proxy(proxy_name)
+            .await
+    )
+}
+
+/// 开始记录连接，直到调用 `stop_connection_recording`
+#[tauri::command]
+pub fn start_connection_recording(output_path: String) -> CmdResult {
+    wrap_err!(CoreManager::global().start_recording(output_path.into()))
+}
+
+#[tauri::command]
+pub fn stop_connection_recording() -> CmdResult {
+    replay::stop_recording();
+    Ok(())
+}
+
+/// 使用当前规则回放记录的连接
+#[tauri::command]
+pub fn replay_connections(input_path: String) -> CmdResult<replay::ReplayReport> {
+    wrap_err!(CoreManager::global().replay_recording(input_path.into()))
+}
+
+/// 开始检查代理服务器的证书变化
+#[tauri::command]
+pub fn start_certificate_watch(proxy_names: Vec<String>) -> CmdResult {
+    tauri::async_runtime::spawn(async move {
+        log_err!(
+            CoreManager::global()
+                .watch_certificate_changes(proxy_names)
+                .await
+        );
+    });
+    Ok(())
+}
+
+/// 停止检查代理服务器的证书变化
+#[tauri::command]
+pub fn stop_certificate_watch() -> CmdResult {
+    CoreManager::global().stop_certificate_watch();
+    Ok(())
+}
+
+/// 检查配置文件的DNS部分
+#[tauri::command]
+pub fn validate_dns_config(config_path: String) -> CmdResult<Vec<DnsConfigWarning>> {
+    wrap_err!(CoreManager::global().validate_dns_config(&config_path))
+}
@@ -7,11 +7,14 @@ use crate::{
 };
 use anyhow::{anyhow, Result};
 use once_cell::sync::OnceCell;
+use serde_yaml::Mapping;
+use std::collections::HashSet;
 use std::path::PathBuf;
 use tokio::time::{sleep, Duration};
 
 pub const RUNTIME_CONFIG: &str = "clash-verge.yaml";
-pub const CHECK_CONFIG: &str = "clash-verge-check.yaml";
+/// 验证用的临时配置所在的目录，每次验证使用不同的文件名
+pub const CHECK_CONFIG_DIR: &str = "check";
 
 pub struct Config {
     clash_config: Draft<IClashTemp>,
@@ -73,47 +76,62 @@ impl Config {
         // 生成运行时配置文件并验证
         let config_result = Self::generate_file(ConfigType::Run);
 
+        // 通知类型、验证错误、通知内容和回退配置中保留的字段
         let validation_result = if let Ok(_) = config_result {
             // 验证配置文件
             println!("[首次启动] 开始验证配置");
             
             match CoreManager::global().validate_config().await {
-                Ok((is_valid, error_msg)) => {
-                    if !is_valid {
-                        println!("[首次启动] 配置验证失败，使用默认最小配置启动: {}", error_msg);
-                        CoreManager::global()
-                            .use_default_config("config_validate::boot_error", &error_msg)
-                            .await?;
-                        Some(("config_validate::boot_error", error_msg))
-                    } else {
-                        println!("[首次启动] 配置验证成功");
-                        Some(("config_validate::success", String::new()))
+                Ok(Err(error)) => match CoreManager::global().recover_config(&error).await {
+                    // 禁用出错的增强项后可以通过验证
+                    Ok(Some(message)) => {
+                        println!("[首次启动] 配置验证失败，以安全模式启动: {}", message);
+                        Some(("config_validate::safe_mode", None, message, None))
                     }
+                    recovered => {
+                        crate::log_err!(recovered);
+                        println!("[首次启动] 配置验证失败，使用默认最小配置启动: {}", error);
+                        let keys = CoreManager::global().write_default_config().await?;
+                        Some((
+                            "config_validate::boot_error",
+                            Some(error),
+                            String::new(),
+                            Some(keys),
+                        ))
+                    }
+                },
+                Ok(Ok(())) => {
+                    println!("[首次启动] 配置验证成功");
+                    Some(("config_validate::success", None, String::new(), None))
                 }
                 Err(err) => {
                     println!("[首次启动] 验证进程执行失败: {}", err);
-                    CoreManager::global()
-                        .use_default_config("config_validate::process_terminated", "")
-                        .await?;
-                    Some(("config_validate::process_terminated", String::new()))
+                    let keys = CoreManager::global().write_default_config().await?;
+                    Some((
+                        "config_validate::process_terminated",
+                        None,
+                        String::new(),
+                        Some(keys),
+                    ))
                 }
             }
         } else {
             println!("[首次启动] 生成配置文件失败，使用默认配置");
-            CoreManager::global()
-                .use_default_config(
-                    "config_validate::error",
-                    "",
-                )
-                .await?;
-            Some(("config_validate::error", String::new()))
+            let keys = CoreManager::global().write_default_config().await?;
+            Some(("config_validate::error", None, String::new(), Some(keys)))
         };
 
-        // 在单独的任务中发送通知
-        if let Some((msg_type, msg_content)) = validation_result {
+        // 在单独的任务中发送通知，回退配置的提示也只在这里发送一次
+        if let Some((msg_type, error, message, fallback_keys)) = validation_result {
             tauri::async_runtime::spawn(async move {
                 sleep(Duration::from_secs(2)).await;
-                handle::Handle::notice_message(msg_type, &msg_content);
+                match error {
+                    Some(error) => handle::Handle::notice_error(msg_type, &error),
+                    None => handle::Handle::notice_message(msg_type, message),
+                }
+                if let Some(keys) = fallback_keys {
+                    CoreManager::notice_fallback(&keys);
+                }
             });
         }
 
@@ -124,7 +142,6 @@ impl Config {
     pub fn generate_file(typ: ConfigType) -> Result<PathBuf> {
         let path = match typ {
             ConfigType::Run => dirs::app_home_dir()?.join(RUNTIME_CONFIG),
-            ConfigType::Check => dirs::app_home_dir()?.join(CHECK_CONFIG),
         };
 
         let runtime = Config::runtime();
@@ -138,22 +155,79 @@ impl Config {
         Ok(path)
     }
 
+    /// 把指定的配置写到验证用的临时文件中
+    pub fn generate_check_file(config: &Mapping) -> Result<PathBuf> {
+        let path = Self::check_file_path()?;
+        help::save_yaml(&path, config, Some("# Generated by Clash Verge"))?;
+        Ok(path)
+    }
+
+    /// 每次验证使用不同的文件，避免同时进行的验证互相覆盖
+    fn check_file_path() -> Result<PathBuf> {
+        let dir = dirs::app_home_dir()?.join(CHECK_CONFIG_DIR);
+        std::fs::create_dir_all(&dir)?;
+        Ok(dir.join(format!("{}.yaml", help::get_uid("clash-verge-check-"))))
+    }
+
     /// 生成订阅存好
     pub async fn generate() -> Result<()> {
-        let (config, exists_keys, logs) = enhance::enhance().await;
+        Self::generate_with(&HashSet::new()).await
+    }
+
+    /// 跳过部分增强项生成订阅
+    pub async fn generate_with(skip: &HashSet<String>) -> Result<()> {
+        let (config, exists_keys, logs) = enhance::enhance_with(skip).await;
+        let secret = Self::current_secret()?;
 
-        *Config::runtime().draft() = IRuntime {
+        let mut runtime = IRuntime {
             config: Some(config),
             exists_keys,
             chain_logs: logs,
+            applied_hash: None,
+            secret: None,
         };
+        runtime.set_secret(secret);
+        *Config::runtime().draft() = runtime;
 
         Ok(())
     }
+
+    /// 本次启动内核使用的控制接口密钥，还没有生成时生成一个
+    pub fn current_secret() -> Result<String> {
+        let secret = { Config::runtime().latest().secret.clone() };
+        match secret {
+            Some(secret) => Ok(secret),
+            None => Self::new_secret(),
+        }
+    }
+
+    /// 每次启动内核前更换控制接口的密钥，返回新的密钥是否和之前的不同
+    /// 已经提交的和草稿中的运行时配置都需要更新，否则丢弃草稿后会使用旧的密钥
+    pub fn rotate_secret() -> Result<bool> {
+        let secret = Self::new_secret()?;
+        let changed = {
+            let mut runtime = Config::runtime().data();
+            let changed = runtime.secret.as_ref() != Some(&secret);
+            runtime.set_secret(secret.clone());
+            changed
+        };
+        Config::runtime().latest().set_secret(secret);
+        Ok(changed)
+    }
+
+    /// verge 中固定了密钥时使用固定的，否则生成 32 字节的随机密钥
+    fn new_secret() -> Result<String> {
+        let pinned = { Config::verge().latest().external_controller_secret.clone() };
+        if let Some(pinned) = pinned.filter(|secret| !secret.is_empty()) {
+            return Ok(pinned);
+        }
+        let mut bytes = [0u8; 32];
+        getrandom::getrandom(&mut bytes)?;
+        Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+    }
 }
 
 #[derive(Debug)]
 pub enum ConfigType {
     Run,
-    Check,
 }
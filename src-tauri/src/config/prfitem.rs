@@ -1,9 +1,11 @@
+use crate::core::{operation, profile_watcher::ProfileWatcher};
 use crate::utils::{dirs, help, resolve::VERSION, tmpl};
 use anyhow::{bail, Context, Result};
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Mapping;
 use std::fs;
+use std::path::{Component, Path, PathBuf};
 use sysproxy::Sysproxy;
 
 use super::Config;
@@ -95,6 +97,30 @@ pub struct PrfOption {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub danger_accept_invalid_certs: Option<bool>,
 
+    /// for `remote` profile
+    /// custom CA bundle, file path or inline PEM
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_ca_pem: Option<String>,
+
+    /// for `remote` profile
+    /// client certificate for mTLS, file path or inline PEM
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<String>,
+
+    /// for `remote` profile
+    /// client private key for mTLS, file path or inline PEM
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<String>,
+
+    /// for `remote` profile
+    /// skip certificate verification
+    /// only takes effect when `insecure_skip_verify_confirmed` is also set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insecure_skip_verify: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insecure_skip_verify_confirmed: Option<bool>,
+
     pub merge: Option<String>,
 
     pub script: Option<String>,
@@ -116,6 +142,13 @@ impl PrfOption {
                 a.danger_accept_invalid_certs = b
                     .danger_accept_invalid_certs
                     .or(a.danger_accept_invalid_certs);
+                a.custom_ca_pem = b.custom_ca_pem.or(a.custom_ca_pem);
+                a.client_cert = b.client_cert.or(a.client_cert);
+                a.client_key = b.client_key.or(a.client_key);
+                a.insecure_skip_verify = b.insecure_skip_verify.or(a.insecure_skip_verify);
+                a.insecure_skip_verify_confirmed = b
+                    .insecure_skip_verify_confirmed
+                    .or(a.insecure_skip_verify_confirmed);
                 a.update_interval = b.update_interval.or(a.update_interval);
                 a.merge = b.merge.or(a.merge);
                 a.script = b.script.or(a.script);
@@ -127,6 +160,225 @@ impl PrfOption {
             t => t.0.or(t.1),
         }
     }
+
+    /// 是否要求跳过证书校验，旧版本的 `danger_accept_invalid_certs` 与 `insecure_skip_verify` 等价
+    fn wants_skip_verify(&self) -> bool {
+        self.danger_accept_invalid_certs.unwrap_or(false)
+            || self.insecure_skip_verify.unwrap_or(false)
+    }
+
+    /// 旧版本保存的 `danger_accept_invalid_certs` 在加入确认之前就已经生效，读取时视为已确认
+    pub fn migrate_legacy_skip_verify(&mut self) {
+        if self.danger_accept_invalid_certs == Some(true)
+            && self.insecure_skip_verify_confirmed.is_none()
+        {
+            self.insecure_skip_verify_confirmed = Some(true);
+        }
+    }
+
+    /// 是否跳过证书校验，新设置的两种写法都需要经过确认才生效
+    pub fn skip_verify(&self) -> bool {
+        self.wants_skip_verify() && self.insecure_skip_verify_confirmed.unwrap_or(false)
+    }
+
+    /// 检查TLS相关配置
+    /// 在保存时调用，以便证书错误尽早暴露而不是等到定时更新时
+    pub fn check_tls(&self) -> Result<()> {
+        if self.wants_skip_verify() && !self.insecure_skip_verify_confirmed.unwrap_or(false) {
+            bail!("skipping certificate verification requires explicit confirmation");
+        }
+
+        if let Some(ca) = self.custom_ca_pem.as_ref() {
+            let certs = reqwest::Certificate::from_pem_bundle(&read_pem(ca)?)
+                .context("failed to parse the custom CA certificate")?;
+            if certs.is_empty() {
+                bail!("the custom CA does not contain any certificate");
+            }
+        }
+
+        match (self.client_cert.as_ref(), self.client_key.as_ref()) {
+            (Some(cert), Some(key)) => {
+                load_identity(cert, key)?;
+            }
+            (None, None) => {}
+            _ => bail!("`client_cert` and `client_key` must be set together"),
+        }
+
+        Ok(())
+    }
+
+    /// 下载订阅使用的请求客户端，所有订阅请求都通过这里创建
+    /// 按订阅的设置使用软件自己的代理或系统代理，并应用TLS相关配置
+    pub fn fetch_client(
+        option: Option<&Self>,
+        user_agent: Option<String>,
+    ) -> Result<reqwest::Client> {
+        let mut builder = reqwest::ClientBuilder::new().use_rustls_tls().no_proxy();
+        let with_proxy = option.map_or(false, |o| o.with_proxy.unwrap_or(false));
+        let self_proxy = option.map_or(false, |o| o.self_proxy.unwrap_or(false));
+
+        // 使用软件自己的代理
+        if self_proxy {
+            let port = Config::verge()
+                .latest()
+                .verge_mixed_port
+                .unwrap_or(Config::clash().data().get_mixed_port());
+
+            let proxy_scheme = format!("http://127.0.0.1:{port}");
+
+            if let Ok(proxy) = reqwest::Proxy::http(&proxy_scheme) {
+                builder = builder.proxy(proxy);
+            }
+            if let Ok(proxy) = reqwest::Proxy::https(&proxy_scheme) {
+                builder = builder.proxy(proxy);
+            }
+            if let Ok(proxy) = reqwest::Proxy::all(&proxy_scheme) {
+                builder = builder.proxy(proxy);
+            }
+        }
+        // 使用系统代理
+        else if with_proxy {
+            if let Ok(p @ Sysproxy { enable: true, .. }) = Sysproxy::get_system_proxy() {
+                let proxy_scheme = format!("http://{}:{}", p.host, p.port);
+
+                if let Ok(proxy) = reqwest::Proxy::http(&proxy_scheme) {
+                    builder = builder.proxy(proxy);
+                }
+                if let Ok(proxy) = reqwest::Proxy::https(&proxy_scheme) {
+                    builder = builder.proxy(proxy);
+                }
+                if let Ok(proxy) = reqwest::Proxy::all(&proxy_scheme) {
+                    builder = builder.proxy(proxy);
+                }
+            }
+        }
+
+        let version = match VERSION.get() {
+            Some(v) => format!("clash-verge/v{}", v),
+            None => "clash-verge/unknown".to_string(),
+        };
+
+        if let Some(option) = option {
+            builder = option.apply_tls(builder)?;
+        }
+        builder = builder.user_agent(user_agent.unwrap_or(version));
+        Ok(builder.build()?)
+    }
+
+    /// 将TLS相关配置应用到请求客户端
+    fn apply_tls(
+        &self,
+        mut builder: reqwest::ClientBuilder,
+    ) -> Result<reqwest::ClientBuilder> {
+        if let Some(ca) = self.custom_ca_pem.as_ref() {
+            let certs = reqwest::Certificate::from_pem_bundle(&read_pem(ca)?)
+                .context("failed to parse the custom CA certificate")?;
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+
+        if let (Some(cert), Some(key)) = (self.client_cert.as_ref(), self.client_key.as_ref()) {
+            builder = builder.identity(load_identity(cert, key)?);
+        }
+
+        Ok(builder.danger_accept_invalid_certs(self.skip_verify()))
+    }
+
+    /// 是否设置了需要使用订阅自己的请求客户端的TLS配置
+    fn has_tls(&self) -> bool {
+        self.custom_ca_pem.is_some()
+            || (self.client_cert.is_some() && self.client_key.is_some())
+            || self.skip_verify()
+    }
+
+    /// 去掉TLS相关的配置，用于导出备份
+    pub fn strip_tls(&mut self) {
+        self.custom_ca_pem = None;
+        self.client_cert = None;
+        self.client_key = None;
+        self.danger_accept_invalid_certs = None;
+        self.insecure_skip_verify = None;
+        self.insecure_skip_verify_confirmed = None;
+    }
+}
+
+/// 下载订阅中的 http rule provider 到内核读取缓存的位置，失败时只记录日志
+async fn prefetch_rule_sets(client: &reqwest::Client, config: &Mapping) {
+    let Some(providers) = config.get("rule-providers").and_then(|v| v.as_mapping()) else {
+        return;
+    };
+    let home = match dirs::app_home_dir() {
+        Ok(home) => home,
+        Err(err) => {
+            log::warn!(target: "app", "failed to prefetch rule sets: {err}");
+            return;
+        }
+    };
+    let token = tokio_util::sync::CancellationToken::new();
+    for (name, provider) in providers {
+        let name = name.as_str().unwrap_or_default();
+        if provider.get("type").and_then(|v| v.as_str()) != Some("http") {
+            continue;
+        }
+        let Some(url) = provider.get("url").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let path = provider.get("path").and_then(|v| v.as_str());
+        let Some(target) = rule_set_cache_path(&home, url, path) else {
+            log::warn!(target: "app", "skip prefetching rule set {name}, unsafe path {path:?}");
+            continue;
+        };
+        if let Some(parent) = target.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let result = async {
+            let part = operation::download_file(client, url, &target, &token, |_, _| {}).await?;
+            fs::rename(part, &target)?;
+            anyhow::Ok(())
+        }
+        .await;
+        match result {
+            Ok(_) => log::info!(target: "app", "prefetched rule set {name}"),
+            Err(err) => log::warn!(target: "app", "failed to prefetch rule set {name}: {err}"),
+        }
+    }
+}
+
+/// rule provider 的缓存文件，与内核的规则相同：
+/// 设置了 `path` 时相对内核的工作目录，否则为 `rules/<url 的 md5>`
+/// 不在工作目录中的路径内核也不会读取，返回空
+fn rule_set_cache_path(home: &Path, url: &str, path: Option<&str>) -> Option<PathBuf> {
+    use md5::{Digest, Md5};
+
+    let relative = match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let hash = Md5::digest(url.as_bytes());
+            let hash = hash.iter().map(|b| format!("{b:02x}")).collect::<String>();
+            Path::new("rules").join(hash)
+        }
+    };
+    let safe = relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    safe.then(|| home.join(relative))
+}
+
+/// 读取PEM内容，支持文件路径或直接填写PEM文本
+fn read_pem(value: &str) -> Result<Vec<u8>> {
+    let value = value.trim();
+    if value.starts_with("-----BEGIN") {
+        return Ok(value.as_bytes().to_vec());
+    }
+    fs::read(value).with_context(|| format!("failed to read the pem file \"{value}\""))
+}
+
+fn load_identity(cert: &str, key: &str) -> Result<reqwest::Identity> {
+    let mut pem = read_pem(cert)?;
+    pem.push(b'\n');
+    pem.extend(read_pem(key)?);
+    reqwest::Identity::from_pem(&pem).context("failed to parse the client certificate or key")
 }
 
 impl PrfItem {
@@ -234,10 +486,6 @@ impl PrfItem {
         option: Option<PrfOption>,
     ) -> Result<PrfItem> {
         let opt_ref = option.as_ref();
-        let with_proxy = opt_ref.map_or(false, |o| o.with_proxy.unwrap_or(false));
-        let self_proxy = opt_ref.map_or(false, |o| o.self_proxy.unwrap_or(false));
-        let accept_invalid_certs =
-            opt_ref.map_or(false, |o| o.danger_accept_invalid_certs.unwrap_or(false));
         let user_agent = opt_ref.and_then(|o| o.user_agent.clone());
         let update_interval = opt_ref.and_then(|o| o.update_interval);
         let mut merge = opt_ref.and_then(|o| o.merge.clone());
@@ -245,53 +493,8 @@ impl PrfItem {
         let mut rules = opt_ref.and_then(|o| o.rules.clone());
         let mut proxies = opt_ref.and_then(|o| o.proxies.clone());
         let mut groups = opt_ref.and_then(|o| o.groups.clone());
-        let mut builder = reqwest::ClientBuilder::new().use_rustls_tls().no_proxy();
-
-        // 使用软件自己的代理
-        if self_proxy {
-            let port = Config::verge()
-                .latest()
-                .verge_mixed_port
-                .unwrap_or(Config::clash().data().get_mixed_port());
-
-            let proxy_scheme = format!("http://127.0.0.1:{port}");
-
-            if let Ok(proxy) = reqwest::Proxy::http(&proxy_scheme) {
-                builder = builder.proxy(proxy);
-            }
-            if let Ok(proxy) = reqwest::Proxy::https(&proxy_scheme) {
-                builder = builder.proxy(proxy);
-            }
-            if let Ok(proxy) = reqwest::Proxy::all(&proxy_scheme) {
-                builder = builder.proxy(proxy);
-            }
-        }
-        // 使用系统代理
-        else if with_proxy {
-            if let Ok(p @ Sysproxy { enable: true, .. }) = Sysproxy::get_system_proxy() {
-                let proxy_scheme = format!("http://{}:{}", p.host, p.port);
-
-                if let Ok(proxy) = reqwest::Proxy::http(&proxy_scheme) {
-                    builder = builder.proxy(proxy);
-                }
-                if let Ok(proxy) = reqwest::Proxy::https(&proxy_scheme) {
-                    builder = builder.proxy(proxy);
-                }
-                if let Ok(proxy) = reqwest::Proxy::all(&proxy_scheme) {
-                    builder = builder.proxy(proxy);
-                }
-            }
-        }
-
-        let version = match VERSION.get() {
-            Some(v) => format!("clash-verge/v{}", v),
-            None => "clash-verge/unknown".to_string(),
-        };
-
-        builder = builder.danger_accept_invalid_certs(accept_invalid_certs);
-        builder = builder.user_agent(user_agent.unwrap_or(version));
-
-        let resp = builder.build()?.get(url).send().await?;
+        let client = PrfOption::fetch_client(opt_ref, user_agent)?;
+        let resp = client.get(url).send().await?;
 
         let status_code = resp.status();
         if !StatusCode::is_success(&status_code) {
@@ -373,6 +576,11 @@ impl PrfItem {
             bail!("profile does not contain `proxies` or `proxy-providers`");
         }
 
+        // 内核下载 rule provider 时不会使用订阅的TLS配置，先用同样的客户端下载到缓存位置
+        if opt_ref.is_some_and(PrfOption::has_tls) {
+            prefetch_rule_sets(&client, &yaml).await;
+        }
+
         if merge.is_none() {
             let merge_item = PrfItem::from_merge(None)?;
             Config::profiles().data().append_item(merge_item.clone())?;
@@ -411,6 +619,12 @@ impl PrfItem {
             selected: None,
             extra,
             option: Some(PrfOption {
+                custom_ca_pem: opt_ref.and_then(|o| o.custom_ca_pem.clone()),
+                client_cert: opt_ref.and_then(|o| o.client_cert.clone()),
+                client_key: opt_ref.and_then(|o| o.client_key.clone()),
+                insecure_skip_verify: opt_ref.and_then(|o| o.insecure_skip_verify),
+                insecure_skip_verify_confirmed: opt_ref
+                    .and_then(|o| o.insecure_skip_verify_confirmed),
                 update_interval,
                 merge,
                 script,
@@ -559,6 +773,56 @@ impl PrfItem {
 
         let file = self.file.clone().unwrap();
         let path = dirs::app_profiles_dir()?.join(file);
+        ProfileWatcher::global().note_own_write(&path);
         fs::write(path, data.as_bytes()).context("failed to save the file")
     }
 }
+
+#[test]
+fn test_skip_verify() {
+    let option = |legacy, insecure, confirmed| PrfOption {
+        danger_accept_invalid_certs: legacy,
+        insecure_skip_verify: insecure,
+        insecure_skip_verify_confirmed: confirmed,
+        ..PrfOption::default()
+    };
+
+    // 新设置的两种写法都需要确认后才跳过证书校验
+    assert!(!option(Some(true), None, None).skip_verify());
+    assert!(option(Some(true), None, None).check_tls().is_err());
+    assert!(!option(None, Some(true), Some(false)).skip_verify());
+    assert!(option(None, Some(true), None).check_tls().is_err());
+    assert!(option(Some(true), None, Some(true)).skip_verify());
+    assert!(option(None, Some(true), Some(true)).skip_verify());
+    assert!(option(None, Some(true), Some(true)).check_tls().is_ok());
+
+    // 只有确认标记时不跳过
+    assert!(!option(None, None, Some(true)).skip_verify());
+    assert!(option(None, None, None).check_tls().is_ok());
+
+    // 已保存的旧配置读取时视为已确认，继续跳过证书校验
+    let mut legacy = option(Some(true), None, None);
+    legacy.migrate_legacy_skip_verify();
+    assert!(legacy.skip_verify());
+    assert!(legacy.check_tls().is_ok());
+
+    // 明确取消过确认的配置不会被迁移
+    let mut revoked = option(Some(true), None, Some(false));
+    revoked.migrate_legacy_skip_verify();
+    assert!(!revoked.skip_verify());
+}
+
+#[test]
+fn test_rule_set_cache_path() {
+    let home = Path::new("/home/user/.verge");
+    assert_eq!(
+        rule_set_cache_path(home, "https://example.com/reject.yaml", Some("./ruleset/reject.yaml")),
+        Some(home.join("./ruleset/reject.yaml"))
+    );
+    assert_eq!(
+        rule_set_cache_path(home, "https://example.com/", None),
+        Some(home.join("rules").join("182ccedb33a9e03fbf1079b209da1a31"))
+    );
+    assert_eq!(rule_set_cache_path(home, "https://example.com/", Some("../evil")), None);
+    assert_eq!(rule_set_cache_path(home, "https://example.com/", Some("/etc/passwd")), None);
+}
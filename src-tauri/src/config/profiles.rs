@@ -1,4 +1,5 @@
 use super::{prfitem::PrfItem, PrfOption};
+use crate::core::profile_watcher::ProfileWatcher;
 use crate::utils::{dirs, help};
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
@@ -27,17 +28,7 @@ impl IProfiles {
     pub fn new() -> Self {
         match dirs::profiles_path().and_then(|path| help::read_yaml::<Self>(&path)) {
             Ok(mut profiles) => {
-                if profiles.items.is_none() {
-                    profiles.items = Some(vec![]);
-                }
-                // compatible with the old old old version
-                if let Some(items) = profiles.items.as_mut() {
-                    for item in items.iter_mut() {
-                        if item.uid.is_none() {
-                            item.uid = Some(help::get_uid("d"));
-                        }
-                    }
-                }
+                profiles.upgrade_items();
                 profiles
             }
             Err(err) => {
@@ -47,6 +38,24 @@ impl IProfiles {
         }
     }
 
+    /// 读取后兼容旧版本保存的订阅
+    pub fn upgrade_items(&mut self) {
+        if self.items.is_none() {
+            self.items = Some(vec![]);
+        }
+        // compatible with the old old old version
+        if let Some(items) = self.items.as_mut() {
+            for item in items.iter_mut() {
+                if item.uid.is_none() {
+                    item.uid = Some(help::get_uid("d"));
+                }
+                if let Some(option) = item.option.as_mut() {
+                    option.migrate_legacy_skip_verify();
+                }
+            }
+        }
+    }
+
     pub fn template() -> Self {
         Self {
             items: Some(vec![]),
@@ -88,6 +97,32 @@ impl IProfiles {
         self.items.as_ref()
     }
 
+    /// 当前订阅前后相邻的可激活订阅（remote/local），到两端时循环
+    pub fn neighbor_profile(&self, forward: bool) -> Option<String> {
+        let uids = self
+            .items
+            .as_ref()?
+            .iter()
+            .filter(|item| matches!(item.itype.as_deref(), Some("remote" | "local")))
+            .filter_map(|item| item.uid.clone())
+            .collect::<Vec<_>>();
+        if uids.is_empty() {
+            return None;
+        }
+
+        let len = uids.len();
+        let current = self
+            .current
+            .as_ref()
+            .and_then(|current| uids.iter().position(|uid| uid == current));
+        let index = match current {
+            Some(index) if forward => (index + 1) % len,
+            Some(index) => (index + len - 1) % len,
+            None => 0,
+        };
+        Some(uids[index].clone())
+    }
+
     /// find the item by the uid
     pub fn get_item(&self, uid: &String) -> Result<&PrfItem> {
         if let Some(items) = self.items.as_ref() {
@@ -121,6 +156,7 @@ impl IProfiles {
 
             let file = item.file.clone().unwrap();
             let path = dirs::app_profiles_dir()?.join(&file);
+            ProfileWatcher::global().note_own_write(&path);
 
             fs::File::create(path)
                 .with_context(|| format!("failed to create file \"{}\"", file))?
@@ -224,6 +260,7 @@ impl IProfiles {
                         each.file = Some(file.clone());
 
                         let path = dirs::app_profiles_dir()?.join(&file);
+                        ProfileWatcher::global().note_own_write(&path);
 
                         fs::File::create(path)
                             .with_context(|| format!("failed to create file \"{}\"", file))?
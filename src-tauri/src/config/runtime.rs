@@ -1,6 +1,7 @@
 use crate::enhance::field::use_keys;
 use serde::{Deserialize, Serialize};
 use serde_yaml::{Mapping, Value};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
 pub struct IRuntime {
@@ -9,6 +10,12 @@ pub struct IRuntime {
     // 这些keys不一定都生效
     pub exists_keys: Vec<String>,
     pub chain_logs: HashMap<String, Vec<(String, String)>>,
+    /// 已推送给内核的配置的哈希，内核重启后失效
+    #[serde(skip)]
+    pub applied_hash: Option<String>,
+    /// 本次启动内核使用的控制接口密钥，只通过命令提供给前端，不写入日志
+    #[serde(skip)]
+    pub secret: Option<String>,
 }
 
 impl IRuntime {
@@ -16,6 +23,54 @@ impl IRuntime {
         Self::default()
     }
 
+    /// 同时更新运行时配置中的 `secret`
+    pub fn set_secret(&mut self, secret: String) {
+        if let Some(config) = self.config.as_mut() {
+            config.insert("secret".into(), secret.clone().into());
+        }
+        self.secret = Some(secret);
+    }
+
+    /// 提供给前端查看或导出的配置，密钥替换为占位符
+    pub fn redacted_config(&self) -> Option<Mapping> {
+        let mut config = self.config.clone()?;
+        if config.contains_key("secret") {
+            config.insert("secret".into(), "<redacted>".into());
+        }
+        Some(config)
+    }
+
+    /// 生成的配置没有开启内核的 `store-selected` 时，由应用回放保存的节点选择
+    /// 两者只有一个生效，切换订阅后选择只会被恢复一次
+    pub fn selection_replay(&self) -> bool {
+        let store_selected = self
+            .config
+            .as_ref()
+            .and_then(|config| config.get("profile"))
+            .and_then(|profile| profile.get("store-selected"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        !store_selected
+    }
+
+    /// 配置的 SHA-256，用于判断重新生成的配置是否变化
+    /// 生成配置时顶层字段的顺序不固定，按字段排序后再计算
+    pub fn config_hash(&self) -> Option<String> {
+        let config = self.config.as_ref()?;
+        let mut entries = config
+            .iter()
+            .map(|(key, value)| Some((serde_yaml::to_string(key).ok()?, value)))
+            .collect::<Option<Vec<_>>>()?;
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut hasher = Sha256::new();
+        for (key, value) in entries {
+            hasher.update(key);
+            hasher.update(serde_yaml::to_string(value).ok()?);
+        }
+        Some(format!("{:x}", hasher.finalize()))
+    }
+
     // 这里只更改 allow-lan | ipv6 | log-level | tun
     pub fn patch_config(&mut self, patch: Mapping) {
         if let Some(config) = self.config.as_mut() {
@@ -47,3 +102,63 @@ impl IRuntime {
         }
     }
 }
+
+#[test]
+fn test_config_hash() {
+    let runtime = |yaml: &str| IRuntime {
+        config: Some(serde_yaml::from_str(yaml).unwrap()),
+        ..IRuntime::default()
+    };
+
+    let a = runtime("mixed-port: 7897\nmode: rule\ntun:\n  enable: true\n");
+    let b = runtime("mode: rule\ntun:\n  enable: true\nmixed-port: 7897\n");
+    let c = runtime("mode: rule\ntun:\n  enable: false\nmixed-port: 7897\n");
+
+    assert!(a.config_hash().is_some());
+    assert_eq!(a.config_hash(), b.config_hash());
+    assert_ne!(a.config_hash(), c.config_hash());
+    assert_eq!(IRuntime::default().config_hash(), None);
+}
+
+#[test]
+fn test_set_secret() {
+    let mut runtime = IRuntime {
+        config: Some(serde_yaml::from_str("secret: \"\"\nmode: rule\n").unwrap()),
+        ..IRuntime::default()
+    };
+    runtime.set_secret("abc".into());
+    assert_eq!(runtime.secret.as_deref(), Some("abc"));
+    let config = runtime.config.as_ref().unwrap();
+    assert_eq!(config.get("secret").and_then(Value::as_str), Some("abc"));
+
+    // 密钥不会随运行时状态一起序列化
+    let yaml = serde_yaml::to_string(&IRuntime {
+        config: None,
+        ..runtime
+    })
+    .unwrap();
+    assert!(!yaml.contains("abc"));
+
+    let mut empty = IRuntime::default();
+    empty.set_secret("abc".into());
+    assert!(empty.config.is_none());
+}
+
+#[test]
+fn test_redacted_config() {
+    let mut runtime = IRuntime {
+        config: Some(serde_yaml::from_str("secret: \"\"\nmode: rule\n").unwrap()),
+        ..IRuntime::default()
+    };
+    runtime.set_secret("abc".into());
+    let config = runtime.redacted_config().unwrap();
+    assert_eq!(config.get("secret").and_then(Value::as_str), Some("<redacted>"));
+    assert_eq!(config.get("mode").and_then(Value::as_str), Some("rule"));
+
+    let runtime = IRuntime {
+        config: Some(serde_yaml::from_str("mode: rule\n").unwrap()),
+        ..IRuntime::default()
+    };
+    assert!(!runtime.redacted_config().unwrap().contains_key("secret"));
+    assert!(IRuntime::default().redacted_config().is_none());
+}
@@ -102,6 +102,9 @@ pub struct IVerge {
     /// enable global hotkey
     pub enable_global_hotkey: Option<bool>,
 
+    /// 全局热键关闭时，热键只在主窗口获得焦点时注册和执行，默认关闭
+    pub enable_in_app_hotkey: Option<bool>,
+
     /// 切换代理时自动关闭连接
     pub auto_close_connection: Option<bool>,
 
@@ -184,6 +187,77 @@ pub struct IVerge {
 
     /// 轻量模式 - 只保留内核运行
     pub enable_lite_mode: Option<bool>,
+
+    /// 内核的 `profile.store-selected`，由内核自行保存并恢复策略组的选择
+    /// 开启时不再由应用回放节点选择，见 `IRuntime::selection_replay`
+    /// 未设置时沿用订阅自身的设置
+    pub core_store_selected: Option<bool>,
+
+    /// 内核的 `profile.store-fake-ip`，重启后保留 fake-ip 映射
+    pub core_store_fake_ip: Option<bool>,
+
+    /// 切换订阅后由应用回放保存的节点选择
+    /// 开启时生成配置会关闭内核的 `store-selected`，默认为假
+    pub prefer_selection_replay: Option<bool>,
+
+    /// 内核自身流量使用的上游代理，如 http://127.0.0.1:8080
+    /// 通过 HTTPS_PROXY/ALL_PROXY 传给 sidecar 进程
+    pub upstream_proxy: Option<String>,
+
+    /// 等待内核控制接口就绪的超时时间（秒），默认 10 秒
+    pub core_startup_timeout: Option<u64>,
+
+    /// 验证配置的超时时间（秒），默认 20 秒
+    pub core_validate_timeout: Option<u64>,
+
+    /// 合并配置更新请求的等待时间（毫秒），默认 200 毫秒
+    pub config_update_debounce: Option<u64>,
+
+    /// 订阅及其扩展文件在外部修改后自动重新加载配置，默认开启
+    pub enable_profile_hot_reload: Option<bool>,
+
+    /// 上次异常退出后遗留的内核的处理方式，默认为 restart
+    /// restart: 结束遗留的内核后重新启动
+    /// adopt: 控制接口可用时继续使用遗留的内核
+    pub orphan_core_action: Option<String>,
+
+    /// 内核端口被其他程序占用时的处理方式，默认为 fail
+    /// fail: 不启动内核并提示占用端口的进程
+    /// auto: 自动换用附近的空闲端口
+    pub port_conflict_action: Option<String>,
+
+    /// 启动内核时追加的参数，验证配置（-t）时同样使用
+    pub clash_core_extra_args: Option<Vec<String>>,
+
+    /// 只在运行内核时追加的参数，验证配置时不使用，如 -ext-ctl-unix
+    pub clash_core_run_only_args: Option<Vec<String>>,
+
+    /// 快捷键切换系统代理、TUN 模式后发送系统通知，默认开启
+    pub enable_hotkey_notification: Option<bool>,
+
+    /// 同一个热键两次触发的最小间隔（毫秒），忽略按住按键时的重复触发，默认 300
+    pub hotkey_debounce_ms: Option<u64>,
+
+    /// 策略组延迟测试的并发数，默认为 8
+    pub delay_test_concurrency: Option<usize>,
+
+    /// DNS 配置变化后自动清空内核的 fake-ip 缓存，已有的 fake-ip 映射会失效，默认关闭
+    pub auto_flush_fakeip: Option<bool>,
+
+    /// 控制接口的连接方式，`tcp`（默认）或 `socket`
+    /// `socket` 在 macOS/Linux 上为 unix socket，Windows 上为命名管道
+    pub external_controller_transport: Option<String>,
+
+    /// 固定的控制接口密钥，为空时每次启动内核使用新的随机密钥
+    pub external_controller_secret: Option<String>,
+
+    /// 切换代理时关闭哪些连接，默认为 group
+    /// group: 代理链经过切换的策略组的连接
+    /// all: 所有连接
+    pub auto_close_connection_scope: Option<String>,
+
+    /// 重启内核时总是结束进程后重新启动，不使用内核的 POST /restart，用于排查问题
+    pub force_full_core_restart: Option<bool>,
 }
 
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
@@ -369,6 +443,27 @@ impl IVerge {
         patch!(webdav_password);
         patch!(enable_tray_speed);
         patch!(enable_lite_mode);
+        patch!(core_store_selected);
+        patch!(core_store_fake_ip);
+        patch!(prefer_selection_replay);
+        patch!(upstream_proxy);
+        patch!(core_startup_timeout);
+        patch!(core_validate_timeout);
+        patch!(config_update_debounce);
+        patch!(enable_profile_hot_reload);
+        patch!(orphan_core_action);
+        patch!(port_conflict_action);
+        patch!(clash_core_extra_args);
+        patch!(clash_core_run_only_args);
+        patch!(enable_in_app_hotkey);
+        patch!(enable_hotkey_notification);
+        patch!(hotkey_debounce_ms);
+        patch!(delay_test_concurrency);
+        patch!(auto_flush_fakeip);
+        patch!(external_controller_transport);
+        patch!(external_controller_secret);
+        patch!(auto_close_connection_scope);
+        patch!(force_full_core_restart);
     }
 
     /// 在初始化前尝试拿到单例端口的值
@@ -380,6 +475,16 @@ impl IVerge {
         SERVER_PORT
     }
 
+    /// 生成配置时写入的 `store-selected`，为空时不改动订阅中的值
+    /// 用户选择其中一种后，另一种在生成配置时关闭，避免同时开启时互相覆盖
+    /// 最终由谁恢复选择以生成的配置为准，见 `IRuntime::selection_replay`
+    pub fn selection_store(&self) -> Option<bool> {
+        if self.prefer_selection_replay.unwrap_or(false) {
+            return Some(false);
+        }
+        self.core_store_selected
+    }
+
     /// 获取日志等级
     pub fn get_log_level(&self) -> LevelFilter {
         if let Some(level) = self.app_log_level.as_ref() {
@@ -458,6 +563,27 @@ pub struct IVergeResponse {
     pub webdav_password: Option<String>,
     pub enable_tray_speed: Option<bool>,
     pub enable_lite_mode: Option<bool>,
+    pub core_store_selected: Option<bool>,
+    pub core_store_fake_ip: Option<bool>,
+    pub prefer_selection_replay: Option<bool>,
+    pub upstream_proxy: Option<String>,
+    pub core_startup_timeout: Option<u64>,
+    pub core_validate_timeout: Option<u64>,
+    pub config_update_debounce: Option<u64>,
+    pub enable_profile_hot_reload: Option<bool>,
+    pub orphan_core_action: Option<String>,
+    pub port_conflict_action: Option<String>,
+    pub clash_core_extra_args: Option<Vec<String>>,
+    pub clash_core_run_only_args: Option<Vec<String>>,
+    pub enable_in_app_hotkey: Option<bool>,
+    pub enable_hotkey_notification: Option<bool>,
+    pub hotkey_debounce_ms: Option<u64>,
+    pub delay_test_concurrency: Option<usize>,
+    pub auto_flush_fakeip: Option<bool>,
+    pub external_controller_transport: Option<String>,
+    pub external_controller_secret: Option<String>,
+    pub auto_close_connection_scope: Option<String>,
+    pub force_full_core_restart: Option<bool>,
 }
 
 impl From<IVerge> for IVergeResponse {
@@ -521,6 +647,27 @@ impl From<IVerge> for IVergeResponse {
             webdav_password: verge.webdav_password,
             enable_tray_speed: verge.enable_tray_speed,
             enable_lite_mode: verge.enable_lite_mode,
+            core_store_selected: verge.core_store_selected,
+            core_store_fake_ip: verge.core_store_fake_ip,
+            prefer_selection_replay: verge.prefer_selection_replay,
+            upstream_proxy: verge.upstream_proxy,
+            core_startup_timeout: verge.core_startup_timeout,
+            core_validate_timeout: verge.core_validate_timeout,
+            config_update_debounce: verge.config_update_debounce,
+            enable_profile_hot_reload: verge.enable_profile_hot_reload,
+            orphan_core_action: verge.orphan_core_action,
+            port_conflict_action: verge.port_conflict_action,
+            clash_core_extra_args: verge.clash_core_extra_args,
+            clash_core_run_only_args: verge.clash_core_run_only_args,
+            enable_in_app_hotkey: verge.enable_in_app_hotkey,
+            enable_hotkey_notification: verge.enable_hotkey_notification,
+            hotkey_debounce_ms: verge.hotkey_debounce_ms,
+            delay_test_concurrency: verge.delay_test_concurrency,
+            auto_flush_fakeip: verge.auto_flush_fakeip,
+            external_controller_transport: verge.external_controller_transport,
+            external_controller_secret: verge.external_controller_secret,
+            auto_close_connection_scope: verge.auto_close_connection_scope,
+            force_full_core_restart: verge.force_full_core_restart,
         }
     }
 }
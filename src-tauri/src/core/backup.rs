@@ -1,4 +1,4 @@
-use crate::config::Config;
+use crate::config::{Config, IProfiles};
 use crate::utils::dirs;
 use anyhow::Error;
 use once_cell::sync::OnceCell;
@@ -186,7 +186,9 @@ impl WebDavClient {
     }
 }
 
-pub fn create_backup() -> Result<(String, PathBuf), Error> {
+/// 创建备份压缩包
+/// `include_tls_options` 为 false 时，订阅中的证书和私钥等TLS配置不会被导出
+pub fn create_backup(include_tls_options: bool) -> Result<(String, PathBuf), Error> {
     let now = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
     let zip_file_name = format!("{}-backup-{}.zip", OS, now);
     let zip_path = temp_dir().join(&zip_file_name);
@@ -219,8 +221,62 @@ pub fn create_backup() -> Result<(String, PathBuf), Error> {
     zip.start_file(dirs::VERGE_CONFIG, options)?;
     zip.write_all(serde_yaml::to_string(&verge_config)?.as_bytes())?;
 
+    let profiles: IProfiles =
+        serde_yaml::from_str(&fs::read_to_string(dirs::profiles_path()?)?)?;
     zip.start_file(dirs::PROFILE_YAML, options)?;
-    zip.write_all(fs::read(dirs::profiles_path()?)?.as_slice())?;
+    zip.write_all(backup_profiles(profiles, include_tls_options)?.as_bytes())?;
     zip.finish()?;
     Ok((zip_file_name, zip_path))
 }
+
+/// 备份中的订阅列表，不包含TLS配置时去掉证书、私钥和跳过证书校验的设置
+fn backup_profiles(mut profiles: IProfiles, include_tls_options: bool) -> Result<String, Error> {
+    if !include_tls_options {
+        profiles
+            .items
+            .iter_mut()
+            .flatten()
+            .filter_map(|item| item.option.as_mut())
+            .for_each(|option| option.strip_tls());
+    }
+    Ok(serde_yaml::to_string(&profiles)?)
+}
+
+#[test]
+fn test_backup_without_tls_options() -> Result<(), Error> {
+    use crate::config::{PrfItem, PrfOption};
+
+    let option = PrfOption {
+        danger_accept_invalid_certs: Some(true),
+        insecure_skip_verify: Some(true),
+        insecure_skip_verify_confirmed: Some(true),
+        custom_ca_pem: Some("/path/to/ca.pem".into()),
+        ..PrfOption::default()
+    };
+    let profiles = IProfiles {
+        current: Some("r1".into()),
+        items: Some(vec![PrfItem {
+            uid: Some("r1".into()),
+            option: Some(option),
+            ..PrfItem::default()
+        }]),
+    };
+
+    // 恢复时按读取订阅列表的方式处理，跳过证书校验不会被重新开启
+    let yaml = backup_profiles(profiles.clone(), false)?;
+    let mut restored: IProfiles = serde_yaml::from_str(&yaml)?;
+    restored.upgrade_items();
+    let option = restored.get_item(&"r1".to_string())?.option.clone().unwrap_or_default();
+    assert!(!option.skip_verify());
+    assert_eq!(option.danger_accept_invalid_certs, None);
+    assert_eq!(option.insecure_skip_verify_confirmed, None);
+    assert_eq!(option.custom_ca_pem, None);
+
+    // 选择包含TLS配置时原样保留
+    let yaml = backup_profiles(profiles, true)?;
+    let mut restored: IProfiles = serde_yaml::from_str(&yaml)?;
+    restored.upgrade_items();
+    let option = restored.get_item(&"r1".to_string())?.option.clone().unwrap_or_default();
+    assert!(option.skip_verify());
+    Ok(())
+}
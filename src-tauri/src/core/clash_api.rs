@@ -1,9 +1,20 @@
-use crate::config::Config;
-use anyhow::{bail, Result};
-use reqwest::header::HeaderMap;
+use crate::config::{Config, PrfSelected};
+use crate::core::{controller, handle, operation};
+use crate::utils::help;
+use anyhow::{anyhow, bail, Context, Result};
+use futures::StreamExt;
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use percent_encoding::NON_ALPHANUMERIC;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Mapping;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::async_runtime::JoinHandle;
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::Message;
 
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct Rate {
@@ -11,85 +22,1264 @@ pub struct Rate {
     pub down: u64,
 }
 
+/// 内核重新加载配置时需要初始化 provider 等，比普通请求慢
+const CONFIG_RELOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// PUT /configs
-/// path 是绝对路径
+/// path 是绝对路径，只在连接失败时重试
+/// 超时的重新加载可能仍在进行，重发会让调用方等待数倍的超时
 pub async fn put_configs(path: &str) -> Result<()> {
-    let (url, headers) = clash_client_info()?;
-    let url = format!("{url}/configs?force=true");
-
     let mut data = HashMap::new();
     data.insert("path", path);
 
-    let client = reqwest::ClientBuilder::new().no_proxy().build()?;
-    let builder = client.put(&url).headers(headers).json(&data);
-    let response = builder.send().await?;
+    controller::put("/configs?force=true")
+        .json(&data)
+        .timeout(CONFIG_RELOAD_TIMEOUT)
+        .connect_retries_only()
+        .send()
+        .await?
+        .error_for_status()?;
+    invalidate_rules_cache();
+    invalidate_proxies_cache();
+    Ok(())
+}
+
+/// PATCH /configs
+pub async fn patch_configs(config: &Mapping) -> Result<()> {
+    patch_configs_with_timeout(config, controller::CONTROL_TIMEOUT).await
+}
+
+/// PATCH /configs
+/// 停止内核等场景使用很短的超时，避免等待已经无响应的内核
+pub async fn patch_configs_with_timeout(config: &Mapping, timeout: Duration) -> Result<()> {
+    controller::patch("/configs")
+        .json(config)
+        .timeout(timeout)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// GET /configs
+/// 获取内核当前的代理模式
+pub async fn get_mode() -> Result<String> {
+    let response = controller::get("/configs").send().await?.error_for_status()?;
+    let configs = response.json::<serde_json::Value>()?;
+    match configs["mode"].as_str() {
+        Some(mode) => Ok(mode.to_lowercase()),
+        None => bail!("failed to get mode from the core configs"),
+    }
+}
+
+/// GET /configs
+/// 获取内核当前的基础配置，保留内核返回的原始字段
+pub async fn get_configs() -> Result<serde_json::Value> {
+    let response = controller::get("/configs").send().await?.error_for_status()?;
+    response.json::<serde_json::Value>()
+}
+
+/// GET /connections
+/// 获取当前的连接列表，保留内核返回的原始字段
+pub async fn get_connections_raw() -> Result<Vec<serde_json::Value>> {
+    let response = controller::get("/connections").send().await?.error_for_status()?;
+    let mut snapshot = response.json::<serde_json::Value>()?;
+    match snapshot["connections"].take() {
+        serde_json::Value::Array(connections) => Ok(connections),
+        _ => Ok(vec![]),
+    }
+}
+
+/// GET /connections
+/// 获取当前连接的快照
+pub async fn get_connections() -> Result<ConnectionsSnapshot> {
+    let response = controller::get("/connections").send().await?.error_for_status()?;
+    response.json::<ConnectionsSnapshot>()
+}
+
+/// 内核没有连接时返回 `null`
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionsSnapshot {
+    #[serde(default)]
+    pub download_total: u64,
+    #[serde(default)]
+    pub upload_total: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub connections: Vec<Connection>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Connection {
+    pub id: String,
+    #[serde(default)]
+    pub metadata: ConnectionMetadata,
+    #[serde(default)]
+    pub upload: u64,
+    #[serde(default)]
+    pub download: u64,
+    /// 开始时间，RFC 3339 格式
+    #[serde(default)]
+    pub start: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub chains: Vec<String>,
+    #[serde(default)]
+    pub rule: String,
+    #[serde(default)]
+    pub rule_payload: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionMetadata {
+    #[serde(default)]
+    pub network: String,
+    #[serde(default, rename = "type")]
+    pub conn_type: String,
+    #[serde(default, rename = "sourceIP")]
+    pub source_ip: String,
+    #[serde(default)]
+    pub source_port: String,
+    #[serde(default, rename = "destinationIP")]
+    pub destination_ip: String,
+    #[serde(default)]
+    pub destination_port: String,
+    #[serde(default)]
+    pub host: String,
+    #[serde(default)]
+    pub remote_destination: String,
+    #[serde(default)]
+    pub process: String,
+    #[serde(default)]
+    pub process_path: String,
+}
+
+/// 按条件筛选后的连接，`total` 为截断前的数量
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionsPage {
+    pub total: usize,
+    pub truncated: bool,
+    pub download_total: u64,
+    pub upload_total: u64,
+    pub connections: Vec<Connection>,
+}
+
+impl Connection {
+    /// 域名、目标 IP 或代理链中包含 `filter`，不区分大小写
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        let metadata = &self.metadata;
+        metadata.host.to_lowercase().contains(&filter)
+            || metadata.destination_ip.contains(&filter)
+            || self.chains.iter().any(|chain| chain.to_lowercase().contains(&filter))
+    }
+}
+
+impl ConnectionsSnapshot {
+    /// 按域名（没有域名时为目标 IP）包含的字符串筛选，可以按流量从大到小排序，最多返回 `limit` 个
+    pub fn query(self, host: Option<&str>, sort_by_traffic: bool, limit: usize) -> ConnectionsPage {
+        let host = host.map(str::trim).filter(|host| !host.is_empty()).map(str::to_lowercase);
+        let mut connections = self
+            .connections
+            .into_iter()
+            .filter(|conn| {
+                let Some(host) = host.as_deref() else {
+                    return true;
+                };
+                let metadata = &conn.metadata;
+                metadata.host.to_lowercase().contains(host)
+                    || metadata.destination_ip.contains(host)
+            })
+            .collect::<Vec<_>>();
+        if sort_by_traffic {
+            connections.sort_by_key(|conn| std::cmp::Reverse(conn.upload + conn.download));
+        }
+
+        let total = connections.len();
+        connections.truncate(limit);
+        ConnectionsPage {
+            total,
+            truncated: total > limit,
+            download_total: self.download_total,
+            upload_total: self.upload_total,
+            connections,
+        }
+    }
+}
+
+/// DELETE /connections
+/// 关闭所有连接，返回关闭前的连接数
+pub async fn close_all_connections() -> Result<usize> {
+    let response = controller::get("/connections").send().await?;
+    let snapshot = response.json::<serde_json::Value>()?;
+    let count = snapshot["connections"].as_array().map_or(0, |c| c.len());
+
+    controller::delete("/connections").send().await?.error_for_status()?;
+    Ok(count)
+}
+
+/// DELETE /connections/{id}
+/// 关闭一个连接，连接已经不存在（404）时视为成功
+pub async fn close_connection(id: &str) -> Result<()> {
+    let id = percent_encoding::utf8_percent_encode(id, NON_ALPHANUMERIC);
+    let response = controller::delete(format!("/connections/{id}")).send().await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(());
+    }
+    response.error_for_status()?;
+    Ok(())
+}
+
+/// 关闭域名或代理链包含 `filter` 的连接，返回关闭的数量
+pub async fn close_connections_matching(filter: &str) -> Result<usize> {
+    close_connections_where(|conn| conn.matches(filter)).await
+}
+
+/// 同时关闭连接的请求数
+const CLOSE_CONNECTIONS_CONCURRENCY: usize = 8;
+
+/// 获取连接快照后并发关闭满足 `predicate` 的连接，返回关闭的数量
+/// 已经断开的连接（404）视为关闭成功，单个失败不影响其他的，全部失败时返回错误
+pub async fn close_connections_where<F>(predicate: F) -> Result<usize>
+where
+    F: Fn(&Connection) -> bool,
+{
+    let snapshot = get_connections().await?;
+    let ids = snapshot
+        .connections
+        .iter()
+        .filter(|conn| predicate(conn))
+        .map(|conn| conn.id.clone())
+        .collect::<Vec<_>>();
+    let results = futures::stream::iter(ids.iter())
+        .map(|id| async move { close_connection(id).await })
+        .buffer_unordered(CLOSE_CONNECTIONS_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut closed = 0;
+    let mut first_err = None;
+    for result in results {
+        match result {
+            Ok(()) => closed += 1,
+            Err(err) => {
+                log::warn!(target: "app", "failed to close connection: {err}");
+                first_err.get_or_insert(err);
+            }
+        }
+    }
+    match first_err {
+        Some(err) if closed == 0 => Err(err),
+        _ => Ok(closed),
+    }
+}
+
+/// 内核中生效的规则
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RuleEntry {
+    #[serde(rename = "type")]
+    pub rule_type: String,
+    pub payload: String,
+    pub proxy: String,
+}
+
+#[derive(Deserialize)]
+struct RulesRes {
+    rules: Vec<RuleEntry>,
+}
+
+/// GET /rules
+pub async fn get_rules() -> Result<Vec<RuleEntry>> {
+    let response = controller::get("/rules").send().await?.error_for_status()?;
+    Ok(response.json::<RulesRes>()?.rules)
+}
+
+/// 规则只在应用配置后变化，短时间内的多次查询（如输入搜索词）复用上次的结果
+const RULES_CACHE_TTL: Duration = Duration::from_secs(3);
+
+static RULES_CACHE: Lazy<Mutex<Option<(Instant, Arc<Vec<RuleEntry>>)>>> =
+    Lazy::new(|| Mutex::new(None));
+
+/// GET /rules，带缓存
+pub async fn get_rules_cached() -> Result<Arc<Vec<RuleEntry>>> {
+    if let Some((fetched_at, rules)) = RULES_CACHE.lock().as_ref() {
+        if fetched_at.elapsed() < RULES_CACHE_TTL {
+            return Ok(rules.clone());
+        }
+    }
+    let rules = Arc::new(get_rules().await?);
+    *RULES_CACHE.lock() = Some((Instant::now(), rules.clone()));
+    Ok(rules)
+}
+
+/// 应用新配置或更新 rule provider 后清空缓存
+pub fn invalidate_rules_cache() {
+    RULES_CACHE.lock().take();
+}
+
+/// 带序号的规则，`index` 从 1 开始，是规则在完整列表中的位置
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexedRule {
+    pub index: usize,
+    #[serde(flatten)]
+    pub rule: RuleEntry,
+}
+
+/// 筛选后的一页规则，`total` 为全部规则数，`filtered` 为筛选后的数量
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RulesPage {
+    pub total: usize,
+    pub filtered: usize,
+    pub rules: Vec<IndexedRule>,
+}
+
+/// 类型、内容或目标策略包含 `query`，不区分大小写，返回从 `offset` 开始的最多 `limit` 条
+pub fn query_rules(
+    rules: &[RuleEntry],
+    query: Option<&str>,
+    offset: usize,
+    limit: usize,
+) -> RulesPage {
+    let query = query.map(str::trim).filter(|q| !q.is_empty()).map(str::to_lowercase);
+    let matched = rules
+        .iter()
+        .enumerate()
+        .filter(|(_, rule)| {
+            let Some(query) = query.as_deref() else {
+                return true;
+            };
+            rule.payload.to_lowercase().contains(query)
+                || rule.rule_type.to_lowercase().contains(query)
+                || rule.proxy.to_lowercase().contains(query)
+        })
+        .collect::<Vec<_>>();
+
+    RulesPage {
+        total: rules.len(),
+        filtered: matched.len(),
+        rules: matched
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(i, rule)| IndexedRule {
+                index: i + 1,
+                rule: rule.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// 代理页面会频繁轮询，短时间内的重复请求复用上次解析的结果
+const PROXIES_CACHE_TTL: Duration = Duration::from_millis(1500);
+
+struct CachedSnapshot {
+    /// 获取快照时的控制接口和密钥
+    key: String,
+    fetched_at: Instant,
+    value: Arc<serde_json::Value>,
+}
+
+/// 按请求路径缓存 `/proxies` 和 `/providers/proxies` 的快照
+static PROXIES_CACHE: Lazy<Mutex<HashMap<&'static str, CachedSnapshot>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 每次清空缓存时递增，避免清空前发出的请求把旧快照写回缓存
+static PROXIES_CACHE_GEN: AtomicU64 = AtomicU64::new(0);
+
+/// 内核以新的端口或密钥重启后，旧的快照自然不再命中
+fn snapshot_key() -> String {
+    let endpoint = controller::Endpoint::current();
+    format!("{:?}|{}", endpoint.transport, endpoint.secret.unwrap_or_default())
+}
+
+async fn get_snapshot(path: &'static str, fresh: bool) -> Result<Arc<serde_json::Value>> {
+    let key = snapshot_key();
+    if !fresh {
+        if let Some(cached) = PROXIES_CACHE.lock().get(path) {
+            if cached.key == key && cached.fetched_at.elapsed() < PROXIES_CACHE_TTL {
+                return Ok(cached.value.clone());
+            }
+        }
+    }
+
+    let generation = PROXIES_CACHE_GEN.load(Ordering::Acquire);
+    let response = controller::get(path).send().await?.error_for_status()?;
+    let value = Arc::new(response.json::<serde_json::Value>()?);
+
+    let mut cache = PROXIES_CACHE.lock();
+    if PROXIES_CACHE_GEN.load(Ordering::Acquire) == generation {
+        let snapshot = CachedSnapshot {
+            key,
+            fetched_at: Instant::now(),
+            value: value.clone(),
+        };
+        cache.insert(path, snapshot);
+    }
+    Ok(value)
+}
+
+/// GET /proxies，带缓存，`fresh` 为 true 时跳过缓存重新获取
+pub async fn get_proxies(fresh: bool) -> Result<Arc<serde_json::Value>> {
+    get_snapshot("/proxies", fresh).await
+}
+
+/// GET /providers/proxies，带缓存，保留内核返回的原始字段
+pub async fn get_providers_proxies(fresh: bool) -> Result<Arc<serde_json::Value>> {
+    get_snapshot("/providers/proxies", fresh).await
+}
+
+/// 应用配置、切换节点、更新 provider 或重启内核后清空
+pub fn invalidate_proxies_cache() {
+    let mut cache = PROXIES_CACHE.lock();
+    PROXIES_CACHE_GEN.fetch_add(1, Ordering::AcqRel);
+    cache.clear();
+}
+
+/// GET /proxies
+/// 返回策略组名称和其包含的节点
+pub async fn get_proxy_groups() -> Result<HashMap<String, Vec<String>>> {
+    let response = controller::get("/proxies").send().await?.error_for_status()?;
+    let res = response.json::<serde_json::Value>()?;
+
+    let groups = res["proxies"]
+        .as_object()
+        .map(|proxies| {
+            proxies
+                .iter()
+                .filter_map(|(name, proxy)| {
+                    let all = proxy["all"].as_array()?;
+                    let all = all.iter().filter_map(|p| p.as_str().map(String::from));
+                    Some((name.clone(), all.collect()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(groups)
+}
+
+/// PUT /proxies/{group}
+/// 切换策略组选中的节点
+pub async fn select_proxy(group: &str, name: &str) -> Result<()> {
+    let group = percent_encoding::utf8_percent_encode(group, NON_ALPHANUMERIC);
+    let path = format!("/proxies/{group}");
+
+    controller::put(&path)
+        .json(&serde_json::json!({ "name": name }))
+        .send()
+        .await?
+        .error_for_status()?;
+    invalidate_proxies_cache();
+    Ok(())
+}
+
+/// 重新应用保存的节点选择，返回生效的数量
+/// 订阅更新后已经不存在的策略组或节点直接跳过
+pub async fn apply_selected(selected: &[PrfSelected]) -> Result<usize> {
+    if selected.is_empty() {
+        return Ok(0);
+    }
+
+    let groups = get_proxy_groups().await?;
+    let mut count = 0;
+    for item in selected {
+        let (Some(group), Some(now)) = (item.name.as_ref(), item.now.as_ref()) else {
+            continue;
+        };
+        if !groups.get(group).is_some_and(|all| all.contains(now)) {
+            log::debug!(target: "app", "skip the selection \"{now}\" of group \"{group}\"");
+            continue;
+        }
+        match select_proxy(group, now).await {
+            Ok(_) => count += 1,
+            Err(err) => log::warn!(target: "app", "{err}"),
+        }
+    }
+    Ok(count)
+}
 
+/// 未设置 `default_latency_test` 时使用的测试地址
+pub const DEFAULT_TEST_URL: &str = "http://cp.cloudflare.com/generate_204";
+
+/// 未设置 `default_latency_timeout` 时使用的超时时间，单位毫秒
+pub const DEFAULT_TEST_TIMEOUT: u32 = 10000;
+
+/// 单个节点的延迟测试结果，超时或测试失败时 `delay` 为空且 `timeout` 为真
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProxyDelay {
+    pub name: String,
+    pub delay: Option<u64>,
+    pub timeout: bool,
+}
+
+impl ProxyDelay {
+    fn timeout(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            delay: None,
+            timeout: true,
+        }
+    }
+}
+
+/// 读取延迟测试的地址和超时时间，参数为空时使用 verge 配置中的默认值
+pub fn delay_test_options(url: Option<String>, timeout: Option<u32>) -> (String, u32) {
+    let verge = Config::verge();
+    let verge = verge.latest();
+    let url = url
+        .or(verge.default_latency_test.clone())
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or(DEFAULT_TEST_URL.into());
+    let timeout = timeout
+        .or(verge.default_latency_timeout.map(|t| t.max(0) as u32))
+        .filter(|t| *t > 0)
+        .unwrap_or(DEFAULT_TEST_TIMEOUT);
+    (url, timeout)
+}
+
+/// GET /proxies/{name}/delay
+/// 测试节点延迟，内核返回超时（503/504）时不视为错误
+pub async fn test_delay(
+    name: &str,
+    url: Option<String>,
+    timeout: Option<u32>,
+) -> Result<ProxyDelay> {
+    let (test_url, timeout) = delay_test_options(url, timeout);
+    let encoded = percent_encoding::utf8_percent_encode(name, NON_ALPHANUMERIC);
+
+    // 给内核留出返回超时结果的时间
+    let response = controller::get(format!("/proxies/{encoded}/delay"))
+        .query(&[("timeout", &format!("{timeout}")), ("url", &test_url)])
+        .timeout(Duration::from_millis(timeout as u64 + 2000))
+        .retries(0)
+        .send()
+        .await;
+
+    let response = match response {
+        Ok(response) => response,
+        Err(err) if controller::is_timeout(&err) => return Ok(ProxyDelay::timeout(name)),
+        Err(err) => return Err(err),
+    };
+    let status = response.status().as_u16();
+    parse_delay_response(name, status, &response.text())
+}
+
+fn parse_delay_response(name: &str, status: u16, body: &str) -> Result<ProxyDelay> {
+    #[derive(Deserialize)]
+    struct DelayRes {
+        delay: u64,
+    }
+
+    match status {
+        200 => {
+            let res = serde_json::from_str::<DelayRes>(body)?;
+            Ok(ProxyDelay {
+                name: name.into(),
+                delay: Some(res.delay),
+                timeout: false,
+            })
+        }
+        503 | 504 | 408 => Ok(ProxyDelay::timeout(name)),
+        _ => bail!("failed to test the delay of \"{name}\" with status \"{status}\"\n{body}")
+    }
+}
+
+/// 策略组延迟测试中每个节点的结果，逐个发送给前端
+pub const DELAY_RESULT_EVENT: &str = "verge://delay-result";
+
+/// 未设置 `delay_test_concurrency` 时的并发数
+pub const DEFAULT_DELAY_CONCURRENCY: usize = 8;
+
+/// 正在进行的策略组延迟测试的任务id，同一个策略组再次测试时取消旧的
+static GROUP_DELAY_OPERATIONS: Lazy<Mutex<HashMap<String, String>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupDelayResult {
+    pub group: String,
+    #[serde(flatten)]
+    pub result: ProxyDelay,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GroupDelaySummary {
+    pub group: String,
+    pub total: usize,
+    pub alive: usize,
+    pub timeout: usize,
+    pub results: Vec<ProxyDelay>,
+}
+
+impl GroupDelaySummary {
+    fn new(group: &str, results: Vec<ProxyDelay>) -> Self {
+        let alive = results.iter().filter(|r| r.delay.is_some()).count();
+        Self {
+            group: group.into(),
+            total: results.len(),
+            alive,
+            timeout: results.len() - alive,
+            results,
+        }
+    }
+}
+
+/// 在后台测试策略组中所有节点的延迟，立即返回任务id
+/// 每个结果通过 `verge://delay-result` 事件发送，任务结果为 `GroupDelaySummary`
+/// 同一个策略组有未完成的测试时会先取消它，被取消的任务在 `operation::finished` 中标记为 cancelled
+pub fn test_group_delay(group: String, url: Option<String>, timeout: Option<u32>) -> String {
+    let id = help::get_uid("delay");
+    if let Some(old) = GROUP_DELAY_OPERATIONS.lock().insert(group.clone(), id.clone()) {
+        log::info!(target: "app", "cancel the previous delay test of group \"{group}\"");
+        operation::OperationRegistry::global().cancel(&old);
+    }
+
+    operation::spawn(Some(id), |id, token| async move {
+        let result = operation::cancellable(&token, run_group_delay(&group, url, timeout)).await;
+        let mut operations = GROUP_DELAY_OPERATIONS.lock();
+        if operations.get(&group) == Some(&id) {
+            operations.remove(&group);
+        }
+        result
+    })
+}
+
+async fn run_group_delay(
+    group: &str,
+    url: Option<String>,
+    timeout: Option<u32>,
+) -> Result<GroupDelaySummary> {
+    let members = get_proxy_groups()
+        .await?
+        .remove(group)
+        .ok_or_else(|| anyhow!("proxy group \"{group}\" not found"))?;
+    let (url, timeout) = delay_test_options(url, timeout);
+
+    // 优先使用 mihomo 的策略组测试接口，不支持时逐个测试
+    if let Some(delays) = get_group_delay(group, &url, timeout).await? {
+        let results = members
+            .into_iter()
+            .map(|name| {
+                let result = match delays.get(&name) {
+                    Some(delay) if *delay > 0 => ProxyDelay {
+                        name,
+                        delay: Some(*delay),
+                        timeout: false,
+                    },
+                    _ => ProxyDelay::timeout(&name),
+                };
+                emit_delay_result(group, &result);
+                result
+            })
+            .collect();
+        return Ok(GroupDelaySummary::new(group, results));
+    }
+
+    let concurrency = {
+        let verge = Config::verge();
+        let concurrency = verge.latest().delay_test_concurrency;
+        concurrency.filter(|n| *n > 0).unwrap_or(DEFAULT_DELAY_CONCURRENCY)
+    };
+    let results = futures::stream::iter(members)
+        .map(|name| {
+            let url = url.clone();
+            async move {
+                let result = match test_delay(&name, Some(url), Some(timeout)).await {
+                    Ok(result) => result,
+                    Err(err) => {
+                        log::warn!(target: "app", "failed to test delay of \"{name}\": {err}");
+                        ProxyDelay::timeout(&name)
+                    }
+                };
+                emit_delay_result(group, &result);
+                result
+            }
+        })
+        .buffer_unordered(concurrency)
+        .collect::<Vec<_>>()
+        .await;
+    Ok(GroupDelaySummary::new(group, results))
+}
+
+/// GET /group/{name}/delay
+/// 只有 mihomo 支持，不支持时返回 None；超时的节点不在结果中
+async fn get_group_delay(
+    group: &str,
+    test_url: &str,
+    timeout: u32,
+) -> Result<Option<HashMap<String, u64>>> {
+    let encoded = percent_encoding::utf8_percent_encode(group, NON_ALPHANUMERIC);
+    let response = controller::get(format!("/group/{encoded}/delay"))
+        .query(&[("timeout", timeout.to_string().as_str()), ("url", test_url)])
+        .timeout(Duration::from_millis(timeout as u64 + 2000))
+        .retries(0)
+        .send()
+        .await;
+
+    let response = match response {
+        Ok(response) => response,
+        Err(err) if controller::is_timeout(&err) => return Ok(Some(HashMap::new())),
+        Err(err) => return Err(err),
+    };
     match response.status().as_u16() {
-        204 => Ok(()),
+        200 => Ok(Some(response.json::<HashMap<String, u64>>()?)),
+        // 整个策略组都超时
+        503 | 504 | 408 => Ok(Some(HashMap::new())),
+        404 | 405 => Ok(None),
         status => {
-            let body = response.text().await?;
-           // print!("failed to put configs with status \"{}\"\n{}\n{}", status, url, body);
-            bail!("failed to put configs with status \"{status}\"\n{url}\n{body}");
+            let body = response.text();
+            bail!("failed to test the delay of group \"{group}\" with status \"{status}\"\n{body}")
         }
     }
 }
 
-/// PATCH /configs
-pub async fn patch_configs(config: &Mapping) -> Result<()> {
-    let (url, headers) = clash_client_info()?;
-    let url = format!("{url}/configs");
+fn emit_delay_result(group: &str, result: &ProxyDelay) {
+    let payload = GroupDelayResult {
+        group: group.into(),
+        result: result.clone(),
+    };
+    handle::Handle::emit_event(DELAY_RESULT_EVENT, payload);
+}
+
+/// 更新订阅类型的 provider 需要下载，网络较慢时可能需要较长时间
+const PROVIDER_UPDATE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 健康检查会测试 provider 中的所有节点
+const PROVIDER_HEALTHCHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyProvider {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub provider_type: String,
+    /// HTTP、File、Inline 或 Compatible（策略组中直接写的节点）
+    pub vehicle_type: String,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub proxies: Vec<serde_json::Value>,
+    #[serde(
+        default,
+        deserialize_with = "lenient_subscription_info",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub subscription_info: Option<SubscriptionInfo>,
+}
+
+/// 订阅类 provider 响应头中的 `subscription-userinfo`，字段名沿用内核返回的大写形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SubscriptionInfo {
+    #[serde(default, deserialize_with = "lenient_u64")]
+    pub upload: u64,
+    #[serde(default, deserialize_with = "lenient_u64")]
+    pub download: u64,
+    #[serde(default, deserialize_with = "lenient_u64")]
+    pub total: u64,
+    /// 到期时间的秒级时间戳，0 表示不过期
+    #[serde(default, deserialize_with = "lenient_u64")]
+    pub expire: u64,
+}
+
+impl SubscriptionInfo {
+    /// 部分机场不提供流量信息时返回全 0
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// 字段不是对象时当作没有订阅信息，不影响 provider 列表的解析
+fn lenient_subscription_info<'de, D>(deserializer: D) -> Result<Option<SubscriptionInfo>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.and_then(|value| serde_json::from_value(value).ok()))
+}
 
-    let client = reqwest::ClientBuilder::new().no_proxy().build()?;
-    let builder = client.patch(&url).headers(headers.clone()).json(config);
-    builder.send().await?;
+/// 兼容字符串、小数和负数，无法解析或为负时按 0 处理
+fn lenient_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    let number = match value {
+        Some(serde_json::Value::Number(number)) => match number.as_u64() {
+            Some(number) => return Ok(number),
+            None => number.as_f64(),
+        },
+        Some(serde_json::Value::String(text)) => text.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+    Ok(number
+        .filter(|number| number.is_finite() && *number > 0.0)
+        .map(|number| number as u64)
+        .unwrap_or_default())
+}
+
+/// 单个 provider 的更新结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderUpdateResult {
+    pub name: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
+struct ProvidersRes<T> {
+    #[serde(default, deserialize_with = "null_as_default")]
+    providers: HashMap<String, T>,
+}
+
+/// GET /providers/proxies
+/// 只返回来自订阅或文件的 provider，按名称排序
+pub async fn get_proxy_providers() -> Result<Vec<ProxyProvider>> {
+    let body = get_providers("proxies").await?;
+    let res = serde_json::from_str::<ProvidersRes<ProxyProvider>>(&body)?;
+    let mut providers = res
+        .providers
+        .into_values()
+        .filter(|p| is_remote_or_file(&p.vehicle_type))
+        .collect::<Vec<_>>();
+    providers.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(providers)
+}
+
+/// PUT /providers/proxies/{name}
+/// 重新拉取 provider 的内容
+pub async fn update_proxy_provider(name: &str) -> Result<()> {
+    update_provider("proxies", name).await?;
+    invalidate_proxies_cache();
     Ok(())
 }
 
-#[derive(Default, Debug, Clone, Deserialize, Serialize)]
-pub struct DelayRes {
-    delay: u64,
+/// GET /providers/proxies/{name}/healthcheck
+/// 测试 provider 中所有节点的延迟
+pub async fn healthcheck_proxy_provider(name: &str) -> Result<()> {
+    let encoded = percent_encoding::utf8_percent_encode(name, NON_ALPHANUMERIC);
+    let response = controller::get(format!("/providers/proxies/{encoded}/healthcheck"))
+        .timeout(PROVIDER_HEALTHCHECK_TIMEOUT)
+        .retries(0)
+        .send()
+        .await?;
+    provider_response(response, "healthcheck", name)?;
+    invalidate_proxies_cache();
+    Ok(())
 }
 
-/// GET /proxies/{name}/delay
-/// 获取代理延迟
-pub async fn get_proxy_delay(
+/// 依次更新所有 proxy provider，单个失败不影响其他的
+pub async fn update_all_proxy_providers() -> Result<Vec<ProviderUpdateResult>> {
+    let names = get_proxy_providers()
+        .await?
+        .into_iter()
+        .map(|p| p.name)
+        .collect::<Vec<_>>();
+    let results = update_providers("proxies", names).await;
+    invalidate_proxies_cache();
+    Ok(results)
+}
+
+/// rule provider 更新成功后通知前端重新获取规则
+pub const RULE_PROVIDERS_UPDATED_EVENT: &str = "verge://rule-providers-updated";
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleProvider {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub provider_type: String,
+    #[serde(default)]
+    pub behavior: String,
+    #[serde(default)]
+    pub format: String,
+    #[serde(default)]
+    pub rule_count: usize,
+    pub vehicle_type: String,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+/// GET /providers/rules
+/// 只返回来自订阅或文件的 provider，按名称排序
+pub async fn get_rule_providers() -> Result<Vec<RuleProvider>> {
+    let body = get_providers("rules").await?;
+    let res = serde_json::from_str::<ProvidersRes<RuleProvider>>(&body)?;
+    let mut providers = res
+        .providers
+        .into_values()
+        .filter(|p| is_remote_or_file(&p.vehicle_type))
+        .collect::<Vec<_>>();
+    providers.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(providers)
+}
+
+/// PUT /providers/rules/{name}
+/// 重新拉取 rule provider 的内容，成功后发送 `verge://rule-providers-updated`
+pub async fn update_rule_provider(name: &str) -> Result<()> {
+    update_provider("rules", name).await?;
+    invalidate_rules_cache();
+    handle::Handle::emit_event(RULE_PROVIDERS_UPDATED_EVENT, vec![name]);
+    Ok(())
+}
+
+/// 依次更新所有 rule provider，单个失败不影响其他的
+pub async fn update_all_rule_providers() -> Result<Vec<ProviderUpdateResult>> {
+    let names = get_rule_providers()
+        .await?
+        .into_iter()
+        .map(|p| p.name)
+        .collect::<Vec<_>>();
+    let results = update_providers("rules", names).await;
+
+    let updated = results
+        .iter()
+        .filter(|r| r.ok)
+        .map(|r| r.name.clone())
+        .collect::<Vec<_>>();
+    if !updated.is_empty() {
+        invalidate_rules_cache();
+        handle::Handle::emit_event(RULE_PROVIDERS_UPDATED_EVENT, updated);
+    }
+    Ok(results)
+}
+
+fn is_remote_or_file(vehicle_type: &str) -> bool {
+    matches!(vehicle_type.to_lowercase().as_str(), "http" | "file")
+}
+
+/// GET /providers/{kind}
+async fn get_providers(kind: &str) -> Result<String> {
+    let response = controller::get(format!("/providers/{kind}"))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(response.text())
+}
+
+/// PUT /providers/{kind}/{name}
+async fn update_provider(kind: &str, name: &str) -> Result<()> {
+    let encoded = percent_encoding::utf8_percent_encode(name, NON_ALPHANUMERIC);
+    let response = controller::put(format!("/providers/{kind}/{encoded}"))
+        .timeout(PROVIDER_UPDATE_TIMEOUT)
+        .retries(0)
+        .send()
+        .await?;
+    provider_response(response, "update", name)
+}
+
+async fn update_providers(kind: &str, names: Vec<String>) -> Vec<ProviderUpdateResult> {
+    let mut results = vec![];
+    for name in names {
+        let result = update_provider(kind, &name).await;
+        if let Err(err) = &result {
+            log::warn!(target: "app", "{err}");
+        }
+        results.push(ProviderUpdateResult {
+            name,
+            ok: result.is_ok(),
+            error: result.err().map(|err| format!("{err:#}")),
+        });
+    }
+    results
+}
+
+/// 内核成功时返回 204 且没有内容，旧版本可能返回 200
+/// `action` 为 update 或 healthcheck，用于错误信息
+fn provider_response(response: controller::Response, action: &str, name: &str) -> Result<()> {
+    response
+        .error_for_status()
+        .with_context(|| format!("failed to {action} provider \"{name}\""))?;
+    Ok(())
+}
+
+/// 内核正在更新 GEO 数据库时返回的错误
+pub const GEO_UPDATING: &str = "the GEO databases are already being updated";
+
+/// POST /configs/geo
+/// 由内核下载并重新加载 GEO 数据库，网络较慢时需要较长时间
+pub async fn update_geo() -> Result<()> {
+    let response = controller::post("/configs/geo")
+        .json(&serde_json::json!({}))
+        .timeout(Duration::from_secs(180))
+        .send()
+        .await?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text();
+    geo_update_error(status.as_u16(), &body)
+}
+
+fn geo_update_error(status: u16, body: &str) -> Result<()> {
+    let message = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| v["message"].as_str().map(String::from))
+        .unwrap_or(body.into());
+    // mihomo: "GEO database is updating, skip"
+    if message.to_lowercase().contains("updating") {
+        bail!(GEO_UPDATING);
+    }
+    if status == 404 {
+        bail!("the core does not support updating the GEO databases");
+    }
+    bail!("failed to update the GEO databases with status \"{status}\"\n{message}")
+}
+
+/// 旧版内核没有 /upgrade 时返回的错误
+pub const UPGRADE_UNSUPPORTED: &str = "the core does not support in-place upgrade";
+
+/// POST /upgrade
+/// 内核下载新版本替换自身的可执行文件后自行重启
+pub async fn upgrade_core() -> Result<()> {
+    let response = controller::post("/upgrade")
+        .json(&serde_json::json!({}))
+        .timeout(Duration::from_secs(180))
+        .send()
+        .await?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    if status.as_u16() == 404 {
+        bail!(UPGRADE_UNSUPPORTED);
+    }
+    let body = response.text();
+    let message = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|v| v["message"].as_str().map(String::from))
+        .unwrap_or(body);
+    bail!("{message}")
+}
+
+/// POST /cache/fakeip/flush
+/// 清空内核的 fake-ip 缓存
+pub async fn flush_fakeip() -> Result<()> {
+    let response = controller::post("/cache/fakeip/flush").send().await?;
+
+    match response.status().as_u16() {
+        200 | 204 => Ok(()),
+        404 => bail!("the core does not support flushing the fake-ip cache or fake-ip is disabled"),
+        status => {
+            let body = response.text();
+            bail!("failed to flush the fake-ip cache with status \"{status}\"\n{body}")
+        }
+    }
+}
+
+/// 支持查询的记录类型
+pub const DNS_QUERY_TYPES: [&str; 10] =
+    ["A", "AAAA", "CNAME", "TXT", "MX", "NS", "SRV", "PTR", "HTTPS", "SOA"];
+
+/// DNS 查询的结果，NXDOMAIN 和超时不视为错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DnsQueryStatus {
+    Ok,
+    NxDomain,
+    Timeout,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DnsRecord {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub record_type: String,
+    pub ttl: u32,
+    pub data: String,
+}
+
+/// mihomo 不返回由哪个上游或策略解析，这里只有应答记录
+#[derive(Debug, Clone, Serialize)]
+pub struct DnsQueryResult {
+    pub name: String,
+    pub qtype: String,
+    pub status: DnsQueryStatus,
+    /// DNS 响应码，0 为 NOERROR，3 为 NXDOMAIN
+    pub rcode: Option<u16>,
+    pub answers: Vec<DnsRecord>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DnsQueryRes {
+    #[serde(rename = "Status")]
+    status: u16,
+    #[serde(rename = "Answer", default, deserialize_with = "null_as_default")]
+    answer: Vec<DnsAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DnsAnswer {
     name: String,
-    test_url: Option<String>,
-    timeout: i32,
-) -> Result<DelayRes> {
-    let (url, headers) = clash_client_info()?;
-    let url = format!("{url}/proxies/{name}/delay");
+    #[serde(rename = "type")]
+    record_type: u16,
+    #[serde(rename = "TTL", default)]
+    ttl: u32,
+    #[serde(default)]
+    data: String,
+}
 
-    let default_url = "http://cp.cloudflare.com/generate_204";
-    let test_url = test_url
-        .map(|s| if s.is_empty() { default_url.into() } else { s })
-        .unwrap_or(default_url.into());
+/// GET /dns/query?name=...&type=...
+/// 通过内核的 DNS 解析域名，记录类型不支持时返回错误
+pub async fn dns_query(name: &str, qtype: &str) -> Result<DnsQueryResult> {
+    let name = name.trim();
+    if name.is_empty() {
+        bail!("the domain name is empty");
+    }
+    let qtype = qtype.trim().to_uppercase();
+    if !DNS_QUERY_TYPES.contains(&qtype.as_str()) {
+        bail!("unsupported record type \"{qtype}\"");
+    }
 
-    let client = reqwest::ClientBuilder::new().no_proxy().build()?;
-    let builder = client
-        .get(&url)
-        .headers(headers)
-        .query(&[("timeout", &format!("{timeout}")), ("url", &test_url)]);
-    let response = builder.send().await?;
+    let response = controller::get("/dns/query")
+        .query(&[("name", name), ("type", qtype.as_str())])
+        .timeout(Duration::from_secs(10))
+        .retries(0)
+        .send()
+        .await;
 
-    Ok(response.json::<DelayRes>().await?)
+    let mut result = DnsQueryResult {
+        name: name.into(),
+        qtype,
+        status: DnsQueryStatus::Timeout,
+        rcode: None,
+        answers: vec![],
+        error: None,
+    };
+    let response = match response {
+        Ok(response) => response,
+        Err(err) if controller::is_timeout(&err) => return Ok(result),
+        Err(err) => return Err(err),
+    };
+    let status = response.status();
+    parse_dns_response(&mut result, status.as_u16(), &response.text())?;
+    Ok(result)
+}
+
+fn parse_dns_response(result: &mut DnsQueryResult, status: u16, body: &str) -> Result<()> {
+    if status == 404 {
+        bail!("the core does not support /dns/query");
+    }
+    if status != 200 {
+        // 内核解析失败时返回 {"message": "..."}
+        let message = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|v| v["message"].as_str().map(String::from))
+            .unwrap_or(body.into());
+        let lower = message.to_lowercase();
+        result.status = if lower.contains("timeout") || lower.contains("deadline") {
+            DnsQueryStatus::Timeout
+        } else {
+            DnsQueryStatus::Failed
+        };
+        result.error = Some(message);
+        return Ok(());
+    }
+
+    let res = serde_json::from_str::<DnsQueryRes>(body)?;
+    result.rcode = Some(res.status);
+    result.status = match res.status {
+        0 => DnsQueryStatus::Ok,
+        3 => DnsQueryStatus::NxDomain,
+        _ => DnsQueryStatus::Failed,
+    };
+    result.answers = res
+        .answer
+        .into_iter()
+        .map(|answer| DnsRecord {
+            name: answer.name,
+            record_type: dns_type_name(answer.record_type),
+            ttl: answer.ttl,
+            data: answer.data,
+        })
+        .collect();
+    Ok(())
+}
+
+fn dns_type_name(record_type: u16) -> String {
+    match record_type {
+        1 => "A".into(),
+        2 => "NS".into(),
+        5 => "CNAME".into(),
+        6 => "SOA".into(),
+        12 => "PTR".into(),
+        15 => "MX".into(),
+        16 => "TXT".into(),
+        28 => "AAAA".into(),
+        33 => "SRV".into(),
+        65 => "HTTPS".into(),
+        other => format!("TYPE{other}"),
+    }
+}
+
+/// POST /restart
+/// 内核重新执行自身并重新读取配置文件，内核不支持时返回 false
+pub async fn restart() -> Result<bool> {
+    let response = controller::post("/restart").send().await?;
+    match response.status().as_u16() {
+        200 | 204 => Ok(true),
+        404 | 405 => Ok(false),
+        status => {
+            let body = response.text();
+            bail!("failed to restart the core with status \"{status}\"\n/restart\n{body}");
+        }
+    }
 }
 
-/// 根据clash info获取clash服务地址和请求头
-fn clash_client_info() -> Result<(String, HeaderMap)> {
-    let client = { Config::clash().data().get_client_info() };
+/// GET /version
+/// 用于判断内核的控制接口是否已经就绪
+pub async fn get_version() -> Result<String> {
+    Ok(get_api_version().await?.version)
+}
 
-    let server = format!("http://{}", client.server);
+/// /version 返回的内核版本，`meta` 为真表示 Clash.Meta/mihomo
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CoreApiVersion {
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub meta: bool,
+}
 
-    let mut headers = HeaderMap::new();
-    headers.insert("Content-Type", "application/json".parse()?);
+/// GET /version
+pub async fn get_api_version() -> Result<CoreApiVersion> {
+    // 用于轮询内核是否就绪，由调用方决定是否重试
+    let response = controller::get("/version")
+        .timeout(Duration::from_secs(1))
+        .retries(0)
+        .send()
+        .await?
+        .error_for_status()?;
+    response.json::<CoreApiVersion>()
+}
 
-    if let Some(secret) = client.secret {
-        let secret = format!("Bearer {}", secret).parse()?;
-        headers.insert("Authorization", secret);
+/// GET /debug/pprof
+/// 内核以 debug 日志级别运行时才提供该接口
+pub async fn is_debug_enabled() -> bool {
+    match controller::get("/debug/pprof").retries(0).send().await {
+        Ok(response) => response.status().is_success(),
+        Err(_) => false,
     }
+}
 
-    Ok((server, headers))
+/// PUT /debug/gc
+pub async fn gc() -> Result<()> {
+    controller::put("/debug/gc").send().await?.error_for_status()?;
+    Ok(())
 }
 
 /// 缩短clash的日志
@@ -131,11 +1321,443 @@ pub fn parse_check_output(log: String) -> String {
     log
 }
 
-#[cfg(target_os = "macos")]
-pub fn get_traffic_ws_url() -> Result<String> {
-    let (url, _) = clash_client_info()?;
-    let ws_url = url.replace("http://", "ws://") + "/traffic";
-    Ok(ws_url)
+/// 订阅断开后第一次重连前等待的时间，之后每次失败翻倍
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(500);
+/// 重连等待时间的上限
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+/// 连接保持超过这个时间后才断开，视为新的故障，从初始等待时间重新开始
+const RECONNECT_STABLE_AFTER: Duration = Duration::from_secs(10);
+
+/// 订阅的连接状态变化时发送，前端据此显示断开而不是过时的数据
+const SUBSCRIPTION_STATE_EVENT: &str = "verge://subscription-state";
+
+/// 到内核的 WebSocket 订阅
+pub const TRAFFIC_SUBSCRIPTION: &str = "traffic";
+pub const MEMORY_SUBSCRIPTION: &str = "memory";
+pub const LOGS_SUBSCRIPTION: &str = "logs";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionState {
+    Connected,
+    /// 等待重连
+    Disconnected,
+    /// 内核已经主动停止，不再重连
+    Stopped,
+}
+
+/// 内核是否应当在运行，`generation` 在每次内核就绪时递增
+#[derive(Debug, Clone, Copy, Default)]
+struct CoreLink {
+    running: bool,
+    generation: u64,
+}
+
+fn core_link() -> &'static tokio::sync::watch::Sender<CoreLink> {
+    static LINK: OnceCell<tokio::sync::watch::Sender<CoreLink>> = OnceCell::new();
+    LINK.get_or_init(|| tokio::sync::watch::channel(CoreLink::default()).0)
+}
+
+/// 内核启动或重启完成，等待中的订阅立即重连
+pub fn notify_core_ready() {
+    core_link().send_modify(|link| {
+        link.running = true;
+        link.generation += 1;
+    });
+}
+
+/// 内核被主动停止，断开所有订阅且不再重连，直到 `notify_core_ready`
+pub fn notify_core_stopped() {
+    core_link().send_modify(|link| link.running = false);
+}
+
+fn subscription_states() -> &'static Mutex<HashMap<&'static str, SubscriptionState>> {
+    static STATES: OnceCell<Mutex<HashMap<&'static str, SubscriptionState>>> = OnceCell::new();
+    STATES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn subscription_state_sender() -> &'static broadcast::Sender<(&'static str, SubscriptionState)> {
+    static SENDER: OnceCell<broadcast::Sender<(&'static str, SubscriptionState)>> =
+        OnceCell::new();
+    SENDER.get_or_init(|| broadcast::channel(16).0)
+}
+
+/// 接收订阅连接状态的变化，托盘等后端的消费者用它清除过时的数据
+pub fn subscribe_subscription_states() -> broadcast::Receiver<(&'static str, SubscriptionState)> {
+    subscription_state_sender().subscribe()
+}
+
+/// 订阅当前的连接状态，没有订阅时为空
+pub fn subscription_state(name: &str) -> Option<SubscriptionState> {
+    subscription_states().lock().get(name).copied()
+}
+
+fn set_subscription_state(name: &'static str, state: SubscriptionState) {
+    if subscription_states().lock().insert(name, state) == Some(state) {
+        return;
+    }
+    log::debug!(target: "app", "{name} subscription is {state:?}");
+    let _ = subscription_state_sender().send((name, state));
+    handle::Handle::emit_event(
+        SUBSCRIPTION_STATE_EVENT,
+        serde_json::json!({ "name": name, "state": state }),
+    );
+}
+
+fn clear_subscription_state(name: &str) {
+    subscription_states().lock().remove(name);
+}
+
+/// 重连的指数退避，抖动避免多个订阅同时重连
+#[derive(Debug, Default)]
+struct Backoff {
+    attempt: u32,
+}
+
+impl Backoff {
+    /// `jitter` 为 0..=u16::MAX，最多在基础时间上增加一半，结果不超过 `RECONNECT_MAX_DELAY`
+    fn next_delay(&mut self, jitter: u16) -> Duration {
+        let base = RECONNECT_BASE_DELAY
+            .saturating_mul(2u32.saturating_pow(self.attempt))
+            .min(RECONNECT_MAX_DELAY);
+        self.attempt = self.attempt.saturating_add(1);
+        let jitter = base / 2 * jitter as u32 / u16::MAX as u32;
+        (base + jitter).min(RECONNECT_MAX_DELAY)
+    }
+
+    fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+/// 等待 `delay` 后重连，期间内核重新就绪（`generation` 变化）时立即返回 true
+async fn wait_reconnect(
+    link: &mut tokio::sync::watch::Receiver<CoreLink>,
+    generation: u64,
+    delay: Duration,
+) -> bool {
+    tokio::select! {
+        _ = tokio::time::sleep(delay) => false,
+        ready = link.wait_for(|link| link.generation != generation) => ready.is_ok(),
+    }
+}
+
+/// 保持一个到内核的 WebSocket 订阅，`forward` 连接后一直转发到连接断开
+/// 断开后按指数退避重连，内核就绪时立即重连，内核主动停止时断开且不再重连
+/// 只有 `give_up` 对错误返回 true 时才结束，返回这个错误
+async fn run_subscription<F, Fut>(
+    name: &'static str,
+    give_up: fn(&anyhow::Error) -> bool,
+    mut forward: F,
+) -> anyhow::Error
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<()>>,
+{
+    let mut link = core_link().subscribe();
+    let mut backoff = Backoff::default();
+    loop {
+        if !link.borrow().running {
+            set_subscription_state(name, SubscriptionState::Stopped);
+            if link.wait_for(|link| link.running).await.is_err() {
+                return anyhow!("the core link was closed");
+            }
+            backoff.reset();
+        }
+
+        let generation = link.borrow_and_update().generation;
+        let started = tokio::time::Instant::now();
+        let result = tokio::select! {
+            result = forward() => result,
+            _ = link.wait_for(|link| !link.running) => Ok(()),
+        };
+        match result {
+            Err(err) if give_up(&err) => {
+                clear_subscription_state(name);
+                return err;
+            }
+            Err(err) => log::debug!(target: "app", "{name} ws disconnected: {err}"),
+            Ok(()) => {}
+        }
+        if !link.borrow().running {
+            continue;
+        }
+
+        set_subscription_state(name, SubscriptionState::Disconnected);
+        if started.elapsed() >= RECONNECT_STABLE_AFTER {
+            backoff.reset();
+        }
+        let delay = backoff.next_delay(controller::random_jitter());
+        if wait_reconnect(&mut link, generation, delay).await {
+            backoff.reset();
+        }
+    }
+}
+
+/// /traffic 推送的实时速率，单位 B/s
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Traffic {
+    pub up: u64,
+    pub down: u64,
+}
+
+/// 前端接收流量数据的事件
+const TRAFFIC_EVENT: &str = "verge://traffic";
+/// 两次发送流量事件的最小间隔
+const TRAFFIC_EMIT_INTERVAL: Duration = Duration::from_millis(250);
+
+fn traffic_sender() -> &'static broadcast::Sender<Traffic> {
+    static SENDER: OnceCell<broadcast::Sender<Traffic>> = OnceCell::new();
+    SENDER.get_or_init(|| broadcast::channel(16).0)
+}
+
+fn traffic_task() -> &'static Mutex<Option<JoinHandle<()>>> {
+    static TASK: OnceCell<Mutex<Option<JoinHandle<()>>>> = OnceCell::new();
+    TASK.get_or_init(|| Mutex::new(None))
+}
+
+/// 接收实时流量，内核重启后仍然是同一个订阅
+pub fn subscribe_traffic() -> broadcast::Receiver<Traffic> {
+    traffic_sender().subscribe()
+}
+
+/// 连接内核的 /traffic 并发送 `verge://traffic` 事件，已经在订阅时不重复连接
+/// 内核启动后调用，断开后会一直重连，直到 `stop_traffic_subscription`
+pub fn start_traffic_subscription() {
+    let mut task = traffic_task().lock();
+    if task.is_none() {
+        *task = Some(tauri::async_runtime::spawn(async {
+            run_subscription(TRAFFIC_SUBSCRIPTION, |_| false, forward_traffic).await;
+        }));
+    }
+}
+
+pub fn stop_traffic_subscription() {
+    if let Some(task) = traffic_task().lock().take() {
+        task.abort();
+    }
+    clear_subscription_state(TRAFFIC_SUBSCRIPTION);
+}
+
+async fn forward_traffic() -> Result<()> {
+    let mut stream = controller::connect_ws("/traffic", &[]).await?;
+    log::info!(target: "app", "traffic ws connection established");
+    set_subscription_state(TRAFFIC_SUBSCRIPTION, SubscriptionState::Connected);
+
+    let mut last_emit: Option<Instant> = None;
+    while let Some(message) = stream.next().await {
+        let Message::Text(text) = message? else {
+            continue;
+        };
+        let traffic = serde_json::from_str::<Traffic>(&text)?;
+        let _ = traffic_sender().send(traffic);
+
+        if last_emit.is_some_and(|last| last.elapsed() < TRAFFIC_EMIT_INTERVAL) {
+            continue;
+        }
+        last_emit = Some(Instant::now());
+        handle::Handle::emit_event(TRAFFIC_EVENT, traffic);
+    }
+    bail!("traffic ws closed")
+}
+
+/// /memory 推送的内核内存占用，单位 B
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Memory {
+    pub inuse: u64,
+    #[serde(default)]
+    pub oslimit: u64,
+}
+
+/// 前端接收内存占用的事件
+const MEMORY_EVENT: &str = "verge://memory";
+/// 内核不支持 /memory 时发送一次，前端据此隐藏内存占用
+const MEMORY_UNSUPPORTED_EVENT: &str = "verge://memory-unsupported";
+
+/// 当前的内核不支持 /memory，内核重启后重新检测
+static MEMORY_UNSUPPORTED: AtomicBool = AtomicBool::new(false);
+
+/// 订阅内存占用的前端数量和任务，没有订阅时断开连接
+#[derive(Default)]
+struct MemorySubscription {
+    listeners: usize,
+    task: Option<JoinHandle<()>>,
+}
+
+fn memory_subscription() -> &'static Mutex<MemorySubscription> {
+    static SUBSCRIPTION: OnceCell<Mutex<MemorySubscription>> = OnceCell::new();
+    SUBSCRIPTION.get_or_init(|| Mutex::new(MemorySubscription::default()))
+}
+
+/// 增加一个内存占用的订阅，第一个订阅时连接内核的 /memory
+/// 内核不支持时返回 false
+pub fn start_memory_subscription() -> bool {
+    let mut subscription = memory_subscription().lock();
+    subscription.listeners += 1;
+    if MEMORY_UNSUPPORTED.load(Ordering::SeqCst) {
+        return false;
+    }
+    if subscription.task.is_none() {
+        subscription.task = Some(spawn_memory_task());
+    }
+    true
+}
+
+/// 减少一个内存占用的订阅，没有订阅时断开连接
+pub fn stop_memory_subscription() {
+    let mut subscription = memory_subscription().lock();
+    subscription.listeners = subscription.listeners.saturating_sub(1);
+    if subscription.listeners == 0 {
+        if let Some(task) = subscription.task.take() {
+            task.abort();
+        }
+        clear_subscription_state(MEMORY_SUBSCRIPTION);
+    }
+}
+
+/// 内核重启后重新检测是否支持 /memory，仍有订阅时重新连接
+pub fn restart_memory_subscription() {
+    MEMORY_UNSUPPORTED.store(false, Ordering::SeqCst);
+    let mut subscription = memory_subscription().lock();
+    if let Some(task) = subscription.task.take() {
+        task.abort();
+    }
+    if subscription.listeners > 0 {
+        subscription.task = Some(spawn_memory_task());
+    }
+}
+
+fn spawn_memory_task() -> JoinHandle<()> {
+    tauri::async_runtime::spawn(async {
+        let err = run_subscription(MEMORY_SUBSCRIPTION, is_ws_unsupported, forward_memory).await;
+        log::info!(target: "app", "the core does not support /memory: {err}");
+        MEMORY_UNSUPPORTED.store(true, Ordering::SeqCst);
+        handle::Handle::emit_event(MEMORY_UNSUPPORTED_EVENT, ());
+    })
+}
+
+async fn forward_memory() -> Result<()> {
+    let mut stream = controller::connect_ws("/memory", &[]).await?;
+    log::info!(target: "app", "memory ws connection established");
+    set_subscription_state(MEMORY_SUBSCRIPTION, SubscriptionState::Connected);
+
+    while let Some(message) = stream.next().await {
+        let Message::Text(text) = message? else {
+            continue;
+        };
+        let memory = serde_json::from_str::<Memory>(&text)?;
+        handle::Handle::emit_event(MEMORY_EVENT, memory);
+    }
+    bail!("memory ws closed")
+}
+
+/// 握手时内核返回了普通的 HTTP 响应（如 404），说明没有这个接口
+/// 连接被拒绝等错误是内核没有运行，需要继续重连；401/403 是密钥已经更换，也需要重连
+fn is_ws_unsupported(err: &anyhow::Error) -> bool {
+    use tokio_tungstenite::tungstenite::Error;
+    match err.downcast_ref::<Error>() {
+        Some(Error::Http(response)) => !matches!(response.status().as_u16(), 401 | 403),
+        _ => false,
+    }
+}
+
+/// 前端接收内核日志的事件，每次发送一批
+const CORE_LOG_EVENT: &str = "verge://core-log";
+/// 日志合并发送的间隔，避免 debug 等级的大量日志阻塞 webview
+const CORE_LOG_BATCH_INTERVAL: Duration = Duration::from_millis(200);
+/// 每批最多发送的日志数，超出时丢弃较早的
+const CORE_LOG_BATCH_LIMIT: usize = 500;
+/// 内核支持的日志等级
+pub const CORE_LOG_LEVELS: [&str; 5] = ["debug", "info", "warning", "error", "silent"];
+
+/// /logs 推送的内核日志，`time` 为应用收到日志的本地时间
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CoreLog {
+    #[serde(rename = "type")]
+    pub log_type: String,
+    pub payload: String,
+    #[serde(default)]
+    pub time: String,
+}
+
+/// 正在订阅的日志等级和任务
+fn core_log_task() -> &'static Mutex<Option<(String, JoinHandle<()>)>> {
+    static TASK: OnceCell<Mutex<Option<(String, JoinHandle<()>)>>> = OnceCell::new();
+    TASK.get_or_init(|| Mutex::new(None))
+}
+
+/// 连接内核的 /logs 并发送 `verge://core-log` 事件，已经订阅时按新的等级重新连接
+/// 断开（如内核重启）后按退避时间重连，直到 `stop_core_log_subscription`
+pub fn start_core_log_subscription(level: &str) -> Result<()> {
+    let level = level.to_lowercase();
+    if !CORE_LOG_LEVELS.contains(&level.as_str()) {
+        bail!("invalid log level \"{level}\"");
+    }
+
+    let mut task = core_log_task().lock();
+    if task.as_ref().is_some_and(|(current, _)| *current == level) {
+        return Ok(());
+    }
+
+    let log_level = level.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        let forward = || {
+            let level = log_level.clone();
+            async move { forward_core_logs(&level).await }
+        };
+        run_subscription(LOGS_SUBSCRIPTION, |_| false, forward).await;
+    });
+    if let Some((_, old)) = task.replace((level, handle)) {
+        old.abort();
+    }
+    Ok(())
+}
+
+pub fn stop_core_log_subscription() {
+    if let Some((_, task)) = core_log_task().lock().take() {
+        task.abort();
+    }
+    clear_subscription_state(LOGS_SUBSCRIPTION);
+}
+
+async fn forward_core_logs(level: &str) -> Result<()> {
+    let mut stream = controller::connect_ws("/logs", &[("level", level)]).await?;
+    log::info!(target: "app", "log ws connection established");
+    set_subscription_state(LOGS_SUBSCRIPTION, SubscriptionState::Connected);
+
+    let mut batch = vec![];
+    let mut interval = tokio::time::interval(CORE_LOG_BATCH_INTERVAL);
+    loop {
+        tokio::select! {
+            message = stream.next() => {
+                let Some(message) = message else {
+                    break;
+                };
+                let Message::Text(text) = message? else {
+                    continue;
+                };
+                let mut entry = serde_json::from_str::<CoreLog>(&text)?;
+                entry.time = chrono::Local::now().format("%m-%d %H:%M:%S").to_string();
+                push_core_log(&mut batch, entry);
+            }
+            _ = interval.tick() => {
+                if !batch.is_empty() {
+                    handle::Handle::emit_event(CORE_LOG_EVENT, std::mem::take(&mut batch));
+                }
+            }
+        }
+    }
+    if !batch.is_empty() {
+        handle::Handle::emit_event(CORE_LOG_EVENT, batch);
+    }
+    bail!("log ws closed")
+}
+
+fn push_core_log(batch: &mut Vec<CoreLog>, entry: CoreLog) {
+    if batch.len() >= CORE_LOG_BATCH_LIMIT {
+        batch.remove(0);
+    }
+    batch.push(entry);
 }
 
 #[test]
@@ -154,3 +1776,351 @@ fn test_parse_check_output() {
 
     assert_eq!(res1, res3);
 }
+
+#[test]
+fn test_parse_traffic() {
+    let traffic = serde_json::from_str::<Traffic>(r#"{"up":1024,"down":20480}"#).unwrap();
+    assert_eq!(traffic, Traffic { up: 1024, down: 20480 });
+}
+
+#[test]
+fn test_parse_connections() {
+    // mihomo 返回的 /connections
+    let res = r#"{
+        "downloadTotal": 1638400,
+        "uploadTotal": 204800,
+        "connections": [
+            {
+                "id": "0b7e6b2c-51f3-4d8e-9b57-2a2f1f0a1c11",
+                "metadata": {
+                    "network": "tcp",
+                    "type": "Tun",
+                    "sourceIP": "198.18.0.1",
+                    "destinationIP": "142.250.72.14",
+                    "sourcePort": "52134",
+                    "destinationPort": "443",
+                    "inboundIP": "",
+                    "inboundPort": "0",
+                    "inboundName": "DEFAULT-TUN",
+                    "inboundUser": "",
+                    "host": "www.google.com",
+                    "dnsMode": "fake-ip",
+                    "uid": 0,
+                    "process": "chrome.exe",
+                    "processPath": "C:\\Program Files\\Google\\Chrome\\Application\\chrome.exe",
+                    "specialProxy": "",
+                    "specialRules": "",
+                    "remoteDestination": "142.250.72.14",
+                    "dscp": 0,
+                    "sniffHost": ""
+                },
+                "upload": 4096,
+                "download": 1048576,
+                "start": "2024-05-01T12:00:00.000000000+08:00",
+                "chains": ["HK-01", "Proxy"],
+                "rule": "DomainSuffix",
+                "rulePayload": "google.com"
+            },
+            {
+                "id": "6a0d8d4e-1c2b-4f7a-8f3e-6b9f3c2d1e22",
+                "metadata": {
+                    "network": "udp",
+                    "type": "Tun",
+                    "sourceIP": "198.18.0.1",
+                    "destinationIP": "1.1.1.1",
+                    "sourcePort": "60000",
+                    "destinationPort": "53",
+                    "host": "",
+                    "process": "",
+                    "processPath": ""
+                },
+                "upload": 80,
+                "download": 120,
+                "start": "2024-05-01T12:00:01.000000000+08:00",
+                "chains": ["DIRECT"],
+                "rule": "Match",
+                "rulePayload": ""
+            }
+        ],
+        "memory": 52428800
+    }"#;
+    let snapshot = serde_json::from_str::<ConnectionsSnapshot>(res).unwrap();
+    assert_eq!(snapshot.download_total, 1638400);
+    assert_eq!(snapshot.connections.len(), 2);
+    let conn = &snapshot.connections[0];
+    assert_eq!(conn.metadata.host, "www.google.com");
+    assert_eq!(conn.metadata.source_ip, "198.18.0.1");
+    assert_eq!(conn.metadata.destination_port, "443");
+    assert_eq!(conn.metadata.process, "chrome.exe");
+    assert_eq!(conn.chains, vec!["HK-01", "Proxy"]);
+    assert_eq!(conn.rule_payload, "google.com");
+
+    let page = snapshot.clone().query(Some("GOOGLE"), false, 10);
+    assert_eq!((page.total, page.truncated), (1, false));
+    let page = snapshot.clone().query(Some("1.1.1"), false, 10);
+    assert_eq!(page.connections[0].metadata.destination_port, "53");
+    assert!(snapshot.connections[0].matches("hk-01"));
+    assert!(snapshot.connections[1].matches("1.1.1.1"));
+    assert!(!snapshot.connections[1].matches("google"));
+    let page = snapshot.query(None, true, 1);
+    assert_eq!((page.total, page.truncated), (2, true));
+    assert_eq!(page.connections[0].metadata.host, "www.google.com");
+
+    let empty = serde_json::from_str::<ConnectionsSnapshot>(
+        r#"{"downloadTotal":0,"uploadTotal":0,"connections":null}"#,
+    )
+    .unwrap();
+    assert!(empty.connections.is_empty());
+}
+
+#[test]
+fn test_parse_delay_response() {
+    let res = parse_delay_response("🇭🇰 HK/01", 200, r#"{"delay":128}"#).unwrap();
+    assert_eq!(res.delay, Some(128));
+    assert!(!res.timeout);
+
+    let res = parse_delay_response("HK 01", 504, r#"{"message":"Timeout"}"#).unwrap();
+    assert_eq!(res, ProxyDelay::timeout("HK 01"));
+    let res = parse_delay_response("HK 01", 503, r#"{"message":"An error occurred"}"#).unwrap();
+    assert!(res.timeout);
+
+    assert!(parse_delay_response("HK 01", 404, r#"{"message":"resource not found"}"#).is_err());
+}
+
+#[test]
+fn test_group_delay_summary() {
+    let results = vec![
+        ProxyDelay {
+            name: "HK 01".into(),
+            delay: Some(80),
+            timeout: false,
+        },
+        ProxyDelay::timeout("JP 01"),
+    ];
+    let summary = GroupDelaySummary::new("Proxy", results);
+    assert_eq!((summary.total, summary.alive, summary.timeout), (2, 1, 1));
+
+    let event = GroupDelayResult {
+        group: "Proxy".into(),
+        result: ProxyDelay::timeout("JP 01"),
+    };
+    let value = serde_json::to_value(event).unwrap();
+    assert_eq!(value["group"], "Proxy");
+    assert_eq!(value["name"], "JP 01");
+    assert_eq!(value["timeout"], true);
+}
+
+#[test]
+fn test_parse_proxy_providers() {
+    let res = r#"{
+        "providers": {
+            "default": { "name": "default", "type": "Proxy", "vehicleType": "Compatible",
+                "proxies": null },
+            "airport": { "name": "airport", "type": "Proxy", "vehicleType": "HTTP",
+                "updatedAt": "2024-05-01T08:00:00.000000+08:00",
+                "proxies": [{ "name": "HK 01" }],
+                "subscriptionInfo": { "Upload": 0, "Download": 1024, "Total": 0, "Expire": 0 } }
+        }
+    }"#;
+    let res = serde_json::from_str::<ProvidersRes<ProxyProvider>>(res).unwrap();
+    assert!(res.providers["default"].proxies.is_empty());
+    let airport = &res.providers["airport"];
+    assert_eq!(airport.proxies.len(), 1);
+    assert!(airport.updated_at.is_some());
+    assert!(is_remote_or_file(&airport.vehicle_type));
+    assert!(!is_remote_or_file("Compatible"));
+}
+
+#[test]
+fn test_parse_rule_providers() {
+    let res = r#"{
+        "providers": {
+            "reject": { "name": "reject", "type": "Rule", "behavior": "Domain",
+                "format": "YamlRule", "ruleCount": 120, "vehicleType": "HTTP",
+                "updatedAt": "2024-05-01T08:00:00.000000+08:00" }
+        }
+    }"#;
+    let res = serde_json::from_str::<ProvidersRes<RuleProvider>>(res).unwrap();
+    let reject = &res.providers["reject"];
+    assert_eq!(reject.rule_count, 120);
+    assert_eq!(reject.behavior, "Domain");
+
+    let empty = serde_json::from_str::<ProvidersRes<RuleProvider>>(r#"{"providers":null}"#);
+    assert!(empty.unwrap().providers.is_empty());
+}
+
+#[test]
+fn test_parse_core_log() {
+    let entry = serde_json::from_str::<CoreLog>(
+        r#"{"type":"info","payload":"[TCP] 127.0.0.1:52134 --> www.google.com:443"}"#,
+    )
+    .unwrap();
+    assert_eq!(entry.log_type, "info");
+    assert!(entry.time.is_empty());
+
+    let mut batch = vec![];
+    for _ in 0..CORE_LOG_BATCH_LIMIT + 10 {
+        push_core_log(&mut batch, entry.clone());
+    }
+    assert_eq!(batch.len(), CORE_LOG_BATCH_LIMIT);
+}
+
+#[test]
+fn test_parse_memory() {
+    let memory = serde_json::from_str::<Memory>(r#"{"inuse":52428800,"oslimit":0}"#).unwrap();
+    assert_eq!(memory.inuse, 52428800);
+    let memory = serde_json::from_str::<Memory>(r#"{"inuse":1024}"#).unwrap();
+    assert_eq!(memory.oslimit, 0);
+
+    let refused = anyhow::Error::new(tokio_tungstenite::tungstenite::Error::ConnectionClosed);
+    assert!(!is_ws_unsupported(&refused));
+}
+
+#[test]
+fn test_parse_dns_response() {
+    let empty = || DnsQueryResult {
+        name: "www.google.com".into(),
+        qtype: "A".into(),
+        status: DnsQueryStatus::Timeout,
+        rcode: None,
+        answers: vec![],
+        error: None,
+    };
+
+    let body = r#"{"Status":0,"Question":[{"name":"www.google.com.","qtype":1,"qclass":1}],
+        "Answer":[{"name":"www.google.com.","type":5,"TTL":300,"data":"www.l.google.com."},
+        {"name":"www.l.google.com.","type":1,"TTL":60,"data":"198.18.0.8"}]}"#;
+    let mut result = empty();
+    parse_dns_response(&mut result, 200, body).unwrap();
+    assert_eq!(result.status, DnsQueryStatus::Ok);
+    assert_eq!(result.answers.len(), 2);
+    assert_eq!(result.answers[0].record_type, "CNAME");
+    assert_eq!(result.answers[1].data, "198.18.0.8");
+
+    let mut result = empty();
+    parse_dns_response(&mut result, 200, r#"{"Status":3,"Answer":null}"#).unwrap();
+    assert_eq!(result.status, DnsQueryStatus::NxDomain);
+    assert_eq!(result.rcode, Some(3));
+
+    let mut result = empty();
+    let body = r#"{"message":"context deadline exceeded"}"#;
+    parse_dns_response(&mut result, 500, body).unwrap();
+    assert_eq!(result.status, DnsQueryStatus::Timeout);
+
+    assert!(parse_dns_response(&mut empty(), 404, "").is_err());
+}
+
+#[test]
+fn test_geo_update_error() {
+    let body = r#"{"message":"GEO database is updating, skip"}"#;
+    let err = geo_update_error(500, body).unwrap_err();
+    assert_eq!(err.to_string(), GEO_UPDATING);
+
+    let err = geo_update_error(500, r#"{"message":"can't download GeoSite.dat"}"#).unwrap_err();
+    assert!(err.to_string().contains("can't download GeoSite.dat"));
+}
+
+#[test]
+fn test_query_rules() {
+    let rule = |rule_type: &str, payload: &str, proxy: &str| RuleEntry {
+        rule_type: rule_type.into(),
+        payload: payload.into(),
+        proxy: proxy.into(),
+    };
+    let rules = vec![
+        rule("DomainSuffix", "google.com", "Proxy"),
+        rule("DomainSuffix", "baidu.com", "DIRECT"),
+        rule("GeoIP", "CN", "DIRECT"),
+        rule("Match", "", "Proxy"),
+    ];
+
+    let page = query_rules(&rules, None, 0, 10);
+    assert_eq!((page.total, page.filtered, page.rules.len()), (4, 4, 4));
+
+    let page = query_rules(&rules, Some(" direct "), 0, 10);
+    assert_eq!((page.total, page.filtered), (4, 2));
+    assert_eq!(page.rules[0].index, 2);
+    assert_eq!(page.rules[1].index, 3);
+
+    let page = query_rules(&rules, Some("GOOGLE"), 0, 10);
+    assert_eq!(page.filtered, 1);
+    assert_eq!(page.rules[0].rule.payload, "google.com");
+
+    let page = query_rules(&rules, Some("proxy"), 1, 10);
+    assert_eq!((page.filtered, page.rules.len()), (2, 1));
+    assert_eq!(page.rules[0].index, 4);
+}
+
+#[test]
+fn test_backoff_delay() {
+    let mut backoff = Backoff::default();
+    let delays = (0..8).map(|_| backoff.next_delay(0).as_millis()).collect::<Vec<_>>();
+    assert_eq!(delays, [500, 1000, 2000, 4000, 8000, 16000, 30000, 30000]);
+
+    // 抖动最多增加一半，且不超过上限
+    backoff.reset();
+    assert_eq!(backoff.next_delay(u16::MAX), Duration::from_millis(750));
+    for _ in 0..40 {
+        assert!(backoff.next_delay(u16::MAX) <= RECONNECT_MAX_DELAY);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_reconnect_schedule() {
+    use tokio::time::Instant;
+
+    let link = CoreLink {
+        running: true,
+        generation: 1,
+    };
+    let (sender, mut receiver) = tokio::sync::watch::channel(link);
+    let mut backoff = Backoff::default();
+
+    // 内核一直不可用时按退避时间等待
+    let start = Instant::now();
+    for expected in [500, 1000, 2000, 4000] {
+        let before = Instant::now();
+        assert!(!wait_reconnect(&mut receiver, 1, backoff.next_delay(0)).await);
+        assert_eq!(before.elapsed(), Duration::from_millis(expected));
+    }
+    assert_eq!(start.elapsed(), Duration::from_millis(7500));
+
+    // 内核就绪时不再等待剩下的 7 秒
+    let before = Instant::now();
+    let wait = wait_reconnect(&mut receiver, 1, backoff.next_delay(0));
+    let ready = async {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        sender.send_modify(|link| link.generation += 1);
+    };
+    let (reconnected, _) = tokio::join!(wait, ready);
+    assert!(reconnected);
+    assert_eq!(before.elapsed(), Duration::from_secs(1));
+}
+
+#[test]
+fn test_subscription_info() {
+    let body = r#"{"providers":{
+        "a":{"name":"a","type":"Proxy","vehicleType":"HTTP","proxies":[],
+            "subscriptionInfo":{"Upload":1024,"Download":"2048","Total":1.5e3,"Expire":-1}},
+        "b":{"name":"b","type":"Proxy","vehicleType":"HTTP","proxies":null,
+            "subscriptionInfo":"invalid"},
+        "c":{"name":"c","type":"Proxy","vehicleType":"File","proxies":[]}
+    }}"#;
+    let res = serde_json::from_str::<ProvidersRes<ProxyProvider>>(body).unwrap();
+    let info = res.providers["a"].subscription_info.unwrap();
+    assert_eq!(
+        info,
+        SubscriptionInfo {
+            upload: 1024,
+            download: 2048,
+            total: 1500,
+            expire: 0,
+        }
+    );
+    assert!(res.providers["b"].subscription_info.is_none());
+    assert!(res.providers["c"].subscription_info.is_none());
+
+    let value = serde_json::to_value(info).unwrap();
+    assert_eq!(value["Download"], 2048);
+    assert!(SubscriptionInfo::default().is_empty());
+}
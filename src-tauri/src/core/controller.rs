@@ -0,0 +1,641 @@
+//! 内核控制接口的连接方式
+//! 默认通过 TCP 访问 `external-controller`，也可以改用 unix socket（Windows 上为命名管道），
+//! 本地连接不占用端口，也不会被局域网中的其他设备访问
+use crate::config::Config;
+use crate::utils::dirs;
+use anyhow::{bail, Context, Result};
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use percent_encoding::NON_ALPHANUMERIC;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::Path;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::tungstenite::{self, Message};
+
+/// verge 中选择本地连接时的取值
+pub const TRANSPORT_SOCKET: &str = "socket";
+
+/// unix socket 的文件名，放在应用目录下
+#[cfg(unix)]
+const UNIX_SOCKET_NAME: &str = "mihomo.sock";
+/// `sockaddr_un.sun_path` 的长度，包含结尾的 0
+#[cfg(target_os = "macos")]
+const UNIX_SOCKET_PATH_MAX: usize = 104;
+#[cfg(all(unix, not(target_os = "macos")))]
+const UNIX_SOCKET_PATH_MAX: usize = 108;
+
+/// 命名管道的前缀，后面加上应用目录的哈希，便携版等多个实例互不冲突
+#[cfg(windows)]
+const PIPE_NAME_PREFIX: &str = r"\\.\pipe\verge-mihomo";
+
+/// 连接池中每个地址保留的空闲连接数，测速时的并发请求大多可以复用
+const POOL_MAX_IDLE: usize = 4;
+/// 空闲连接的保留时间，应短于内核关闭空闲连接的时间
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+const TCP_KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// 当前连接内核控制接口的方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transport {
+    /// `external-controller` 的地址，如 `127.0.0.1:9097`
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(PathBuf),
+    #[cfg(windows)]
+    Pipe(String),
+}
+
+impl Transport {
+    /// 以推送给内核的运行时配置为准，切换方式后在内核重启前仍然使用原来的连接
+    pub fn current() -> Self {
+        let local = {
+            let runtime = Config::runtime();
+            let runtime = runtime.latest();
+            runtime.config.as_ref().and_then(|config| {
+                #[cfg(unix)]
+                let key = "external-controller-unix";
+                #[cfg(windows)]
+                let key = "external-controller-pipe";
+                config.get(key)?.as_str().map(String::from)
+            })
+        };
+        match local.filter(|endpoint| !endpoint.is_empty()) {
+            #[cfg(unix)]
+            Some(path) => Self::Unix(path.into()),
+            #[cfg(windows)]
+            Some(name) => Self::Pipe(name),
+            None => Self::Tcp(Config::clash().data().get_client_info().server),
+        }
+    }
+}
+
+/// 需要注入运行时配置的本地控制接口，未启用时返回 None
+pub fn local_endpoint() -> Result<Option<(&'static str, String)>> {
+    let transport = {
+        Config::verge()
+            .latest()
+            .external_controller_transport
+            .clone()
+    };
+    if transport.as_deref() != Some(TRANSPORT_SOCKET) {
+        return Ok(None);
+    }
+    #[cfg(unix)]
+    let endpoint = (
+        "external-controller-unix",
+        dirs::path_to_str(&unix_socket_path()?)?.into(),
+    );
+    #[cfg(windows)]
+    let endpoint = ("external-controller-pipe", pipe_name_in(&dirs::app_home_dir()?));
+    Ok(Some(endpoint))
+}
+
+/// 按应用目录区分的命名管道名称
+#[cfg(windows)]
+fn pipe_name_in(home: &Path) -> String {
+    format!("{PIPE_NAME_PREFIX}-{}", dir_hash(home))
+}
+
+/// 应用目录下的 `mihomo.sock`，路径过长时改用临时目录
+#[cfg(unix)]
+pub fn unix_socket_path() -> Result<PathBuf> {
+    Ok(socket_path_in(
+        &dirs::app_home_dir()?,
+        &std::env::temp_dir(),
+    ))
+}
+
+#[cfg(unix)]
+fn socket_path_in(home: &Path, temp: &Path) -> PathBuf {
+    let path = home.join(UNIX_SOCKET_NAME);
+    if path.as_os_str().len() < UNIX_SOCKET_PATH_MAX {
+        return path;
+    }
+    // 便携版等目录较深时超出 sun_path 的长度，按应用目录区分不同的实例
+    temp.join(format!("verge-mihomo-{}.sock", dir_hash(home)))
+}
+
+/// 应用目录的短哈希，用于区分不同实例的本地连接
+fn dir_hash(home: &Path) -> String {
+    use sha2::{Digest, Sha256};
+    let hash = Sha256::digest(home.as_os_str().as_encoded_bytes());
+    hash[..4].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// 启动内核前删除上次异常退出留下的 socket 文件，否则内核无法监听
+/// 仍然可以连接的说明有内核在使用，不做处理
+pub fn remove_stale_socket() {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+
+        let Ok(Some((_, path))) = local_endpoint() else {
+            return;
+        };
+        let is_socket =
+            std::fs::symlink_metadata(&path).is_ok_and(|meta| meta.file_type().is_socket());
+        if !is_socket || std::os::unix::net::UnixStream::connect(&path).is_ok() {
+            return;
+        }
+        match std::fs::remove_file(&path) {
+            Ok(_) => log::info!(target: "app", "removed stale controller socket {path}"),
+            Err(err) => log::warn!(target: "app", "failed to remove stale socket {path}: {err}"),
+        }
+    }
+}
+
+/// 控制类请求（读取、修改配置等）默认的超时
+pub const CONTROL_TIMEOUT: Duration = Duration::from_secs(5);
+/// GET/PUT 是幂等的，连接失败或超时时默认重试的次数
+const IDEMPOTENT_RETRIES: u32 = 2;
+/// 第一次重试前等待的时间，之后每次加倍，并加上最多一半的随机抖动
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+
+/// 控制接口请求的错误，调用方可以据此区分内核没有运行、无响应和拒绝了请求
+#[derive(Debug)]
+pub enum ControllerError {
+    /// 无法建立连接，内核没有运行或正在重启
+    Connect(String),
+    /// 在超时时间内没有完成请求
+    Timeout,
+    /// 内核返回了错误的状态码
+    Http { status: u16, body: String },
+}
+
+impl std::fmt::Display for ControllerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Connect(err) => write!(f, "failed to connect to the core: {err}"),
+            Self::Timeout => write!(f, "the request to the core timed out"),
+            Self::Http { status, body } => {
+                write!(f, "the core responded with status \"{status}\"\n{body}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ControllerError {}
+
+pub fn is_timeout(err: &anyhow::Error) -> bool {
+    matches!(err.downcast_ref(), Some(ControllerError::Timeout))
+}
+
+pub fn is_connect_error(err: &anyhow::Error) -> bool {
+    matches!(err.downcast_ref(), Some(ControllerError::Connect(_)))
+}
+
+/// 第 `attempt` 次重试前等待的时间，抖动避免多个请求同时重试
+fn retry_delay(attempt: u32, jitter: u16) -> Duration {
+    let base = RETRY_BASE_DELAY * 2u32.pow(attempt.saturating_sub(1));
+    let jitter = base / 2 * jitter as u32 / u16::MAX as u32;
+    base + jitter
+}
+
+pub fn random_jitter() -> u16 {
+    let mut bytes = [0u8; 2];
+    let _ = getrandom::getrandom(&mut bytes);
+    u16::from_le_bytes(bytes)
+}
+
+/// 发往控制接口的请求，`path` 以 `/` 开头且已经编码
+/// 默认使用 `CONTROL_TIMEOUT`，GET/PUT 在连接失败或超时时重试
+/// 内核返回的错误重试也不会变化，不会重试
+pub struct Request {
+    method: Method,
+    path: String,
+    body: Option<serde_json::Result<Vec<u8>>>,
+    timeout: Duration,
+    retries: u32,
+    /// 为 false 时只在连接失败时重试
+    retry_timeout: bool,
+}
+
+pub fn get(path: impl Into<String>) -> Request {
+    Request::new(Method::GET, path)
+}
+
+pub fn put(path: impl Into<String>) -> Request {
+    Request::new(Method::PUT, path)
+}
+
+pub fn patch(path: impl Into<String>) -> Request {
+    Request::new(Method::PATCH, path)
+}
+
+pub fn post(path: impl Into<String>) -> Request {
+    Request::new(Method::POST, path)
+}
+
+pub fn delete(path: impl Into<String>) -> Request {
+    Request::new(Method::DELETE, path)
+}
+
+impl Request {
+    pub fn new(method: Method, path: impl Into<String>) -> Self {
+        let retries = match method {
+            Method::GET | Method::PUT => IDEMPOTENT_RETRIES,
+            _ => 0,
+        };
+        Self {
+            method,
+            path: path.into(),
+            body: None,
+            timeout: CONTROL_TIMEOUT,
+            retries,
+            retry_timeout: true,
+        }
+    }
+
+    pub fn query(mut self, query: &[(&str, &str)]) -> Self {
+        if !query.is_empty() {
+            let separator = if self.path.contains('?') { '&' } else { '?' };
+            self.path.push(separator);
+            self.path.push_str(&encode_query(query));
+        }
+        self
+    }
+
+    pub fn json<T: Serialize + ?Sized>(mut self, body: &T) -> Self {
+        self.body = Some(serde_json::to_vec(body));
+        self
+    }
+
+    /// 每次尝试的超时，包括建立连接和读取响应的时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 超时本身就是结果（如测速）或耗时很长的请求不应重试
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// 超时后内核可能仍在处理（如重新加载配置），重发只会叠加等待时间，只重试连接失败
+    pub fn connect_retries_only(mut self) -> Self {
+        self.retry_timeout = false;
+        self
+    }
+
+    pub async fn send(mut self) -> Result<Response> {
+        let body = self.body.take().transpose()?.map(Bytes::from);
+        let mut attempt = 0;
+        loop {
+            let result = tokio::time::timeout(self.timeout, self.send_once(body.clone()))
+                .await
+                .unwrap_or_else(|_| Err(ControllerError::Timeout.into()));
+            match result {
+                Err(err) if attempt < self.retries && self.is_retryable(&err) => {
+                    attempt += 1;
+                    let delay = retry_delay(attempt, random_jitter());
+                    let path = self.path.split('?').next().unwrap_or_default();
+                    log::debug!(target: "app", "retry {} {path} in {delay:?}: {err}", self.method);
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn is_retryable(&self, err: &anyhow::Error) -> bool {
+        is_connect_error(err) || (self.retry_timeout && is_timeout(err))
+    }
+
+    async fn send_once(&self, body: Option<Bytes>) -> Result<Response> {
+        let endpoint = Endpoint::current();
+        let headers = endpoint.headers()?;
+        match &endpoint.transport {
+            Transport::Tcp(server) => {
+                let mut builder = http_client(server)?
+                    .request(self.method.clone(), endpoint.url("http", &self.path))
+                    .headers(headers);
+                if let Some(body) = body {
+                    builder = builder.body(body);
+                }
+                let response = builder.send().await.map_err(|err| {
+                    if err.is_connect() {
+                        ControllerError::Connect(err.to_string()).into()
+                    } else {
+                        anyhow::Error::new(err)
+                    }
+                })?;
+                let status = response.status();
+                let body = response.bytes().await?;
+                Ok(Response { status, body })
+            }
+            #[cfg(unix)]
+            Transport::Unix(path) => {
+                let stream = tokio::net::UnixStream::connect(path)
+                    .await
+                    .map_err(|err| {
+                        ControllerError::Connect(format!("{}: {err}", path.display()))
+                    })?;
+                self.send_over(stream, headers, body).await
+            }
+            #[cfg(windows)]
+            Transport::Pipe(name) => {
+                let stream = tokio::net::windows::named_pipe::ClientOptions::new()
+                    .open(name)
+                    .map_err(|err| ControllerError::Connect(format!("{name}: {err}")))?;
+                self.send_over(stream, headers, body).await
+            }
+        }
+    }
+
+    /// 在本地连接上发送一次 HTTP/1.1 请求
+    async fn send_over<S>(
+        &self,
+        stream: S,
+        headers: HeaderMap,
+        body: Option<Bytes>,
+    ) -> Result<Response>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        use http_body_util::{BodyExt, Full};
+
+        let io = hyper_util::rt::TokioIo::new(stream);
+        let (mut sender, connection) = hyper::client::conn::http1::handshake(io).await?;
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = connection.await {
+                log::debug!(target: "app", "controller connection closed: {err}");
+            }
+        });
+
+        let mut builder = hyper::Request::builder()
+            .method(self.method.clone())
+            .uri(&self.path)
+            .header(hyper::header::HOST, "localhost");
+        for (key, value) in headers.iter() {
+            builder = builder.header(key, value);
+        }
+        let body = Full::new(body.unwrap_or_default());
+        let response = sender.send_request(builder.body(body)?).await?;
+        let status = response.status();
+        let body = response.into_body().collect().await?.to_bytes();
+        Ok(Response { status, body })
+    }
+}
+
+/// 已经读取完的响应
+#[derive(Debug)]
+pub struct Response {
+    status: StatusCode,
+    body: Bytes,
+}
+
+impl Response {
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    pub fn error_for_status(self) -> Result<Self> {
+        if self.status.is_client_error() || self.status.is_server_error() {
+            let status = self.status.as_u16();
+            bail!(ControllerError::Http {
+                status,
+                body: self.text(),
+            });
+        }
+        Ok(self)
+    }
+}
+
+/// 本次启动内核使用的密钥，还没有生成运行时配置时使用 config.yaml 中的
+pub fn secret() -> Option<String> {
+    let secret = { Config::runtime().latest().secret.clone() };
+    secret.or_else(|| Config::clash().data().get_client_info().secret)
+}
+
+/// 一次请求使用的控制接口地址和密钥，HTTP 请求、WebSocket 和快照缓存都从这里读取，
+/// 内核重启更换地址或密钥后下一个请求即使用新的
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub transport: Transport,
+    pub secret: Option<String>,
+}
+
+impl Endpoint {
+    pub fn current() -> Self {
+        Self {
+            transport: Transport::current(),
+            secret: secret(),
+        }
+    }
+
+    /// 请求的完整地址，本地连接时主机名只是占位
+    fn url(&self, scheme: &str, path: &str) -> String {
+        match &self.transport {
+            Transport::Tcp(server) => format!("{scheme}://{server}{path}"),
+            _ => format!("{scheme}://localhost{path}"),
+        }
+    }
+
+    fn headers(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Type", "application/json".parse()?);
+
+        if let Some(secret) = self.secret.as_deref().filter(|secret| !secret.is_empty()) {
+            let secret = format!("Bearer {}", secret).parse()?;
+            headers.insert("Authorization", secret);
+        }
+        Ok(headers)
+    }
+}
+
+/// TCP 方式共用的客户端和它对应的地址
+static HTTP_CLIENT: OnceCell<Mutex<Option<(String, reqwest::Client)>>> = OnceCell::new();
+
+/// 复用同一个客户端的连接池，避免频繁请求时每次都新建连接
+/// 地址变化后重新创建，旧地址的空闲连接随旧客户端一起释放
+fn http_client(server: &str) -> Result<reqwest::Client> {
+    let mut cached = HTTP_CLIENT.get_or_init(|| Mutex::new(None)).lock();
+    if let Some((address, client)) = cached.as_ref() {
+        if address == server {
+            return Ok(client.clone());
+        }
+    }
+    let client = reqwest::ClientBuilder::new()
+        .no_proxy()
+        .pool_max_idle_per_host(POOL_MAX_IDLE)
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .tcp_keepalive(TCP_KEEPALIVE)
+        .build()?;
+    *cached = Some((server.into(), client.clone()));
+    Ok(client)
+}
+
+/// 内核重启后丢弃连接池，地址不变时也不再复用连到旧进程的连接
+pub fn reset_http_client() {
+    if let Some(cached) = HTTP_CLIENT.get() {
+        cached.lock().take();
+    }
+}
+
+fn encode_query(query: &[(&str, &str)]) -> String {
+    query
+        .iter()
+        .map(|(key, value)| {
+            let value = percent_encoding::utf8_percent_encode(value, NON_ALPHANUMERIC);
+            format!("{key}={value}")
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// WebSocket 无法设置请求头，密钥通过 token 参数传递
+fn ws_path(path: &str, query: &[(&str, &str)], secret: Option<&str>) -> String {
+    let mut query = query.to_vec();
+    if let Some(secret) = secret.filter(|secret| !secret.is_empty()) {
+        query.push(("token", secret));
+    }
+    if query.is_empty() {
+        return path.into();
+    }
+    format!("{path}?{}", encode_query(&query))
+}
+
+pub type WsStream = BoxStream<'static, Result<Message, tungstenite::Error>>;
+
+/// 连接控制接口的 WebSocket，每次连接时重新读取地址和密钥，修改后重连即可生效
+pub async fn connect_ws(path: &str, query: &[(&str, &str)]) -> Result<WsStream> {
+    let endpoint = Endpoint::current();
+    let url = endpoint.url("ws", &ws_path(path, query, endpoint.secret.as_deref()));
+    match &endpoint.transport {
+        Transport::Tcp(_) => {
+            let (stream, _) = tokio_tungstenite::connect_async(url).await?;
+            Ok(stream.boxed())
+        }
+        #[cfg(unix)]
+        Transport::Unix(socket) => {
+            let stream = tokio::net::UnixStream::connect(socket)
+                .await
+                .with_context(|| format!("failed to connect to {}", socket.display()))?;
+            let (stream, _) = tokio_tungstenite::client_async(url, stream).await?;
+            Ok(stream.boxed())
+        }
+        #[cfg(windows)]
+        Transport::Pipe(name) => {
+            let stream = tokio::net::windows::named_pipe::ClientOptions::new()
+                .open(name)
+                .with_context(|| format!("failed to connect to {name}"))?;
+            let (stream, _) = tokio_tungstenite::client_async(url, stream).await?;
+            Ok(stream.boxed())
+        }
+    }
+}
+
+#[test]
+fn test_ws_path() {
+    assert_eq!(ws_path("/traffic", &[], None), "/traffic");
+    assert_eq!(ws_path("/memory", &[], Some("")), "/memory");
+    assert_eq!(
+        ws_path("/logs", &[("level", "info")], Some("a b&c")),
+        "/logs?level=info&token=a%20b%26c"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_socket_path_in() {
+    let temp = Path::new("/tmp");
+    let home = Path::new("/home/user/.local/share/io.github.clash-verge-rev.clash-verge-rev");
+    assert_eq!(socket_path_in(home, temp), home.join("mihomo.sock"));
+
+    let long = format!("/Users/{}/Library/Application Support/app", "x".repeat(80));
+    let long = PathBuf::from(long);
+    let path = socket_path_in(&long, temp);
+    assert!(path.starts_with(temp));
+    assert!(path.as_os_str().len() < UNIX_SOCKET_PATH_MAX);
+    // 同一个应用目录得到相同的路径
+    assert_eq!(path, socket_path_in(&long, temp));
+}
+
+#[cfg(windows)]
+#[test]
+fn test_pipe_name_in() {
+    let home = Path::new(r"C:\Users\user\AppData\Roaming\clash-verge-rev");
+    let portable = Path::new(r"D:\Clash Verge\.config\clash-verge-rev");
+    let name = pipe_name_in(home);
+    assert!(name.starts_with(PIPE_NAME_PREFIX));
+    // 同一个应用目录得到相同的名称，不同的应用目录互不冲突
+    assert_eq!(name, pipe_name_in(home));
+    assert_ne!(name, pipe_name_in(portable));
+}
+
+#[test]
+fn test_retry_delay() {
+    assert_eq!(retry_delay(1, 0), Duration::from_millis(200));
+    assert_eq!(retry_delay(2, 0), Duration::from_millis(400));
+    assert_eq!(retry_delay(2, u16::MAX), Duration::from_millis(600));
+    let delay = retry_delay(1, u16::MAX / 2);
+    assert!(delay > Duration::from_millis(200) && delay < Duration::from_millis(300));
+}
+
+#[test]
+fn test_controller_error() {
+    let timeout = anyhow::Error::new(ControllerError::Timeout);
+    let connect = anyhow::Error::new(ControllerError::Connect("refused".into()));
+    let http = anyhow::Error::new(ControllerError::Http {
+        status: 503,
+        body: "".into(),
+    });
+
+    let request = put("/configs");
+    assert!(is_timeout(&timeout) && request.is_retryable(&timeout));
+    assert!(is_connect_error(&connect) && request.is_retryable(&connect));
+    assert!(!is_timeout(&http) && !is_connect_error(&http) && !request.is_retryable(&http));
+    assert!(!request.is_retryable(&anyhow::anyhow!("other")));
+
+    let request = request.connect_retries_only();
+    assert!(!request.is_retryable(&timeout));
+    assert!(request.is_retryable(&connect));
+
+    let response = Response {
+        status: StatusCode::BAD_REQUEST,
+        body: Bytes::from_static(b"bad config"),
+    };
+    let err = response.error_for_status().unwrap_err();
+    assert!(matches!(
+        err.downcast_ref(),
+        Some(ControllerError::Http { status: 400, .. })
+    ));
+    assert!(err.to_string().contains("bad config"));
+}
+
+#[test]
+fn test_endpoint_url() {
+    let endpoint = Endpoint {
+        transport: Transport::Tcp("127.0.0.1:9097".into()),
+        secret: None,
+    };
+    assert_eq!(endpoint.url("http", "/proxies"), "http://127.0.0.1:9097/proxies");
+    assert_eq!(endpoint.url("ws", "/logs?level=info"), "ws://127.0.0.1:9097/logs?level=info");
+
+    #[cfg(unix)]
+    {
+        let endpoint = Endpoint {
+            transport: Transport::Unix("/tmp/mihomo.sock".into()),
+            secret: None,
+        };
+        assert_eq!(endpoint.url("ws", "/traffic"), "ws://localhost/traffic");
+    }
+}
@@ -1,6 +1,8 @@
+use crate::core::validation::ValidateErrorCode;
 use crate::log_err;
 use once_cell::sync::OnceCell;
 use parking_lot::RwLock;
+use serde::Serialize;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
 
@@ -63,6 +65,23 @@ impl Handle {
         }
     }
 
+    /// 发送验证失败的通知，附带错误码供前端翻译，消息为默认的英文描述
+    pub fn notice_error<S: Into<String>>(status: S, error: &ValidateErrorCode) {
+        if let Some(window) = Self::global().get_window() {
+            log_err!(window.emit(
+                "verge://notice-message",
+                (status.into(), error.to_string(), error)
+            ));
+        }
+    }
+
+    /// 向前端发送自定义事件
+    pub fn emit_event<P: Serialize + Clone>(event: &str, payload: P) {
+        if let Some(window) = Self::global().get_window() {
+            log_err!(window.emit(event, payload));
+        }
+    }
+
     pub fn set_is_exiting(&self) {
         let mut is_exiting = self.is_exiting.write();
         *is_exiting = true;
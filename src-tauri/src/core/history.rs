@@ -0,0 +1,156 @@
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::Path;
+
+/// 内存中最多保留的事件数量，文件超过两倍时按内存中的事件重写
+pub const CORE_HISTORY_LIMIT: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoreEventKind {
+    Start,
+    StartFailed,
+    Stop,
+    StopFailed,
+    Restart,
+    Crash,
+    ChangeCore,
+    ValidationFailed,
+}
+
+/// 内核的一次生命周期事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoreEvent {
+    /// Unix 时间戳（毫秒）
+    pub timestamp: i64,
+    pub event: CoreEventKind,
+    pub detail: String,
+}
+
+/// 最近的内核生命周期事件，同时追加到 JSON Lines 文件中，重启应用后仍可查看
+#[derive(Debug)]
+pub struct CoreHistory {
+    limit: usize,
+    events: Mutex<VecDeque<CoreEvent>>,
+    /// 文件中的行数，用于判断何时重写
+    file_lines: Mutex<usize>,
+}
+
+impl CoreHistory {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            events: Mutex::new(VecDeque::with_capacity(limit)),
+            file_lines: Mutex::new(0),
+        }
+    }
+
+    /// 读取上次运行记录的事件，无法解析的行会被跳过
+    pub fn load(&self, file: &Path) {
+        let Ok(content) = std::fs::read_to_string(file) else {
+            return;
+        };
+        let lines = content.lines().collect::<Vec<_>>();
+        *self.file_lines.lock() = lines.len();
+
+        let mut events = self.events.lock();
+        let loaded = lines
+            .iter()
+            .filter_map(|line| serde_json::from_str::<CoreEvent>(line).ok())
+            .collect::<Vec<_>>();
+        // 加载前已经记录的事件更新，放在后面
+        let recorded = events.drain(..).collect::<Vec<_>>();
+        events.extend(loaded.into_iter().chain(recorded));
+        while events.len() > self.limit {
+            events.pop_front();
+        }
+    }
+
+    pub fn push(&self, event: CoreEvent, file: Option<&Path>) {
+        let snapshot = {
+            let mut events = self.events.lock();
+            events.push_back(event.clone());
+            while events.len() > self.limit {
+                events.pop_front();
+            }
+            events.clone()
+        };
+        if let Some(file) = file {
+            if let Err(err) = self.append(file, &event, &snapshot) {
+                log::warn!(target: "app", "failed to write core history: {err}");
+            }
+        }
+    }
+
+    pub fn list(&self) -> Vec<CoreEvent> {
+        self.events.lock().iter().cloned().collect()
+    }
+
+    fn append(
+        &self,
+        file: &Path,
+        event: &CoreEvent,
+        snapshot: &VecDeque<CoreEvent>,
+    ) -> anyhow::Result<()> {
+        let mut file_lines = self.file_lines.lock();
+        if *file_lines >= self.limit * 2 {
+            let content = snapshot
+                .iter()
+                .map(|event| serde_json::to_string(event).map(|line| line + "\n"))
+                .collect::<Result<String, _>>()?;
+            std::fs::write(file, content)?;
+            *file_lines = snapshot.len();
+            return Ok(());
+        }
+
+        let mut writer = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(file)?;
+        writeln!(writer, "{}", serde_json::to_string(event)?)?;
+        *file_lines += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp: i64) -> CoreEvent {
+        CoreEvent {
+            timestamp,
+            event: CoreEventKind::Restart,
+            detail: format!("restart {timestamp}"),
+        }
+    }
+
+    #[test]
+    fn test_core_history() {
+        let file = std::env::temp_dir().join(format!(
+            "core-history-{}.jsonl",
+            crate::utils::help::get_uid("t")
+        ));
+        let history = CoreHistory::new(3);
+        for timestamp in 0..5 {
+            history.push(event(timestamp), Some(&file));
+        }
+        let timestamps = history.list().iter().map(|e| e.timestamp).collect::<Vec<_>>();
+        assert_eq!(timestamps, vec![2, 3, 4]);
+
+        // 第7条时文件已有6行，按内存中的事件重写
+        history.push(event(5), Some(&file));
+        history.push(event(6), Some(&file));
+        let content = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(content.lines().count(), 3);
+
+        let loaded = CoreHistory::new(3);
+        loaded.push(event(7), None);
+        loaded.load(&file);
+        let timestamps = loaded.list().iter().map(|e| e.timestamp).collect::<Vec<_>>();
+        assert_eq!(timestamps, vec![5, 6, 7]);
+        std::fs::remove_file(&file).unwrap();
+    }
+}
@@ -1,16 +1,161 @@
 use crate::core::handle;
-use crate::{config::Config, feat, log_err};
-use crate::utils::resolve;
-use anyhow::{bail, Result};
+use crate::{cmds, config::Config, feat};
+use anyhow::{bail, Context, Result};
 use once_cell::sync::OnceCell;
 use parking_lot::Mutex;
-use std::{collections::HashMap, sync::Arc};
-use tauri::Manager;
-use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, ShortcutState};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
 use tauri::async_runtime;
 
+/// 默认的热键防抖间隔
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 只在主窗口获得焦点时执行的功能前缀
+const FOCUS_ONLY_PREFIX: &str = "focused:";
+
+/// 可以带参数的功能，其余功能带参数时注册失败
+const ARG_FUNCS: [&str; 5] = [
+    "change_clash_mode",
+    "select_proxy",
+    "toggle_mux",
+    "reload_script",
+    "toggle_mock_mode",
+];
+
+type HotkeyFn = Box<dyn Fn() + Send + Sync>;
+
 pub struct Hotkey {
     current: Arc<Mutex<Vec<String>>>, // 保存当前的热键设置
+    /// 已配置热键的注册结果，按快捷键索引
+    status: Arc<Mutex<HashMap<String, HotkeyStatus>>>,
+    /// 已经通知过前端的注册失败，避免窗口每次获得焦点时重复通知
+    noticed: Arc<Mutex<HashSet<String>>>,
+    /// `current` 中的热键是否已经注册
+    active: Arc<Mutex<bool>>,
+    /// 全局热键关闭、应用内热键开启时，全部热键只在主窗口获得焦点时执行
+    in_app_only: Arc<AtomicBool>,
+    /// 应用句柄就绪前请求注册的热键 `(hotkey, func)`，`init()` 时注册
+    pending: Arc<Mutex<Vec<(String, String)>>>,
+    /// 暂停的次数，如编辑器获得焦点时，大于 0 时不注册热键
+    suspended: Arc<Mutex<usize>>,
+}
+
+/// 一个已配置热键的注册状态
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeyStatus {
+    pub hotkey: String,
+    pub func: String,
+    pub active: bool,
+    /// 应用句柄还没有就绪，已排队等待注册，还没有生效
+    pub pending: bool,
+    /// 注册失败的原因，如快捷键已被其他程序占用
+    pub error: Option<String>,
+    pub kind: Option<HotkeyErrorKind>,
+}
+
+/// 热键注册请求的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Registration {
+    /// 已经向系统注册
+    Registered,
+    /// 应用句柄还没有就绪，已排队，`init()` 时注册
+    Queued,
+}
+
+/// 热键注册失败的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyErrorKind {
+    /// 功能名称或参数无效
+    Invalid,
+    /// 已经由本程序注册
+    AlreadyRegistered,
+    /// 系统拒绝注册，通常是被其他程序占用
+    Conflict,
+    /// 当前平台不支持的按键组合
+    Unsupported,
+    /// 当前会话不支持全局快捷键，如纯 Wayland 会话
+    Unavailable,
+}
+
+/// 注册快捷键时插件返回的错误，按类别整理后返回给前端
+#[derive(Debug)]
+pub struct HotkeyError {
+    pub kind: HotkeyErrorKind,
+    pub hotkey: String,
+    pub message: String,
+}
+
+impl HotkeyError {
+    /// 插件只返回错误信息，按信息的内容分类
+    fn classify(hotkey: &str, message: String) -> Self {
+        let lower = message.to_lowercase();
+        let kind = if lower.contains("already registered") && !lower.contains("os error") {
+            HotkeyErrorKind::AlreadyRegistered
+        } else if ["couldn't recognize", "unrecognized", "unexpected", "empty", "parse"]
+            .iter()
+            .any(|pattern| lower.contains(pattern))
+        {
+            HotkeyErrorKind::Unsupported
+        } else {
+            HotkeyErrorKind::Conflict
+        };
+        Self {
+            kind,
+            hotkey: hotkey.to_string(),
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for HotkeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            hotkey, message, ..
+        } = self;
+        match self.kind {
+            HotkeyErrorKind::Invalid => write!(f, "{hotkey}: {message}"),
+            HotkeyErrorKind::AlreadyRegistered => {
+                write!(f, "{hotkey} is already registered by the app")
+            }
+            HotkeyErrorKind::Conflict => write!(
+                f,
+                "{hotkey} was rejected by the system, it may be used by another program: {message}"
+            ),
+            HotkeyErrorKind::Unsupported => write!(
+                f,
+                "{hotkey} is not a supported key combination on this platform: {message}"
+            ),
+            HotkeyErrorKind::Unavailable => f.write_str(SESSION_UNSUPPORTED),
+        }
+    }
+}
+
+impl std::error::Error for HotkeyError {}
+
+const SESSION_UNSUPPORTED: &str = "global shortcuts not supported in this session";
+
+/// 保存前检查热键的结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HotkeyVerdict {
+    pub valid: bool,
+    /// 功能名称或参数无效
+    pub func_error: Option<String>,
+    /// 快捷键无法解析
+    pub key_error: Option<String>,
+    /// 已经绑定了这个快捷键的其他功能
+    pub conflict: Option<String>,
+    /// 系统拒绝注册，如快捷键已被其他程序占用
+    pub os_error: Option<String>,
 }
 
 impl Hotkey {
@@ -19,106 +164,390 @@ impl Hotkey {
 
         HOTKEY.get_or_init(|| Hotkey {
             current: Arc::new(Mutex::new(Vec::new())),
+            status: Arc::new(Mutex::new(HashMap::new())),
+            noticed: Arc::new(Mutex::new(HashSet::new())),
+            active: Arc::new(Mutex::new(false)),
+            in_app_only: Arc::new(AtomicBool::new(false)),
+            pending: Arc::new(Mutex::new(Vec::new())),
+            suspended: Arc::new(Mutex::new(0)),
         })
     }
 
     pub fn init(&self) -> Result<()> {
-        let verge = Config::verge();
-        let enable_global_hotkey = verge.latest().enable_global_hotkey.unwrap_or(true);
+        let hotkeys = Config::verge().latest().hotkeys.clone().unwrap_or_default();
+        {
+            let _active = self.active.lock();
+            *self.current.lock() = hotkeys;
+        }
+        self.refresh_from_config();
+
+        let pending = std::mem::take(&mut *self.pending.lock());
+        for (hotkey, func) in pending {
+            log::info!(target: "app", "register pending hotkey {hotkey} -> {func}");
+            // 已配置的热键同时更新注册状态，不再显示为排队中
+            let tracked = self.status.lock().contains_key(&hotkey);
+            let result = if tracked {
+                self.register_tracked(&hotkey, &func)
+            } else {
+                self.register(&hotkey, &func)
+            };
+            if let Err(err) = result {
+                log::error!(target: "app", "failed to register pending hotkey {hotkey}: {err}");
+            }
+        }
+        Ok(())
+    }
 
-        println!("Initializing hotkeys, global hotkey enabled: {}", enable_global_hotkey);
-        log::info!(target: "app", "Initializing hotkeys, global hotkey enabled: {}", enable_global_hotkey);
+    /// 启动早期或退出后没有应用句柄，返回错误而不是 panic
+    fn app_handle() -> Result<AppHandle> {
+        handle::Handle::global().app_handle().context("app handle is not ready")
+    }
 
-        // 如果全局热键被禁用，则不注册热键
-        if !enable_global_hotkey {
-            println!("Global hotkey is disabled, skipping registration");
-            log::info!(target: "app", "Global hotkey is disabled, skipping registration");
-            return Ok(());
+    /// 注册 `current` 中的全部热键，已经注册时什么也不做
+    pub fn enable(&self) {
+        // 持有 `active` 直到注册完成，并发的启用、停用和更新不会重复注册
+        let mut active = self.active.lock();
+        if *active || *self.suspended.lock() > 0 {
+            return;
         }
+        let hotkeys = self.current.lock().clone();
+        println!("Found {} hotkeys to register", hotkeys.len());
+        log::info!(target: "app", "Found {} hotkeys to register", hotkeys.len());
+
+        let mut failed = vec![];
+        for hotkey in hotkeys.iter() {
+            let (func, key) = match Self::parse_hotkey(hotkey) {
+                Some((func, key)) => (Some(func), Some(key)),
+                None => (Some(hotkey.as_str()), None),
+            };
 
-        if let Some(hotkeys) = verge.latest().hotkeys.as_ref() {
-            println!("Found {} hotkeys to register", hotkeys.len());
-            log::info!(target: "app", "Found {} hotkeys to register", hotkeys.len());
-
-            for hotkey in hotkeys.iter() {
-                let mut iter = hotkey.split(',');
-                let func = iter.next();
-                let key = iter.next();
-
-                match (key, func) {
-                    (Some(key), Some(func)) => {
-                        println!("Registering hotkey: {} -> {}", key, func);
-                        log::info!(target: "app", "Registering hotkey: {} -> {}", key, func);
-                        if let Err(e) = self.register(key, func) {
-                            println!("Failed to register hotkey {} -> {}: {:?}", key, func, e);
-                            log::error!(target: "app", "Failed to register hotkey {} -> {}: {:?}", key, func, e);
-                        } else {
-                            println!("Successfully registered hotkey {} -> {}", key, func);
-                            log::info!(target: "app", "Successfully registered hotkey {} -> {}", key, func);
-                        }
-                    }
-                    _ => {
-                        let key = key.unwrap_or("None");
-                        let func = func.unwrap_or("None");
-                        println!("Invalid hotkey configuration: `{key}`:`{func}`");
-                        log::error!(target: "app", "Invalid hotkey configuration: `{key}`:`{func}`");
+            match (key, func) {
+                (Some(key), Some(func)) => {
+                    println!("Registering hotkey: {} -> {}", key, func);
+                    log::info!(target: "app", "Registering hotkey: {} -> {}", key, func);
+                    if let Err(e) = self.register_tracked(key, func) {
+                        println!("Failed to register hotkey {} -> {}: {:?}", key, func, e);
+                        log::error!(target: "app", "Failed to register hotkey {} -> {}: {:?}", key, func, e);
+                        failed.push(key.to_string());
+                    } else {
+                        println!("Successfully registered hotkey {} -> {}", key, func);
+                        log::info!(target: "app", "Successfully registered hotkey {} -> {}", key, func);
                     }
                 }
+                _ => {
+                    let key = key.unwrap_or("None");
+                    let func = func.unwrap_or("None");
+                    println!("Invalid hotkey configuration: `{key}`:`{func}`");
+                    log::error!(target: "app", "Invalid hotkey configuration: `{key}`:`{func}`");
+                }
             }
-            self.current.lock().clone_from(hotkeys);
+        }
+        *active = true;
+        self.notice_failures(&failed);
+    }
+
+    /// 注销全部已配置的热键，保留 `current`，重新启用时注册同样的列表
+    pub fn disable(&self) {
+        let mut active = self.active.lock();
+        if !*active {
+            return;
+        }
+        let hotkeys = self.current.lock().clone();
+        for key in Self::get_map_from_vec(&hotkeys).into_keys() {
+            if let Err(err) = self.unregister(key) {
+                log::warn!(target: "app", "failed to unregister hotkey {key}: {err}");
+            }
+        }
+        *active = false;
+    }
+
+    /// 暂时注销全部热键，如编辑器获得焦点时，按次数计数，全部 `resume()` 后才恢复
+    pub fn suspend(&self) {
+        let count = {
+            let mut suspended = self.suspended.lock();
+            *suspended += 1;
+            *suspended
+        };
+        log::debug!(target: "app", "suspend hotkeys ({count})");
+        if count == 1 {
+            self.disable();
+        }
+    }
+
+    /// 结束一次 `suspend()`，没有暂停时什么也不做
+    pub fn resume(&self) {
+        let count = {
+            let mut suspended = self.suspended.lock();
+            if *suspended == 0 {
+                return;
+            }
+            *suspended -= 1;
+            *suspended
+        };
+        log::debug!(target: "app", "resume hotkeys ({count})");
+        if count == 0 {
+            // 暂停期间 `update()` 只记录了新的列表，这里按配置重新注册
+            self.refresh_from_config();
+        }
+    }
+
+    /// 结束全部暂停，如主窗口销毁后前端不会再调用 `resume()`
+    pub fn resume_all(&self) {
+        let suspended = std::mem::take(&mut *self.suspended.lock());
+        if suspended > 0 {
+            log::info!(target: "app", "resume {suspended} suspended hotkey requests");
+            self.refresh_from_config();
+        }
+    }
+
+    /// 按配置启用或停用热键，全局热键关闭时只在窗口获得焦点时注册
+    /// 开启应用内热键时，回调还会再检查一次主窗口是否获得焦点
+    pub fn refresh_from_config(&self) {
+        let (enable_global_hotkey, enable_in_app_hotkey) = {
+            let verge = Config::verge();
+            let verge = verge.latest();
+            (
+                verge.enable_global_hotkey.unwrap_or(true),
+                verge.enable_in_app_hotkey.unwrap_or(false),
+            )
+        };
+        log::info!(
+            target: "app",
+            "refresh hotkeys, global: {enable_global_hotkey}, in-app: {enable_in_app_hotkey}"
+        );
+
+        // 回调按下时读取，切换模式不需要重新注册
+        self.in_app_only
+            .store(!enable_global_hotkey && enable_in_app_hotkey, Ordering::SeqCst);
+        let focused = handle::Handle::global()
+            .get_window()
+            .is_some_and(|window| window.is_focused().unwrap_or(false));
+        if enable_global_hotkey || focused {
+            self.enable();
         } else {
-            println!("No hotkeys configured");
-            log::info!(target: "app", "No hotkeys configured");
+            self.disable();
         }
+    }
 
-        Ok(())
+    /// 已配置热键的注册状态
+    pub fn get_status(&self) -> Vec<HotkeyStatus> {
+        let mut status = self.status.lock().values().cloned().collect::<Vec<_>>();
+        status.sort_by(|a, b| a.hotkey.cmp(&b.hotkey));
+        status
     }
 
-    pub fn reset(&self) -> Result<()> {
-        let app_handle = handle::Handle::global().app_handle().unwrap();
+    /// 检查功能和快捷键能否注册，需要试注册时会立即注销
+    ///
+    /// `pending` 是设置窗口中还没有保存的全部热键，按它检查冲突，而不是已保存的热键
+    pub fn validate(&self, hotkey: &str, func: &str, pending: &[String]) -> HotkeyVerdict {
+        let mut verdict = HotkeyVerdict::default();
+        if let Err(err) = Self::build_func(func) {
+            verdict.func_error = Some(format!("{err:#}"));
+        }
+        let shortcut = match hotkey.trim().parse::<Shortcut>() {
+            Ok(shortcut) => shortcut,
+            Err(err) => {
+                verdict.key_error = Some(err.to_string());
+                return verdict;
+            }
+        };
+        if let Some(conflict) = Self::find_conflict(shortcut, func, pending) {
+            verdict.conflict = Some(conflict);
+            return verdict;
+        }
+
+        // 已保存的热键可能写法不同，按解析后的快捷键比较
+        let current = self.current.lock().clone();
+        let bound = Self::get_map_from_vec(&current)
+            .into_iter()
+            .find(|(key, _)| key.parse::<Shortcut>().is_ok_and(|key| key == shortcut))
+            .map(|(key, _)| key.to_string());
+
+        if Self::session_unsupported() {
+            verdict.os_error = Some(SESSION_UNSUPPORTED.into());
+            return verdict;
+        }
+        let app_handle = match Self::app_handle() {
+            Ok(app_handle) => app_handle,
+            Err(err) => {
+                verdict.os_error = Some(err.to_string());
+                return verdict;
+            }
+        };
         let manager = app_handle.global_shortcut();
-        manager.unregister_all()?;
-        Ok(())
+        match bound {
+            // 快捷键已经由应用注册，保存时会重新绑定，沿用上次注册的结果
+            Some(key) => {
+                verdict.os_error = self.status.lock().get(&key).and_then(|s| s.error.clone());
+            }
+            None if manager.is_registered(shortcut) => {
+                verdict.os_error = Some("the hotkey is already registered by the app".into());
+            }
+            None => {
+                if let Err(err) = manager.register(shortcut) {
+                    let err = HotkeyError::classify(hotkey.trim(), err.to_string());
+                    verdict.os_error = Some(err.to_string());
+                } else if let Err(err) = manager.unregister(shortcut) {
+                    log::warn!(target: "app", "failed to unregister probed hotkey {hotkey}: {err}");
+                }
+            }
+        }
+
+        verdict.valid = verdict.func_error.is_none()
+            && verdict.conflict.is_none()
+            && verdict.os_error.is_none();
+        verdict
     }
 
-    pub fn register(&self, hotkey: &str, func: &str) -> Result<()> {
-        let app_handle = handle::Handle::global().app_handle().unwrap();
-        let manager = app_handle.global_shortcut();
+    /// 在未保存的热键中查找绑定了同一个快捷键的其他功能
+    fn find_conflict(shortcut: Shortcut, func: &str, pending: &[String]) -> Option<String> {
+        pending
+            .iter()
+            .filter_map(|hotkey| Self::parse_hotkey(hotkey))
+            .filter(|(other, _)| *other != func.trim())
+            .find(|(_, key)| key.parse::<Shortcut>().is_ok_and(|key| key == shortcut))
+            .map(|(other, _)| other.to_string())
+    }
 
-        println!("Attempting to register hotkey: {} for function: {}", hotkey, func);
-        log::info!(target: "app", "Attempting to register hotkey: {} for function: {}", hotkey, func);
+    /// 当前会话是否无法使用全局快捷键，只检测一次
+    fn session_unsupported() -> bool {
+        static UNSUPPORTED: OnceCell<bool> = OnceCell::new();
+        *UNSUPPORTED.get_or_init(|| {
+            // 纯 Wayland 会话（没有 XWayland）无法通过 X11 抓取全局快捷键
+            #[cfg(target_os = "linux")]
+            {
+                let wayland = std::env::var("XDG_SESSION_TYPE")
+                    .is_ok_and(|session| session.eq_ignore_ascii_case("wayland"))
+                    || std::env::var_os("WAYLAND_DISPLAY").is_some();
+                let unsupported = wayland && std::env::var_os("DISPLAY").is_none();
+                if unsupported {
+                    log::warn!(target: "app", "{SESSION_UNSUPPORTED}");
+                }
+                unsupported
+            }
+            #[cfg(not(target_os = "linux"))]
+            false
+        })
+    }
 
-        if manager.is_registered(hotkey) {
-            println!("Hotkey {} was already registered, unregistering first", hotkey);
-            log::info!(target: "app", "Hotkey {} was already registered, unregistering first", hotkey);
-            manager.unregister(hotkey)?;
+    /// 注册已配置的热键并记录结果
+    fn register_tracked(&self, hotkey: &str, func: &str) -> Result<Registration> {
+        let result = if Self::session_unsupported() {
+            Err(HotkeyError {
+                kind: HotkeyErrorKind::Unavailable,
+                hotkey: hotkey.to_string(),
+                message: SESSION_UNSUPPORTED.into(),
+            }
+            .into())
+        } else {
+            self.register(hotkey, func)
+        };
+        let kind = result.as_ref().err().map(|err| {
+            err.downcast_ref::<HotkeyError>()
+                .map_or(HotkeyErrorKind::Invalid, |err| err.kind)
+        });
+        let status = HotkeyStatus {
+            hotkey: hotkey.to_string(),
+            func: func.to_string(),
+            active: matches!(result, Ok(Registration::Registered)),
+            pending: matches!(result, Ok(Registration::Queued)),
+            error: result.as_ref().err().map(|err| format!("{err:#}")),
+            kind,
+        };
+        if result.is_ok() {
+            self.noticed.lock().remove(hotkey);
         }
+        self.status.lock().insert(hotkey.to_string(), status);
+        result
+    }
 
-        let f = match func.trim() {
-            "open_or_close_dashboard" => {
-                println!("Registering open_or_close_dashboard function");
-                log::info!(target: "app", "Registering open_or_close_dashboard function");
-                || {
-                    println!("=== Hotkey Dashboard Window Operation Start ===");
-                    log::info!(target: "app", "=== Hotkey Dashboard Window Operation Start ===");
-                    
-                    // 使用 spawn_blocking 来确保在正确的线程上执行
-                    async_runtime::spawn_blocking(|| {
-                        println!("Creating window in spawn_blocking");
-                        log::info!(target: "app", "Creating window in spawn_blocking");
-                        resolve::create_window();
-                    });
-                    
-                    println!("=== Hotkey Dashboard Window Operation End ===");
-                    log::info!(target: "app", "=== Hotkey Dashboard Window Operation End ===");
-                }
-            },
-            "clash_mode_rule" => || feat::change_clash_mode("rule".into()),
-            "clash_mode_global" => || feat::change_clash_mode("global".into()),
-            "clash_mode_direct" => || feat::change_clash_mode("direct".into()),
-            "toggle_system_proxy" => || feat::toggle_system_proxy(),
-            "toggle_tun_mode" => || feat::toggle_tun_mode(),
-            "quit" => || feat::quit(Some(0)),
+    /// 通知前端本次注册失败且还没有通知过的热键
+    fn notice_failures(&self, failed: &[String]) {
+        let mut noticed = self.noticed.lock();
+        // 会话不支持时只通知一次，不逐个列出热键
+        if Self::session_unsupported() {
+            if !failed.is_empty() && noticed.insert(SESSION_UNSUPPORTED.into()) {
+                handle::Handle::notice_message("hotkey::register_failed", SESSION_UNSUPPORTED);
+            }
+            return;
+        }
+        let status = self.status.lock();
+        let message = failed
+            .iter()
+            .filter(|key| noticed.insert(key.to_string()))
+            .filter_map(|key| status.get(key))
+            .map(|status| {
+                let error = status.error.as_deref().unwrap_or_default();
+                format!("{} ({}): {error}", status.hotkey, status.func)
+            })
+            .collect::<Vec<_>>();
+        if !message.is_empty() {
+            handle::Handle::notice_message("hotkey::register_failed", message.join("\n"));
+        }
+    }
+
+    /// 按功能名称和参数生成热键的回调，返回回调和是否只在窗口获得焦点时执行
+    fn build_func(func: &str) -> Result<(HotkeyFn, bool)> {
+        // 以 `focused:` 开头的功能只在主窗口获得焦点时执行，如 `focused:toggle_tun_mode,{key}`
+        let (focus_only, func) = match func.trim().strip_prefix(FOCUS_ONLY_PREFIX) {
+            Some(func) => (true, func),
+            None => (false, func),
+        };
+
+        // 带参数的功能格式为 `{func},{arg}` 或 `{func}:{arg}`
+        let (name, args) = Self::split_args(func);
+        if args.is_some() && !ARG_FUNCS.contains(&name) {
+            bail!("function \"{name}\" does not take arguments");
+        }
+        let arg = args.map(String::from);
+
+        let f: HotkeyFn = match name {
+            // 在阻塞线程中执行，避免在快捷键回调的线程上创建窗口
+            "open_or_close_dashboard" => Box::new(|| {
+                async_runtime::spawn_blocking(feat::toggle_dashboard);
+            }),
+            "clash_mode_rule" => Box::new(|| feat::change_clash_mode("rule".into())),
+            "clash_mode_global" => Box::new(|| feat::change_clash_mode("global".into())),
+            "clash_mode_direct" => Box::new(|| feat::change_clash_mode("direct".into())),
+            "cycle_clash_mode" => Box::new(feat::cycle_clash_mode),
+            "toggle_system_proxy" => Box::new(feat::toggle_system_proxy_with_notice),
+            "toggle_tun_mode" => Box::new(feat::toggle_tun_mode_with_notice),
+            "toggle_lightweight_mode" => Box::new(feat::toggle_lightweight_mode),
+            "restart_core" => Box::new(feat::restart_clash_core),
+            "next_profile" => Box::new(feat::next_profile),
+            "prev_profile" => Box::new(feat::prev_profile),
+            "copy_proxy_env" => Box::new(feat::copy_proxy_env),
+            "open_app_dir" => Box::new(|| feat::reveal_dir(cmds::open_app_dir)),
+            "open_core_dir" => Box::new(|| feat::reveal_dir(cmds::open_core_dir)),
+            "force_reconnect_all" => Box::new(feat::force_reconnect_all),
+            "clear_connections" => Box::new(feat::clear_connections),
+            "check_connectivity_now" => Box::new(feat::check_connectivity_now),
+            "rotate_sidecar_log" => Box::new(feat::rotate_sidecar_log),
+            "toggle_ipv6_routing" => Box::new(feat::toggle_ipv6_routing),
+            "save_and_reload" => Box::new(feat::save_and_reload),
+            "export_diagnostics" => Box::new(feat::export_diagnostics),
+            "change_clash_mode" => {
+                let mode = match args {
+                    Some(mode @ ("rule" | "global" | "direct")) => mode.to_string(),
+                    _ => bail!("change_clash_mode expects rule, global or direct"),
+                };
+                Box::new(move || feat::change_clash_mode(mode.clone()))
+            }
+            "select_proxy" => {
+                let Some((group, proxy)) = args.and_then(Self::split_proxy_args) else {
+                    bail!("select_proxy expects select_proxy:<group>:<proxy>");
+                };
+                let (group, proxy) = (group.to_string(), proxy.to_string());
+                Box::new(move || feat::select_proxy(group.clone(), proxy.clone()))
+            }
+            "toggle_mux" => Box::new(move || feat::toggle_mux(arg.clone())),
+            "reload_script" => Box::new(move || feat::reload_script(arg.clone())),
+            #[cfg(any(debug_assertions, feature = "mock-mode"))]
+            "toggle_mock_mode" => {
+                let port = arg.as_deref().and_then(|port| port.parse().ok());
+                Box::new(move || feat::toggle_mock_mode(port))
+            }
+            "quit" => Box::new(|| feat::quit(Some(0))),
 
             _ => {
                 println!("Invalid function: {}", func);
@@ -127,20 +556,59 @@ impl Hotkey {
             }
         };
 
-        let is_quit = func.trim() == "quit";
+        // 退出无论绑定到哪个键都要求窗口获得焦点，避免在其他程序中误触
+        let focus_only = focus_only || name == "quit";
+        Ok((f, focus_only))
+    }
+
+    /// 注册热键，应用句柄还没有就绪时排队并返回 [`Registration::Queued`]
+    pub fn register(&self, hotkey: &str, func: &str) -> Result<Registration> {
+        println!("Attempting to register hotkey: {} for function: {}", hotkey, func);
+        log::info!(target: "app", "Attempting to register hotkey: {} for function: {}", hotkey, func);
+
+        let (f, focus_only) = Self::build_func(func)?;
+
+        // 应用句柄还没有就绪时先排队，`init()` 时再注册
+        let Ok(app_handle) = Self::app_handle() else {
+            log::info!(target: "app", "app handle is not ready, queue hotkey {hotkey}");
+            let mut pending = self.pending.lock();
+            pending.retain(|(key, _)| key != hotkey);
+            pending.push((hotkey.to_string(), func.to_string()));
+            return Ok(Registration::Queued);
+        };
+        let manager = app_handle.global_shortcut();
+
+        if manager.is_registered(hotkey) {
+            println!("Hotkey {} was already registered, unregistering first", hotkey);
+            log::info!(target: "app", "Hotkey {} was already registered, unregistering first", hotkey);
+            manager.unregister(hotkey)?;
+        }
 
-        let _ = manager.on_shortcut(hotkey, move |app_handle, hotkey, event| {
+        let in_app_only = self.in_app_only.clone();
+        let last_pressed = Mutex::new(None);
+        // 插件的错误按类别整理，前端据此提示冲突或不支持的按键
+        let result = manager.on_shortcut(hotkey, move |app_handle, hotkey, event| {
             if event.state == ShortcutState::Pressed {
                 println!("Hotkey pressed: {:?}", hotkey);
                 log::info!(target: "app", "Hotkey pressed: {:?}", hotkey);
 
-                if hotkey.key == Code::KeyQ && is_quit {
-                    if let Some(window) = app_handle.get_webview_window("main") {
-                        if window.is_focused().unwrap_or(false) {
-                            println!("Executing quit function");
-                            log::info!(target: "app", "Executing quit function");
-                            f();
-                        }
+                // 按住按键时系统会重复发送按下事件，间隔太短的忽略
+                let window = Self::debounce_window();
+                if !Self::debounce(&mut last_pressed.lock(), Instant::now(), window) {
+                    log::debug!(target: "app", "debounce hotkey {:?}", hotkey);
+                    return;
+                }
+
+                if focus_only || in_app_only.load(Ordering::SeqCst) {
+                    let focused = app_handle
+                        .get_webview_window("main")
+                        .is_some_and(|window| window.is_focused().unwrap_or(false));
+                    if focused {
+                        println!("Executing focus-only function");
+                        log::info!(target: "app", "Executing focus-only function");
+                        f();
+                    } else {
+                        log::info!(target: "app", "window is not focused, ignore hotkey {:?}", hotkey);
                     }
                 } else {
                     // 直接执行函数，不做任何状态检查
@@ -150,14 +618,26 @@ impl Hotkey {
                 }
             }
         });
+        result.map_err(|err| HotkeyError::classify(hotkey, err.to_string()))?;
 
         println!("Successfully registered hotkey {} for {}", hotkey, func);
         log::info!(target: "app", "Successfully registered hotkey {} for {}", hotkey, func);
-        Ok(())
+        Ok(Registration::Registered)
     }
 
     pub fn unregister(&self, hotkey: &str) -> Result<()> {
-        let app_handle = handle::Handle::global().app_handle().unwrap();
+        self.status.lock().remove(hotkey);
+        let app_handle = {
+            let mut pending = self.pending.lock();
+            let queued = pending.len();
+            pending.retain(|(key, _)| key != hotkey);
+            match Self::app_handle() {
+                Ok(app_handle) => app_handle,
+                // 还在排队的热键直接移出队列
+                Err(_) if pending.len() < queued => return Ok(()),
+                Err(err) => return Err(err),
+            }
+        };
         let manager = app_handle.global_shortcut();
         manager.unregister(hotkey)?;
         log::debug!(target: "app", "unregister hotkey {hotkey}");
@@ -165,41 +645,95 @@ impl Hotkey {
     }
 
     pub fn update(&self, new_hotkeys: Vec<String>) -> Result<()> {
+        let active = self.active.lock();
         let mut current = self.current.lock();
+        // 停用时只记录新的列表，启用时再注册
+        if !*active {
+            *current = new_hotkeys;
+            return Ok(());
+        }
         let old_map = Self::get_map_from_vec(&current);
         let new_map = Self::get_map_from_vec(&new_hotkeys);
 
         let (del, add) = Self::get_diff(old_map, new_map);
 
         del.iter().for_each(|key| {
+            self.noticed.lock().remove(*key);
             let _ = self.unregister(key);
         });
 
+        let mut failed = vec![];
         add.iter().for_each(|(key, func)| {
-            log_err!(self.register(key, func));
+            if let Err(err) = self.register_tracked(key, func) {
+                log::error!(target: "app", "{err}");
+                failed.push(key.to_string());
+            }
         });
+        self.notice_failures(&failed);
 
         *current = new_hotkeys;
         Ok(())
     }
 
+    /// 按快捷键索引，同一个功能可以绑定多个快捷键
     fn get_map_from_vec(hotkeys: &[String]) -> HashMap<&str, &str> {
         let mut map = HashMap::new();
 
         hotkeys.iter().for_each(|hotkey| {
-            let mut iter = hotkey.split(',');
-            let func = iter.next();
-            let key = iter.next();
-
-            if func.is_some() && key.is_some() {
-                let func = func.unwrap().trim();
-                let key = key.unwrap().trim();
-                map.insert(key, func);
+            let Some((func, key)) = Self::parse_hotkey(hotkey) else {
+                log::warn!(target: "app", "ignore invalid hotkey configuration `{hotkey}`");
+                return;
+            };
+            if let Some(old) = map.insert(key, func) {
+                log::warn!(
+                    target: "app",
+                    "hotkey {key} is bound to both {old} and {func}, use {func}"
+                );
             }
         });
         map
     }
 
+    /// 解析 `{func},{key}` 或 `{func},{arg},{key}`，快捷键总是最后一段
+    fn parse_hotkey(hotkey: &str) -> Option<(&str, &str)> {
+        let (func, key) = hotkey.rsplit_once(',')?;
+        let (func, key) = (func.trim(), key.trim());
+        if func.is_empty() || key.is_empty() {
+            return None;
+        }
+        Some((func, key))
+    }
+
+    fn debounce_window() -> Duration {
+        let debounce = Config::verge().latest().hotkey_debounce_ms;
+        debounce.map_or(DEFAULT_DEBOUNCE, Duration::from_millis)
+    }
+
+    /// 距离上次按下超过 `window` 时返回 true，持续按住时的重复事件都会被忽略
+    fn debounce(last: &mut Option<Instant>, now: Instant, window: Duration) -> bool {
+        let repeated = last.is_some_and(|last| now.saturating_duration_since(last) < window);
+        *last = Some(now);
+        !repeated
+    }
+
+    /// 拆分 `{func},{args}` 或 `{func}:{args}`，以先出现的分隔符为准，没有参数时返回 `None`
+    fn split_args(func: &str) -> (&str, Option<&str>) {
+        match func.split_once([',', ':']) {
+            Some((name, args)) => (name.trim(), Some(args.trim()).filter(|a| !a.is_empty())),
+            None => (func.trim(), None),
+        }
+    }
+
+    /// 拆分 select_proxy 的 `{group}:{proxy}` 或 `{group},{proxy}`，节点名称中可以包含冒号
+    fn split_proxy_args(args: &str) -> Option<(&str, &str)> {
+        let (group, proxy) = args.split_once([',', ':'])?;
+        let (group, proxy) = (group.trim(), proxy.trim());
+        if group.is_empty() || proxy.is_empty() {
+            return None;
+        }
+        Some((group, proxy))
+    }
+
     fn get_diff<'a>(
         old_map: HashMap<&'a str, &'a str>,
         new_map: HashMap<&'a str, &'a str>,
@@ -231,9 +765,152 @@ impl Hotkey {
 
 impl Drop for Hotkey {
     fn drop(&mut self) {
-        let app_handle = handle::Handle::global().app_handle().unwrap();
+        // 退出时应用句柄可能已经不存在，此时不需要注销
+        let Ok(app_handle) = Self::app_handle() else {
+            return;
+        };
         if let Err(e) = app_handle.global_shortcut().unregister_all() {
             log::error!(target:"app", "Error unregistering all hotkeys: {:?}", e);
         }
     }
 }
+
+#[test]
+fn test_parse_hotkey_args() {
+    let (func, key) = Hotkey::parse_hotkey("select_proxy:Proxy:HK-01,ctrl+alt+1").unwrap();
+    assert_eq!((func, key), ("select_proxy:Proxy:HK-01", "ctrl+alt+1"));
+
+    let (name, args) = Hotkey::split_args(func);
+    assert_eq!((name, args), ("select_proxy", Some("Proxy:HK-01")));
+    assert_eq!(Hotkey::split_proxy_args("Proxy:HK-01"), Some(("Proxy", "HK-01")));
+    assert_eq!(Hotkey::split_proxy_args("Proxy:ss://a:1"), Some(("Proxy", "ss://a:1")));
+    assert_eq!(Hotkey::split_proxy_args("Proxy"), None);
+    assert_eq!(Hotkey::split_proxy_args("Proxy: "), None);
+
+    let (name, args) = Hotkey::split_args("change_clash_mode:global");
+    assert_eq!((name, args), ("change_clash_mode", Some("global")));
+    assert_eq!(Hotkey::split_args("toggle_tun_mode"), ("toggle_tun_mode", None));
+    assert_eq!(Hotkey::split_args("toggle_tun_mode:"), ("toggle_tun_mode", None));
+
+    // 逗号分隔的参数与冒号分隔的参数等价
+    let (name, args) = Hotkey::split_args("change_clash_mode,global");
+    assert_eq!((name, args), ("change_clash_mode", Some("global")));
+    let (name, args) = Hotkey::split_args("select_proxy,Proxy,HK-01");
+    assert_eq!((name, args), ("select_proxy", Some("Proxy,HK-01")));
+    assert_eq!(Hotkey::split_proxy_args("Proxy,HK-01"), Some(("Proxy", "HK-01")));
+
+    let (func, _) = Hotkey::parse_hotkey("change_clash_mode,global,ctrl+alt+g").unwrap();
+    assert!(Hotkey::build_func(func).is_ok());
+    assert!(Hotkey::build_func("change_clash_mode:direct").is_ok());
+    assert!(Hotkey::build_func("change_clash_mode,invalid").is_err());
+    assert!(Hotkey::build_func("toggle_tun_mode,arg").is_err());
+}
+
+#[test]
+fn test_classify_hotkey_error() {
+    let kind = |message: &str| HotkeyError::classify("Ctrl+Shift+S", message.into()).kind;
+    assert_eq!(
+        kind("HotKey { mods: Modifiers(CONTROL), key: KeyQ, id: 1 } already registered"),
+        HotkeyErrorKind::AlreadyRegistered
+    );
+    assert_eq!(
+        kind("Hot key is already registered. (os error 1409)"),
+        HotkeyErrorKind::Conflict
+    );
+    assert_eq!(kind("Failed to register hotkey"), HotkeyErrorKind::Conflict);
+    assert_eq!(
+        kind("Couldn't recognize \"Foo\" as a valid key for hotkey"),
+        HotkeyErrorKind::Unsupported
+    );
+}
+
+#[cfg(test)]
+fn sorted_diff<'a>(
+    old: &'a [String],
+    new: &'a [String],
+) -> (Vec<&'a str>, Vec<(&'a str, &'a str)>) {
+    let (mut del, mut add) =
+        Hotkey::get_diff(Hotkey::get_map_from_vec(old), Hotkey::get_map_from_vec(new));
+    del.sort();
+    add.sort();
+    (del, add)
+}
+
+#[test]
+fn test_get_diff_same_func_on_two_keys() {
+    let old = vec!["toggle_system_proxy,Ctrl+Alt+D".to_string()];
+    let new = vec![
+        "toggle_system_proxy,Ctrl+Alt+D".to_string(),
+        "toggle_system_proxy,MediaPlayPause".to_string(),
+    ];
+    let map = Hotkey::get_map_from_vec(&new);
+    assert_eq!(map.len(), 2);
+
+    let (del, add) = sorted_diff(&old, &new);
+    assert!(del.is_empty());
+    assert_eq!(add, vec![("MediaPlayPause", "toggle_system_proxy")]);
+
+    // 删除其中一个，另一个保持注册
+    let (del, add) = sorted_diff(&new, &old);
+    assert_eq!(del, vec!["MediaPlayPause"]);
+    assert!(add.is_empty());
+}
+
+#[test]
+fn test_get_diff_reassign_key() {
+    let old = vec![
+        "toggle_tun_mode,Ctrl+T".to_string(),
+        "toggle_system_proxy,Ctrl+S".to_string(),
+    ];
+    let new = vec![
+        "restart_core,Ctrl+T".to_string(),
+        "toggle_system_proxy,Ctrl+S".to_string(),
+    ];
+    let (del, add) = sorted_diff(&old, &new);
+    assert_eq!(del, vec!["Ctrl+T"]);
+    assert_eq!(add, vec![("Ctrl+T", "restart_core")]);
+}
+
+#[test]
+fn test_debounce() {
+    let window = Duration::from_millis(300);
+    let start = Instant::now();
+    let mut last = None;
+    assert!(Hotkey::debounce(&mut last, start, window));
+    // 按住按键的重复触发
+    assert!(!Hotkey::debounce(&mut last, start + Duration::from_millis(30), window));
+    assert!(!Hotkey::debounce(&mut last, start + Duration::from_millis(299), window));
+    // 间隔一秒的两次按键都会执行
+    assert!(Hotkey::debounce(&mut last, start + Duration::from_secs(1), window));
+    assert!(Hotkey::debounce(&mut last, start + Duration::from_secs(2), window));
+    // 关闭防抖
+    assert!(Hotkey::debounce(&mut last, start + Duration::from_secs(2), Duration::ZERO));
+}
+
+#[test]
+fn test_validate_hotkey() {
+    let hotkey = Hotkey::global();
+    let pending = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    let verdict = hotkey.validate("not a key", "no_such_func", &[]);
+    assert!(!verdict.valid);
+    assert!(verdict.func_error.is_some());
+    assert!(verdict.key_error.is_some());
+
+    // 按未保存的热键检查冲突，快捷键写法不同也能识别
+    let list = pending(&["toggle_tun_mode,CTRL+ALT+T", "restart_core,ctrl+alt+r"]);
+    let verdict = hotkey.validate("Ctrl+Alt+T", "toggle_system_proxy", &list);
+    assert_eq!(verdict.conflict.as_deref(), Some("toggle_tun_mode"));
+    assert!(!verdict.valid);
+
+    // 功能自己的那一项不算冲突
+    let list = pending(&["toggle_tun_mode,ctrl+alt+t"]);
+    let shortcut = "ctrl+alt+t".parse().unwrap();
+    assert_eq!(Hotkey::find_conflict(shortcut, "toggle_tun_mode", &list), None);
+    let list = pending(&["change_clash_mode,global,ctrl+alt+t"]);
+    assert_eq!(
+        Hotkey::find_conflict(shortcut, "toggle_tun_mode", &list).as_deref(),
+        Some("change_clash_mode,global")
+    );
+    assert_eq!(Hotkey::find_conflict(shortcut, "toggle_tun_mode", &[]), None);
+}
@@ -0,0 +1,111 @@
+//! 界面测试用的模拟代理模式
+//! 开启后所有策略组都指向本地的 HTTPS 模拟服务，关闭时恢复开启前的运行时配置
+use crate::config::{Config, ConfigType};
+use crate::core::{clash_api, handle, CoreManager};
+use crate::utils::dirs;
+use anyhow::{bail, Result};
+use parking_lot::Mutex;
+use serde_yaml::{Mapping, Value};
+
+pub const DEFAULT_MOCK_PORT: u16 = 18443;
+
+const MOCK_PROXY: &str = "mock";
+
+struct MockState {
+    port: u16,
+    snapshot: Option<Mapping>,
+}
+
+static STATE: Mutex<Option<MockState>> = parking_lot::const_mutex(None);
+
+/// 模拟模式开启时返回端口
+pub fn mock_port() -> Option<u16> {
+    STATE.lock().as_ref().map(|state| state.port)
+}
+
+/// 把全部代理替换为本地模拟服务
+pub fn use_mock(mut config: Mapping, port: u16) -> Mapping {
+    let mut proxy = Mapping::new();
+    proxy.insert("name".into(), MOCK_PROXY.into());
+    proxy.insert("type".into(), "http".into());
+    proxy.insert("server".into(), "127.0.0.1".into());
+    proxy.insert("port".into(), port.into());
+    proxy.insert("tls".into(), true.into());
+    proxy.insert("skip-cert-verify".into(), true.into());
+    config.insert("proxies".into(), vec![Value::from(proxy)].into());
+    config.remove("proxy-providers");
+
+    if let Some(groups) = config
+        .get_mut("proxy-groups")
+        .and_then(Value::as_sequence_mut)
+    {
+        for group in groups.iter_mut().filter_map(Value::as_mapping_mut) {
+            group.insert("proxies".into(), vec![MOCK_PROXY].into());
+            group.remove("use");
+            group.remove("filter");
+            group.remove("include-all");
+            group.remove("include-all-proxies");
+            group.remove("include-all-providers");
+        }
+    }
+    config
+}
+
+/// 切换模拟模式，返回切换后的状态
+pub async fn toggle_mock_mode(port: Option<u16>) -> Result<bool> {
+    let state = STATE.lock().take();
+    match state {
+        Some(state) => {
+            // 恢复开启前的运行时配置
+            Config::runtime().draft().config = state.snapshot;
+            let run_path = Config::generate_file(ConfigType::Run)?;
+            match clash_api::put_configs(dirs::path_to_str(&run_path)?).await {
+                Ok(_) => Config::runtime().apply(),
+                Err(err) => {
+                    Config::runtime().discard();
+                    return Err(err);
+                }
+            }
+            log::info!(target: "app", "mock mode disabled");
+        }
+        None => {
+            let port = port.unwrap_or(DEFAULT_MOCK_PORT);
+            let snapshot = Config::runtime().latest().config.clone();
+            *STATE.lock() = Some(MockState { port, snapshot });
+
+            let res = CoreManager::global().update_config().await;
+            if let Err(err) = res.and_then(|res| Ok(res?)) {
+                STATE.lock().take();
+                bail!("failed to enable mock mode: {err}");
+            }
+            log::info!(target: "app", "mock mode enabled on port {port}");
+        }
+    }
+
+    handle::Handle::refresh_clash();
+    Ok(mock_port().is_some())
+}
+
+#[test]
+fn test_use_mock() {
+    let config: Mapping = serde_yaml::from_str(
+        r#"
+proxies:
+  - { name: a, type: ss, server: 1.1.1.1, port: 443 }
+proxy-providers:
+  sub: { type: http, url: "https://example.com" }
+proxy-groups:
+  - { name: Proxy, type: select, proxies: [a], use: [sub] }
+"#,
+    )
+    .unwrap();
+    let config = use_mock(config, 8443);
+
+    assert!(config.get("proxy-providers").is_none());
+    let proxies = config["proxies"].as_sequence().unwrap();
+    assert_eq!(proxies.len(), 1);
+    assert_eq!(proxies[0]["port"].as_u64(), Some(8443));
+    let group = &config["proxy-groups"][0];
+    assert_eq!(group["proxies"][0].as_str(), Some(MOCK_PROXY));
+    assert!(group.get("use").is_none());
+}
@@ -1,13 +1,21 @@
 pub mod backup;
 pub mod clash_api;
+pub mod controller;
 #[allow(clippy::module_inception)]
 mod core;
 pub mod handle;
+pub mod history;
 pub mod hotkey;
+#[cfg(any(debug_assertions, feature = "mock-mode"))]
+pub mod mock;
+pub mod operation;
+pub mod profile_watcher;
+pub mod replay;
 pub mod service;
 pub mod sysopt;
 pub mod timer;
 pub mod tray;
+pub mod validation;
 pub mod win_uwp;
 
 pub use self::core::*;
@@ -0,0 +1,345 @@
+//! 长时间运行的后台任务
+//!
+//! 任务注册后得到一个 id，进度和结果通过带 id 的事件通知前端，
+//! 前端可以通过 `cancel_operation` 取消任务。
+use crate::core::handle;
+use crate::utils::help;
+use anyhow::{bail, Result};
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Default)]
+pub struct OperationRegistry {
+    tokens: Arc<Mutex<HashMap<String, (u64, CancellationToken)>>>,
+    serial: AtomicU64,
+}
+
+/// 注册得到的任务，`serial` 区分复用同一个 id 的先后两个任务
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub id: String,
+    pub token: CancellationToken,
+    serial: u64,
+}
+
+/// 任务被取消，`operation::finished` 中以 `cancelled` 区分，不作为错误
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "operation cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+impl OperationRegistry {
+    pub fn global() -> &'static OperationRegistry {
+        static REGISTRY: OnceCell<OperationRegistry> = OnceCell::new();
+        REGISTRY.get_or_init(OperationRegistry::default)
+    }
+
+    /// 注册任务，未指定 id 时自动生成，id 已存在时取消原来的任务
+    pub fn register(&self, id: Option<String>) -> Operation {
+        let id = id.unwrap_or_else(|| help::get_uid("op"));
+        let token = CancellationToken::new();
+        let serial = self.serial.fetch_add(1, Ordering::Relaxed);
+        let old = self
+            .tokens
+            .lock()
+            .insert(id.clone(), (serial, token.clone()));
+        if let Some((_, old)) = old {
+            old.cancel();
+        }
+        Operation { id, token, serial }
+    }
+
+    /// 任务结束后移除，id 已被新的任务使用时保留新任务的
+    pub fn finish(&self, operation: &Operation) {
+        let mut tokens = self.tokens.lock();
+        if tokens
+            .get(&operation.id)
+            .is_some_and(|(serial, _)| *serial == operation.serial)
+        {
+            tokens.remove(&operation.id);
+        }
+    }
+
+    /// 取消任务，任务不存在时返回 false
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.tokens.lock().remove(id) {
+            Some((_, token)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// 运行到被取消为止，取消时返回 `Cancelled`
+pub async fn cancellable<T>(
+    token: &CancellationToken,
+    task: impl std::future::Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::select! {
+        res = task => res,
+        _ = token.cancelled() => Err(Cancelled.into()),
+    }
+}
+
+/// 在后台运行任务，立即返回任务 id
+/// 任务结束后发送 `operation::finished { id, result, error, cancelled }`
+pub fn spawn<F, Fut, T>(id: Option<String>, task: F) -> String
+where
+    F: FnOnce(String, CancellationToken) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = Result<T>> + Send + 'static,
+    T: Serialize + Send + 'static,
+{
+    let operation = OperationRegistry::global().register(id);
+    let id = operation.id.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = task(operation.id.clone(), operation.token.clone()).await;
+        OperationRegistry::global().finish(&operation);
+
+        let op_id = &operation.id;
+        let payload = match result {
+            Ok(result) => serde_json::json!({ "id": op_id, "result": result, "cancelled": false }),
+            Err(err) if err.is::<Cancelled>() || operation.token.is_cancelled() => {
+                log::info!(target: "app", "operation {op_id} cancelled");
+                serde_json::json!({ "id": op_id, "cancelled": true })
+            }
+            Err(err) => {
+                log::error!(target: "app", "operation {op_id} failed: {err}");
+                serde_json::json!({ "id": op_id, "error": err.to_string(), "cancelled": false })
+            }
+        };
+        handle::Handle::emit_event("operation::finished", payload);
+    });
+    id
+}
+
+/// 发送任务进度 `operation::progress { id, stage, current, total }`
+pub fn emit_progress(id: &str, stage: &str, current: u64, total: Option<u64>) {
+    handle::Handle::emit_event(
+        "operation::progress",
+        serde_json::json!({ "id": id, "stage": stage, "current": current, "total": total }),
+    );
+}
+
+/// 下载文件到 `<path>.part`，成功后返回临时文件路径
+/// 被取消或失败时会删除临时文件
+pub async fn download_file(
+    client: &reqwest::Client,
+    url: &str,
+    path: &Path,
+    token: &CancellationToken,
+    on_progress: impl Fn(u64, Option<u64>),
+) -> Result<PathBuf> {
+    let part = part_path(path);
+    let result = tokio::select! {
+        res = download_to(client, url, &part, &on_progress) => res,
+        _ = token.cancelled() => Err(Cancelled.into()),
+    };
+    if let Err(err) = result {
+        let _ = tokio::fs::remove_file(&part).await;
+        return Err(err);
+    }
+    Ok(part)
+}
+
+/// 下载多个文件，全部下载完成后再一起替换目标文件
+/// 被取消或失败时删除已经下载的临时文件，目标文件保持不变
+pub async fn download_files(
+    client: &reqwest::Client,
+    files: &[(PathBuf, String)],
+    token: &CancellationToken,
+    on_progress: impl Fn(&Path, u64, Option<u64>),
+) -> Result<()> {
+    let mut parts = Vec::new();
+    for (target, url) in files {
+        let res = download_file(client, url, target, token, |current, total| {
+            on_progress(target, current, total)
+        })
+        .await;
+        match res {
+            Ok(part) => parts.push((part, target)),
+            Err(err) => {
+                for (part, _) in parts {
+                    let _ = tokio::fs::remove_file(part).await;
+                }
+                return Err(err);
+            }
+        }
+    }
+
+    for (part, target) in parts {
+        tokio::fs::rename(part, target).await?;
+    }
+    Ok(())
+}
+
+async fn download_to(
+    client: &reqwest::Client,
+    url: &str,
+    part: &Path,
+    on_progress: &impl Fn(u64, Option<u64>),
+) -> Result<()> {
+    let mut response = client.get(url).send().await?.error_for_status()?;
+    let total = response.content_length();
+    let mut file = tokio::fs::File::create(part).await?;
+    let mut current = 0;
+
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk).await?;
+        current += chunk.len() as u64;
+        on_progress(current, total);
+    }
+    file.flush().await?;
+
+    if let Some(total) = total {
+        if current != total {
+            bail!("incomplete download, {current}/{total} bytes");
+        }
+    }
+    Ok(())
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+#[tokio::test]
+async fn test_cancel_download_leaves_no_partial_file() -> Result<()> {
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    // 只发送一部分内容然后挂起的服务端
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        if let Ok((mut socket, _)) = listener.accept().await {
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let header = "HTTP/1.1 200 OK\r\nContent-Length: 1048576\r\n\r\n";
+            let _ = socket.write_all(header.as_bytes()).await;
+            let _ = socket.write_all(&[0u8; 4096]).await;
+            tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+        }
+    });
+
+    let path = std::env::temp_dir().join("test_operation_download.dat");
+    let client = reqwest::Client::new();
+    let operation = OperationRegistry::global().register(None);
+    let (id, token) = (operation.id.clone(), operation.token.clone());
+
+    let cancel_id = id.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        assert!(OperationRegistry::global().cancel(&cancel_id));
+    });
+
+    let url = format!("http://{addr}/geoip.dat");
+    let result = download_file(&client, &url, &path, &token, |_, _| {}).await;
+
+    assert!(result.unwrap_err().is::<Cancelled>(), "cancelled download should fail");
+    assert!(!path.exists());
+    assert!(!part_path(&path).exists(), "partial file should be removed");
+    assert!(!OperationRegistry::global().cancel(&id));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cancel_geo_update_leaves_no_partial_file() -> Result<()> {
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    // 第一个文件完整返回，第二个文件只发送一部分内容然后挂起
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        for complete in [true, false] {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                return;
+            };
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let header = "HTTP/1.1 200 OK\r\nContent-Length: 4096\r\nConnection: close\r\n\r\n";
+            let _ = socket.write_all(header.as_bytes()).await;
+            if complete {
+                let _ = socket.write_all(&[0u8; 4096]).await;
+            } else {
+                let _ = socket.write_all(&[0u8; 1024]).await;
+                tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+            }
+        }
+    });
+
+    let dir = std::env::temp_dir().join("test_operation_geo_update");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir)?;
+    let files = [
+        (dir.join("geoip.dat"), format!("http://{addr}/geoip.dat")),
+        (dir.join("geosite.dat"), format!("http://{addr}/geosite.dat")),
+    ];
+    let token = CancellationToken::new();
+    let canceller = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        canceller.cancel();
+    });
+
+    let client = reqwest::Client::builder().no_proxy().build()?;
+    let result = download_files(&client, &files, &token, |_, _, _| {}).await;
+
+    assert!(result.unwrap_err().is::<Cancelled>(), "cancelled update should fail");
+    let left = std::fs::read_dir(&dir)?.count();
+    assert_eq!(left, 0, "no geo file or partial file should be left");
+    let _ = std::fs::remove_dir_all(&dir);
+    Ok(())
+}
+
+#[test]
+fn test_reused_id_keeps_new_token() {
+    let registry = OperationRegistry::default();
+    let old = registry.register(Some("geo".into()));
+    let new = registry.register(Some("geo".into()));
+    assert!(old.token.is_cancelled());
+
+    // 旧任务结束时不能移除新任务
+    registry.finish(&old);
+    assert!(registry.cancel("geo"));
+    assert!(new.token.is_cancelled());
+
+    registry.finish(&new);
+    assert!(!registry.cancel("geo"));
+}
+
+#[tokio::test]
+async fn test_cancellable() {
+    let token = CancellationToken::new();
+    let canceller = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        canceller.cancel();
+    });
+    let result = cancellable(&token, async {
+        tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+        Ok(())
+    })
+    .await;
+    assert!(result.unwrap_err().is::<Cancelled>());
+
+    let result = cancellable(&CancellationToken::new(), async { Ok(1) }).await;
+    assert_eq!(result.unwrap(), 1);
+}
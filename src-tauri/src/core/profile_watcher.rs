@@ -0,0 +1,240 @@
+//! 监听当前订阅及其扩展配置、扩展脚本的文件，在外部编辑器保存后自动重新加载配置
+use crate::config::Config;
+use crate::core::{handle, CoreManager};
+use crate::log_err;
+use crate::utils::dirs;
+use anyhow::Result;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+/// 连续保存时等待文件安静下来的时间
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// 应用自己写入文件后忽略该文件事件的时间，应用保存后会自行重新加载
+const OWN_WRITE_WINDOW: Duration = Duration::from_secs(1);
+
+pub struct ProfileWatcher {
+    state: Mutex<Option<WatchState>>,
+    /// 当前需要关注的文件，事件回调按此过滤
+    files: Arc<Mutex<HashSet<PathBuf>>>,
+    /// 应用最近写入的文件
+    own_writes: Arc<Mutex<OwnWrites>>,
+}
+
+/// 应用最近写入的文件和写入的时间
+#[derive(Default)]
+struct OwnWrites(HashMap<PathBuf, Instant>);
+
+impl OwnWrites {
+    fn note(&mut self, path: PathBuf, now: Instant) {
+        self.0.retain(|_, at| now.duration_since(*at) < OWN_WRITE_WINDOW);
+        self.0.insert(path, now);
+    }
+
+    fn contains(&self, path: &Path, now: Instant) -> bool {
+        self.0
+            .get(path)
+            .is_some_and(|at| now.duration_since(*at) < OWN_WRITE_WINDOW)
+    }
+}
+
+struct WatchState {
+    watcher: RecommendedWatcher,
+    /// 编辑器可能先写临时文件再重命名覆盖，所以监听的是文件所在的目录
+    dirs: HashSet<PathBuf>,
+}
+
+impl ProfileWatcher {
+    pub fn global() -> &'static ProfileWatcher {
+        static WATCHER: OnceCell<ProfileWatcher> = OnceCell::new();
+
+        WATCHER.get_or_init(|| ProfileWatcher {
+            state: Mutex::new(None),
+            files: Arc::new(Mutex::new(HashSet::new())),
+            own_writes: Arc::new(Mutex::new(OwnWrites::default())),
+        })
+    }
+
+    /// 应用写入订阅目录中的文件前调用，忽略随后的文件事件，避免重复重新加载
+    pub fn note_own_write(&self, path: &Path) {
+        self.own_writes.lock().note(path.to_path_buf(), Instant::now());
+    }
+
+    /// 按当前启用的订阅和扩展文件重新设置监听，关闭热重载时停止监听
+    pub fn refresh(&self) -> Result<()> {
+        let enabled = Config::verge()
+            .latest()
+            .enable_profile_hot_reload
+            .unwrap_or(true);
+        let files = if enabled {
+            Self::active_files()?
+        } else {
+            HashSet::new()
+        };
+
+        let mut state = self.state.lock();
+        if files.is_empty() {
+            // 释放 watcher 后重新加载的任务也会结束
+            if state.take().is_some() {
+                log::info!(target: "app", "profile hot reload stopped");
+            }
+            self.files.lock().clear();
+            return Ok(());
+        }
+
+        let dirs = files
+            .iter()
+            .filter_map(|file| file.parent().map(Path::to_path_buf))
+            .collect::<HashSet<_>>();
+        if state.is_none() {
+            let watcher = self.create_watcher()?;
+            *state = Some(WatchState {
+                watcher,
+                dirs: HashSet::new(),
+            });
+        }
+        let state = state.as_mut().expect("profile watcher was just created");
+        for dir in state.dirs.difference(&dirs) {
+            log_err!(state.watcher.unwatch(dir));
+        }
+        for dir in dirs.difference(&state.dirs) {
+            state.watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+        state.dirs = dirs;
+
+        log::debug!(target: "app", "watching profile files: {files:?}");
+        *self.files.lock() = files;
+        Ok(())
+    }
+
+    fn create_watcher(&self) -> Result<RecommendedWatcher> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let files = self.files.clone();
+        let own_writes = self.own_writes.clone();
+        let watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            let event = match res {
+                Ok(event) => event,
+                Err(err) => {
+                    log::warn!(target: "app", "profile watcher error: {err}");
+                    return;
+                }
+            };
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                return;
+            }
+            let files = files.lock();
+            let own_writes = own_writes.lock();
+            let now = Instant::now();
+            let changed = event
+                .paths
+                .iter()
+                .any(|path| files.contains(path) && !own_writes.contains(path, now));
+            if changed {
+                let _ = tx.send(());
+            }
+        })?;
+
+        tauri::async_runtime::spawn(Self::reload_loop(rx));
+        log::info!(target: "app", "profile hot reload started");
+        Ok(watcher)
+    }
+
+    async fn reload_loop(mut rx: UnboundedReceiver<()>) {
+        while next_batch(&mut rx, RELOAD_DEBOUNCE).await {
+            log::info!(target: "app", "profile files changed, reloading config");
+            match CoreManager::global().update_config().await {
+                Ok(Ok(())) => handle::Handle::refresh_clash(),
+                Ok(Err(error)) => handle::Handle::notice_error("config_validate::error", &error),
+                Err(err) => log::error!(target: "app", "failed to reload config: {err}"),
+            }
+        }
+    }
+
+    /// 当前订阅、它的扩展配置和扩展脚本，以及全局的扩展配置和扩展脚本
+    fn active_files() -> Result<HashSet<PathBuf>> {
+        let profiles_dir = dirs::app_profiles_dir()?;
+        let profiles = Config::profiles();
+        let profiles = profiles.latest();
+
+        let uids = [
+            profiles.get_current(),
+            profiles.current_merge(),
+            profiles.current_script(),
+            profiles.current_rules(),
+            profiles.current_proxies(),
+            profiles.current_groups(),
+            Some("Merge".into()),
+            Some("Script".into()),
+        ];
+        Ok(uids
+            .into_iter()
+            .flatten()
+            .filter_map(|uid| profiles.get_item(&uid).ok()?.file.clone())
+            .map(|file| profiles_dir.join(file))
+            .collect())
+    }
+}
+
+/// 等待下一批文件事件，连续的事件在安静 `debounce` 后合并为一批
+/// 通道关闭时返回 false
+async fn next_batch(rx: &mut UnboundedReceiver<()>, debounce: Duration) -> bool {
+    if rx.recv().await.is_none() {
+        return false;
+    }
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(debounce) => return true,
+            event = rx.recv() => {
+                if event.is_none() {
+                    return false;
+                }
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_next_batch() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let debounce = Duration::from_millis(50);
+
+    // 连续保存合并为一次
+    let saves = tokio::spawn({
+        let tx = tx.clone();
+        async move {
+            for _ in 0..5 {
+                let _ = tx.send(());
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+    });
+    assert!(next_batch(&mut rx, debounce).await);
+    saves.await.unwrap();
+    assert!(rx.try_recv().is_err());
+
+    drop(tx);
+    assert!(!next_batch(&mut rx, debounce).await);
+}
+
+#[test]
+fn test_own_writes() {
+    let mut own_writes = OwnWrites::default();
+    let (profile, merge) = (Path::new("/profiles/a.yaml"), Path::new("/profiles/m.yaml"));
+    let now = Instant::now();
+
+    // 应用写入后的事件被忽略，其他文件和超过时间的事件照常处理
+    own_writes.note(profile.to_path_buf(), now);
+    assert!(own_writes.contains(profile, now + Duration::from_millis(300)));
+    assert!(!own_writes.contains(merge, now));
+    assert!(!own_writes.contains(profile, now + OWN_WRITE_WINDOW));
+
+    // 记录新的写入时清理过期的记录
+    own_writes.note(merge.to_path_buf(), now + OWN_WRITE_WINDOW * 2);
+    assert_eq!(own_writes.0.len(), 1);
+}
@@ -0,0 +1,338 @@
+//! 记录连接并在规则修改后回放，检查连接命中的规则是否发生变化
+use crate::config::Config;
+use crate::core::clash_api;
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// 轮询连接的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 记录文件中的一行
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConnectionRecord {
+    pub id: String,
+    pub metadata: Value,
+    #[serde(default)]
+    pub rule: String,
+    #[serde(default, rename = "rulePayload")]
+    pub rule_payload: String,
+    #[serde(default)]
+    pub chains: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayMismatch {
+    pub host: String,
+    pub destination: String,
+    pub recorded_rule: String,
+    pub recorded_payload: String,
+    pub simulated_rule: String,
+    pub simulated_payload: String,
+    pub simulated_proxy: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplayReport {
+    pub total: usize,
+    pub matched: usize,
+    /// 遇到无法在本地模拟的规则（如 GEOIP、RULE-SET）
+    pub unsupported: usize,
+    pub mismatches: Vec<ReplayMismatch>,
+}
+
+/// 模拟匹配的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleMatch {
+    Matched {
+        rule_type: String,
+        payload: String,
+        proxy: String,
+    },
+    /// 在命中之前遇到了无法模拟的规则
+    Unsupported(String),
+    NotMatched,
+}
+
+fn recorder() -> &'static Mutex<Option<CancellationToken>> {
+    static RECORDER: OnceCell<Mutex<Option<CancellationToken>>> = OnceCell::new();
+    RECORDER.get_or_init(|| Mutex::new(None))
+}
+
+/// 定时获取连接并以 NDJSON 格式追加到文件，直到调用 `stop_recording`
+/// 先打开文件再开始记录，路径无效或没有权限时直接返回错误
+pub fn start_recording(output_path: PathBuf) -> Result<()> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&output_path)
+        .with_context(|| format!("failed to open \"{}\"", output_path.display()))?;
+
+    let token = CancellationToken::new();
+    if let Some(old) = recorder().lock().replace(token.clone()) {
+        old.cancel();
+    }
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = record_connections(file, token).await {
+            log::error!(target: "app", "connection recording stopped: {err}");
+        }
+    });
+    Ok(())
+}
+
+async fn record_connections(mut file: std::fs::File, token: CancellationToken) -> Result<()> {
+    let mut recorded = HashSet::new();
+
+    while !token.is_cancelled() {
+        match clash_api::get_connections_raw().await {
+            Ok(connections) => {
+                for conn in connections {
+                    let record = match serde_json::from_value::<ConnectionRecord>(conn) {
+                        Ok(record) => record,
+                        Err(_) => continue,
+                    };
+                    if recorded.insert(record.id.clone()) {
+                        writeln!(file, "{}", serde_json::to_string(&record)?)?;
+                    }
+                }
+                file.flush()?;
+            }
+            Err(err) => log::warn!(target: "app", "failed to get connections: {err}"),
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            _ = token.cancelled() => {}
+        }
+    }
+    Ok(())
+}
+
+/// 停止记录
+pub fn stop_recording() {
+    if let Some(token) = recorder().lock().take() {
+        token.cancel();
+    }
+}
+
+/// 使用当前运行时的规则回放记录的连接
+pub fn replay_recording(input_path: PathBuf) -> Result<ReplayReport> {
+    let rules = Config::runtime()
+        .latest()
+        .config
+        .as_ref()
+        .and_then(|config| config.get("rules"))
+        .and_then(serde_yaml::Value::as_sequence)
+        .map(|rules| {
+            rules
+                .iter()
+                .filter_map(|rule| rule.as_str().map(String::from))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let file = std::fs::File::open(&input_path)
+        .with_context(|| format!("failed to open \"{}\"", input_path.display()))?;
+    let mut report = ReplayReport::default();
+
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str::<ConnectionRecord>(&line)?;
+        report.total += 1;
+
+        match simulate_rule_match(&record.metadata, &rules) {
+            RuleMatch::Matched {
+                rule_type,
+                payload,
+                proxy,
+            } => {
+                if same_rule_type(&rule_type, &record.rule) && payload == record.rule_payload {
+                    report.matched += 1;
+                } else {
+                    let meta = &record.metadata;
+                    report.mismatches.push(ReplayMismatch {
+                        host: meta["host"].as_str().unwrap_or_default().into(),
+                        destination: format!(
+                            "{}:{}",
+                            meta["destinationIP"].as_str().unwrap_or_default(),
+                            meta["destinationPort"].as_str().unwrap_or_default()
+                        ),
+                        recorded_rule: record.rule.clone(),
+                        recorded_payload: record.rule_payload.clone(),
+                        simulated_rule: rule_type,
+                        simulated_payload: payload,
+                        simulated_proxy: proxy,
+                    });
+                }
+            }
+            RuleMatch::Unsupported(rule) => {
+                log::debug!(target: "app", "replay stopped at unsupported rule {rule}");
+                report.unsupported += 1;
+            }
+            RuleMatch::NotMatched => report.unsupported += 1,
+        }
+    }
+    Ok(report)
+}
+
+/// 按顺序匹配规则，只支持不依赖外部数据的规则类型
+pub fn simulate_rule_match(metadata: &Value, rules: &[String]) -> RuleMatch {
+    let host = metadata["host"].as_str().unwrap_or_default().to_lowercase();
+    let dst_ip = metadata["destinationIP"]
+        .as_str()
+        .and_then(|ip| ip.parse::<IpAddr>().ok());
+    let src_ip = metadata["sourceIP"]
+        .as_str()
+        .and_then(|ip| ip.parse::<IpAddr>().ok());
+    let dst_port = metadata["destinationPort"].as_str().unwrap_or_default();
+    let src_port = metadata["sourcePort"].as_str().unwrap_or_default();
+    let network = metadata["network"].as_str().unwrap_or_default();
+
+    for rule in rules {
+        let parts = rule.split(',').map(str::trim).collect::<Vec<_>>();
+        let rule_type = parts[0].to_uppercase();
+        let (payload, proxy) = match rule_type.as_str() {
+            "MATCH" | "FINAL" if parts.len() >= 2 => ("", parts[1]),
+            _ if parts.len() >= 3 => (parts[1], parts[2]),
+            _ => continue,
+        };
+
+        let hit = match rule_type.as_str() {
+            "DOMAIN" => host == payload.to_lowercase(),
+            "DOMAIN-SUFFIX" => {
+                let suffix = payload.to_lowercase();
+                host == suffix || host.ends_with(&format!(".{suffix}"))
+            }
+            "DOMAIN-KEYWORD" => host.contains(&payload.to_lowercase()),
+            "IP-CIDR" | "IP-CIDR6" => dst_ip.map_or(false, |ip| ip_in_cidr(ip, payload)),
+            "SRC-IP-CIDR" => src_ip.map_or(false, |ip| ip_in_cidr(ip, payload)),
+            "DST-PORT" => port_in_range(dst_port, payload),
+            "SRC-PORT" => port_in_range(src_port, payload),
+            "NETWORK" => network.eq_ignore_ascii_case(payload),
+            "MATCH" | "FINAL" => true,
+            _ => return RuleMatch::Unsupported(rule.clone()),
+        };
+
+        if hit {
+            return RuleMatch::Matched {
+                rule_type,
+                payload: payload.into(),
+                proxy: proxy.into(),
+            };
+        }
+    }
+    RuleMatch::NotMatched
+}
+
+/// 内核返回的规则类型为 `DomainSuffix` 这样的形式
+fn same_rule_type(simulated: &str, recorded: &str) -> bool {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_lowercase()
+    };
+    let (simulated, recorded) = (normalize(simulated), normalize(recorded));
+    simulated == recorded || (simulated == "final" && recorded == "match")
+}
+
+fn ip_in_cidr(ip: IpAddr, cidr: &str) -> bool {
+    let (net, prefix) = match cidr.split_once('/') {
+        Some((net, prefix)) => (net, prefix.parse::<u32>().ok()),
+        None => (cidr, None),
+    };
+    match (ip, net.parse::<IpAddr>()) {
+        (IpAddr::V4(ip), Ok(IpAddr::V4(net))) => {
+            let prefix = prefix.unwrap_or(32).min(32);
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(net) & mask
+        }
+        (IpAddr::V6(ip), Ok(IpAddr::V6(net))) => {
+            let prefix = prefix.unwrap_or(128).min(128);
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(net) & mask
+        }
+        _ => false,
+    }
+}
+
+/// 支持 `443`、`8000-9000` 和 `80/443`
+fn port_in_range(port: &str, payload: &str) -> bool {
+    let port = match port.parse::<u16>() {
+        Ok(port) => port,
+        Err(_) => return false,
+    };
+    payload.split('/').any(|range| match range.split_once('-') {
+        Some((start, end)) => match (start.parse::<u16>(), end.parse::<u16>()) {
+            (Ok(start), Ok(end)) => (start..=end).contains(&port),
+            _ => false,
+        },
+        None => range.parse::<u16>() == Ok(port),
+    })
+}
+
+#[test]
+fn test_simulate_rule_match() {
+    let rules = [
+        "DOMAIN-SUFFIX,google.com,Proxy",
+        "IP-CIDR,10.0.0.0/8,DIRECT,no-resolve",
+        "DST-PORT,8000-9000,Proxy",
+        "GEOIP,CN,DIRECT",
+        "MATCH,Final",
+    ]
+    .map(String::from);
+
+    let meta = serde_json::json!({ "host": "www.google.com", "destinationPort": "443" });
+    assert_eq!(
+        simulate_rule_match(&meta, &rules),
+        RuleMatch::Matched {
+            rule_type: "DOMAIN-SUFFIX".into(),
+            payload: "google.com".into(),
+            proxy: "Proxy".into(),
+        }
+    );
+
+    let meta = serde_json::json!({ "destinationIP": "10.1.2.3", "destinationPort": "443" });
+    assert!(matches!(
+        simulate_rule_match(&meta, &rules),
+        RuleMatch::Matched { proxy, .. } if proxy == "DIRECT"
+    ));
+
+    let meta = serde_json::json!({ "host": "example.com", "destinationPort": "8080" });
+    assert!(matches!(
+        simulate_rule_match(&meta, &rules),
+        RuleMatch::Matched { rule_type, .. } if rule_type == "DST-PORT"
+    ));
+
+    let meta = serde_json::json!({ "host": "example.com", "destinationPort": "443" });
+    assert!(matches!(
+        simulate_rule_match(&meta, &rules),
+        RuleMatch::Unsupported(_)
+    ));
+
+    assert!(same_rule_type("DOMAIN-SUFFIX", "DomainSuffix"));
+    assert!(same_rule_type("MATCH", "Match"));
+    assert!(ip_in_cidr("2001:db8::1".parse().unwrap(), "2001:db8::/32"));
+    assert!(!ip_in_cidr("11.0.0.1".parse().unwrap(), "10.0.0.0/8"));
+}
+
+#[test]
+fn test_start_recording_reports_open_errors() {
+    let path = std::env::temp_dir()
+        .join("clash-verge-missing-dir")
+        .join("connections.ndjson");
+    let err = start_recording(path).unwrap_err();
+    assert!(err.to_string().contains("failed to open"));
+    assert!(recorder().lock().is_none(), "nothing should be recording");
+}
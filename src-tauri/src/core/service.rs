@@ -1,10 +1,10 @@
 use crate::config::Config;
+use crate::core::CoreManager;
 use crate::utils::dirs;
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::path::PathBuf;
-use std::{env::current_exe, process::Command as StdCommand};
+use std::process::Command as StdCommand;
 use tokio::time::Duration;
 
 // Windows only
@@ -17,6 +17,9 @@ pub struct ResponseBody {
     pub bin_path: String,
     pub config_dir: String,
     pub log_file: String,
+    /// 服务启动的内核进程，旧版服务不返回
+    #[serde(default)]
+    pub pid: Option<u32>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -181,10 +184,12 @@ pub async fn check_service() -> Result<JsonResponse> {
 pub(super) async fn run_core_by_service(config_file: &PathBuf) -> Result<()> {
     let clash_core = { Config::verge().latest().clash_core.clone() };
     let clash_core = clash_core.unwrap_or("verge-mihomo".into());
+    // 服务以 root/SYSTEM 运行内核，不能运行用户指定的任意程序
+    if !CoreManager::is_bundled_core(&clash_core) {
+        bail!("the service only runs the bundled core, not \"{clash_core}\"");
+    }
 
-    let bin_ext = if cfg!(windows) { ".exe" } else { "" };
-    let clash_bin = format!("{clash_core}{bin_ext}");
-    let bin_path = current_exe()?.with_file_name(clash_bin);
+    let bin_path = CoreManager::core_binary_path(&clash_core)?;
     let bin_path = dirs::path_to_str(&bin_path)?;
 
     let config_dir = dirs::app_home_dir()?;
@@ -195,28 +200,49 @@ pub(super) async fn run_core_by_service(config_file: &PathBuf) -> Result<()> {
 
     let config_file = dirs::path_to_str(config_file)?;
 
-    let mut map = HashMap::new();
-    map.insert("core_type", clash_core.as_str());
-    map.insert("bin_path", bin_path);
-    map.insert("config_dir", config_dir);
-    map.insert("config_file", config_file);
-    map.insert("log_file", log_path);
+    // 自定义参数可以让以 root/SYSTEM 运行的内核加载任意文件，服务模式下不传递
+    let extra_args = CoreManager::core_extra_args(true);
+    if !extra_args.is_empty() {
+        log::warn!(target: "app", "extra core args are ignored in service mode: {extra_args:?}");
+    }
+
+    let map = serde_json::json!({
+        "core_type": clash_core,
+        "bin_path": bin_path,
+        "config_dir": config_dir,
+        "config_file": config_file,
+        "log_file": log_path,
+    });
 
-    log::info!(target:"app", "start service: {:?}", map.clone());
+    log::info!(target:"app", "start service: {map}");
 
     let url = format!("{SERVICE_URL}/start_clash");
-    let _ = reqwest::ClientBuilder::new()
+    let response = reqwest::ClientBuilder::new()
         .no_proxy()
         .build()?
         .post(url)
         .json(&map)
         .send()
         .await
-        .context("failed to connect to the Clash Verge Service")?;
+        .context("failed to connect to the Clash Verge Service")?
+        .error_for_status()
+        .context("the Clash Verge Service failed to start the core")?;
+
+    // 服务返回非0的 code 表示内核没有启动
+    if let Ok(body) = response.json::<JsonResponse>().await {
+        if body.code != 0 {
+            bail!("the Clash Verge Service failed to start the core: {}", body.msg);
+        }
+    }
 
     Ok(())
 }
 
+/// get the pid of the core started by the service
+pub(super) async fn get_core_pid_by_service() -> Option<u32> {
+    check_service().await.ok()?.data?.pid
+}
+
 /// stop the clash by service
 pub(super) async fn stop_core_by_service() -> Result<()> {
     let url = format!("{SERVICE_URL}/stop_clash");
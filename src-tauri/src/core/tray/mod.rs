@@ -2,6 +2,8 @@ use once_cell::sync::OnceCell;
 #[cfg(target_os = "macos")]
 pub mod speed_rate;
 use crate::core::clash_api::Rate;
+#[cfg(target_os = "macos")]
+use crate::core::clash_api::{SubscriptionState, TRAFFIC_SUBSCRIPTION};
 use crate::{
     cmds,
     config::Config,
@@ -12,13 +14,11 @@ use crate::{
 
 use anyhow::Result;
 #[cfg(target_os = "macos")]
-use futures::StreamExt;
-#[cfg(target_os = "macos")]
 use parking_lot::Mutex;
 #[cfg(target_os = "macos")]
 use parking_lot::RwLock;
 #[cfg(target_os = "macos")]
-pub use speed_rate::{SpeedRate, Traffic};
+pub use speed_rate::SpeedRate;
 #[cfg(target_os = "macos")]
 use std::sync::Arc;
 use tauri::menu::{CheckMenuItem, IsMenuItem};
@@ -88,7 +88,7 @@ impl Tray {
                 match tray_event.as_str() {
                     "system_proxy" => feat::toggle_system_proxy(),
                     "tun_mode" => feat::toggle_tun_mode(),
-                    "main_window" => resolve::create_window(),
+                    "main_window" => feat::toggle_dashboard(),
                     _ => {}
                 }
             }
@@ -103,7 +103,7 @@ impl Tray {
                 match tray_event.as_str() {
                     "system_proxy" => feat::toggle_system_proxy(),
                     "tun_mode" => feat::toggle_tun_mode(),
-                    "main_window" => resolve::create_window(),
+                    "main_window" => feat::toggle_dashboard(),
                     _ => {}
                 }
             }
@@ -312,42 +312,42 @@ impl Tray {
         *self.is_subscribed.write() = true;
 
         let speed_rate = Arc::clone(&self.speed_rate);
-        let is_subscribed = Arc::clone(&self.is_subscribed);
 
         tauri::async_runtime::spawn(async move {
             let mut shutdown = shutdown_rx;
-
-            'outer: loop {
-                match Traffic::get_traffic_stream().await {
-                    Ok(mut stream) => loop {
-                        tokio::select! {
-                            Some(traffic) = stream.next() => {
-                                if let Ok(traffic) = traffic {
-                                    let guard = speed_rate.lock();
-                                    let enable_tray_speed: bool = Config::verge().latest().enable_tray_speed.unwrap_or(true);
-                                    if !enable_tray_speed {
-                                        continue;
-                                    }
-                                    if let Some(sr) = guard.as_ref() {
-                                        if let Some(rate) = sr.update_and_check_changed(traffic.up, traffic.down) {
-                                            let _ = Tray::global().update_icon(Some(rate));
-                                        }
-                                    }
-                                }
-                            }
-                            _ = shutdown.recv() => break 'outer,
+            // 内核的 /traffic 连接由 clash_api 统一维护，这里只接收数据
+            let mut traffic = crate::core::clash_api::subscribe_traffic();
+            let mut states = crate::core::clash_api::subscribe_subscription_states();
+
+            loop {
+                tokio::select! {
+                    received = traffic.recv() => {
+                        let traffic = match received {
+                            Ok(traffic) => traffic,
+                            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        };
+                        let enable_tray_speed: bool = Config::verge().latest().enable_tray_speed.unwrap_or(true);
+                        if !enable_tray_speed {
+                            continue;
                         }
-                    },
-                    Err(e) => {
-                        log::error!(target: "app", "Failed to get traffic stream: {}", e);
-                        // 如果获取流失败，等待一段时间后重试
-                        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-
-                        // 检查是否应该继续重试
-                        if !*is_subscribed.read() {
-                            break;
+                        let rate = speed_rate
+                            .lock()
+                            .as_ref()
+                            .and_then(|sr| sr.update_and_check_changed(traffic.up, traffic.down));
+                        if let Some(rate) = rate {
+                            let _ = Tray::global().update_icon(Some(rate));
                         }
                     }
+                    // 断开后清除速率，不显示过时的数据
+                    Ok((name, state)) = states.recv() => {
+                        if name != TRAFFIC_SUBSCRIPTION || state == SubscriptionState::Connected {
+                            continue;
+                        }
+                        *speed_rate.lock() = Some(SpeedRate::new());
+                        let _ = Tray::global().update_icon(None);
+                    }
+                    _ = shutdown.recv() => break,
                 }
             }
         });
@@ -591,7 +591,9 @@ fn on_menu_event(_: &AppHandle, event: MenuEvent) {
         "open_window" => resolve::create_window(),
         "system_proxy" => feat::toggle_system_proxy(),
         "tun_mode" => feat::toggle_tun_mode(),
-        "copy_env" => feat::copy_clash_env(),
+        "copy_env" => {
+            feat::copy_clash_env();
+        }
         "open_app_dir" => crate::log_err!(cmds::open_app_dir()),
         "open_core_dir" => crate::log_err!(cmds::open_core_dir()),
         "open_logs_dir" => crate::log_err!(cmds::open_logs_dir()),
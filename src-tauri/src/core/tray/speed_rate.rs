@@ -1,14 +1,12 @@
-use crate::core::clash_api::{get_traffic_ws_url, Rate};
+use crate::core::clash_api::Rate;
 use crate::utils::help::format_bytes_speed;
 use anyhow::Result;
-use futures::Stream;
 use image::{ImageBuffer, Rgba};
 use imageproc::drawing::draw_text_mut;
 use parking_lot::Mutex;
 use rusttype::{Font, Scale};
 use std::io::Cursor;
 use std::sync::Arc;
-use tokio_tungstenite::tungstenite::Message;
 
 #[derive(Debug, Clone)]
 pub struct SpeedRate {
@@ -187,52 +185,3 @@ impl SpeedRate {
         INSTANCE.get_or_init(SpeedRate::new)
     }
 }
-
-#[derive(Debug, Clone)]
-pub struct Traffic {
-    pub up: u64,
-    pub down: u64,
-}
-
-impl Traffic {
-    pub async fn get_traffic_stream() -> Result<impl Stream<Item = Result<Traffic, anyhow::Error>>>
-    {
-        use futures::stream::{self, StreamExt};
-        use std::time::Duration;
-
-        let stream = Box::pin(
-            stream::unfold((), |_| async {
-                loop {
-                    let ws_url = get_traffic_ws_url().unwrap();
-
-                    match tokio_tungstenite::connect_async(&ws_url).await {
-                        Ok((ws_stream, _)) => {
-                            log::info!(target: "app", "traffic ws connection established");
-                            return Some((
-                                ws_stream.map(|msg| {
-                                    msg.map_err(anyhow::Error::from).and_then(|msg: Message| {
-                                        let data = msg.into_text()?;
-                                        let json: serde_json::Value = serde_json::from_str(&data)?;
-                                        Ok(Traffic {
-                                            up: json["up"].as_u64().unwrap_or(0),
-                                            down: json["down"].as_u64().unwrap_or(0),
-                                        })
-                                    })
-                                }),
-                                (),
-                            ));
-                        }
-                        Err(e) => {
-                            log::error!(target: "app", "traffic ws connection failed: {e}");
-                            tokio::time::sleep(Duration::from_secs(5)).await;
-                            continue;
-                        }
-                    }
-                }
-            })
-            .flatten(),
-        );
-
-        Ok(stream)
-    }
-}
@@ -0,0 +1,437 @@
+//! 解析内核 `-t` 的输出，把验证错误整理成前端可以定位的结构
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidationErrorKind {
+    /// YAML 语法错误
+    Yaml,
+    /// 配置内容错误，如缺少字段、规则引用了不存在的策略组
+    Parse,
+    /// 订阅或规则集等外部文件的错误
+    Provider,
+    /// 验证超时或进程异常退出
+    Process,
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationError {
+    /// 出错的文件，不在输出中时为验证的配置文件
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub message: String,
+    pub kind: ValidationErrorKind,
+}
+
+/// 内核日志的级别，warn 和 warning 都视为 warning，panic 视为 fatal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoreLogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Fatal,
+}
+
+impl CoreLogLevel {
+    fn parse(level: &str) -> Option<Self> {
+        match level.to_lowercase().as_str() {
+            "debug" | "trace" | "debu" | "trac" => Some(Self::Debug),
+            "info" => Some(Self::Info),
+            "warn" | "warning" => Some(Self::Warning),
+            "error" | "erro" => Some(Self::Error),
+            "fatal" | "fata" | "panic" | "pani" => Some(Self::Fatal),
+            _ => None,
+        }
+    }
+}
+
+/// 内核输出的一行结构化日志
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CoreLogEntry {
+    pub time: Option<String>,
+    pub level: CoreLogLevel,
+    pub message: String,
+}
+
+/// 按行解析后的内核输出
+#[derive(Debug, Clone, Default)]
+pub struct CoreOutput {
+    pub entries: Vec<CoreLogEntry>,
+    /// 无法解析为日志的行，如 `configuration file xxx test failed`
+    pub unstructured: Vec<String>,
+}
+
+impl CoreOutput {
+    pub fn parse(output: &str) -> Self {
+        let mut parsed = Self::default();
+        for line in output.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            match parse_log_line(line) {
+                Some(entry) => parsed.entries.push(entry),
+                None => parsed.unstructured.push(line.to_string()),
+            }
+        }
+        parsed
+    }
+
+    /// 是否解析出了结构化日志，未来版本的内核可能改变输出格式
+    pub fn is_structured(&self) -> bool {
+        !self.entries.is_empty()
+    }
+
+    pub fn has_fatal(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.level == CoreLogLevel::Fatal)
+    }
+
+    /// error 及以上级别的日志
+    pub fn problems(&self) -> Vec<CoreLogEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.level >= CoreLogLevel::Error)
+            .cloned()
+            .collect()
+    }
+}
+
+/// 解析一行日志，支持 logfmt 格式 `time="..." level=error msg="..."`
+/// 和 logrus 的文本格式 `FATA[0000] ...`
+fn parse_log_line(line: &str) -> Option<CoreLogEntry> {
+    if let Some(mut fields) = parse_logfmt(line) {
+        let level = fields.get("level").and_then(|level| CoreLogLevel::parse(level));
+        if let (Some(level), Some(message)) = (level, fields.remove("msg")) {
+            return Some(CoreLogEntry {
+                time: fields.remove("time"),
+                level,
+                message,
+            });
+        }
+    }
+
+    let (prefix, rest) = line.split_once('[')?;
+    let level = CoreLogLevel::parse(prefix)?;
+    let (_, message) = rest.split_once(']')?;
+    Some(CoreLogEntry {
+        time: None,
+        level,
+        message: message.trim().to_string(),
+    })
+}
+
+/// 解析 `key=value key="quoted \"value\""` 形式的字段，格式不符时返回空
+fn parse_logfmt(line: &str) -> Option<HashMap<String, String>> {
+    let mut fields = HashMap::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|c| *c != '=' && !c.is_whitespace()) {
+            key.push(c);
+        }
+        if key.is_empty() || chars.next_if_eq(&'=').is_none() {
+            return None;
+        }
+
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            loop {
+                match chars.next()? {
+                    '\\' => match chars.next()? {
+                        'n' => value.push('\n'),
+                        't' => value.push('\t'),
+                        c => value.push(c),
+                    },
+                    '"' => break,
+                    c => value.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                value.push(c);
+            }
+        }
+        fields.insert(key, value);
+    }
+    Some(fields)
+}
+
+/// 验证失败的原因，前端按 `code` 翻译，`Display` 为默认的英文消息
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum ValidateErrorCode {
+    FileNotFound { path: String },
+    FileReadFailed { path: String, detail: String },
+    InvalidYaml { detail: String },
+    /// 订阅中出现了会覆盖控制接口等设置的键
+    SubscriptionForbiddenKeys { keys: Vec<String> },
+    /// 内核验证未通过，`detail` 为内核输出
+    CoreRejected { detail: String },
+    ValidateProcessExited { exit_code: i32 },
+    ValidateProcessKilled,
+    ValidateTimeout { secs: u64 },
+    ScriptReadFailed { detail: String },
+    ScriptSyntaxError { detail: String },
+    ScriptMissingMain,
+    ScriptRuntimeError { detail: String },
+    ScriptReturnedNothing,
+    ScriptReturnedNonObject { type_name: String },
+    ScriptTimeout { secs: u64 },
+}
+
+impl fmt::Display for ValidateErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FileNotFound { path } => write!(f, "File not found: {path}"),
+            Self::FileReadFailed { path, detail } => write!(f, "Failed to read {path}: {detail}"),
+            Self::InvalidYaml { detail } => write!(f, "Invalid YAML: {detail}"),
+            Self::SubscriptionForbiddenKeys { keys } => {
+                write!(f, "Subscription must not contain: {}", keys.join(", "))
+            }
+            Self::CoreRejected { detail } => write!(f, "{detail}"),
+            Self::ValidateProcessExited { exit_code } => {
+                write!(f, "Validation process exited with code {exit_code}")
+            }
+            Self::ValidateProcessKilled => write!(f, "Validation process was terminated"),
+            Self::ValidateTimeout { secs } => write!(f, "Validation timed out after {secs}s"),
+            Self::ScriptReadFailed { detail } => write!(f, "Failed to read script file: {detail}"),
+            Self::ScriptSyntaxError { detail } => write!(f, "Script syntax error: {detail}"),
+            Self::ScriptMissingMain => write!(f, "Script must contain a main function"),
+            Self::ScriptRuntimeError { detail } => write!(f, "Script runtime error: {detail}"),
+            Self::ScriptReturnedNothing => write!(f, "Script main returned null or undefined"),
+            Self::ScriptReturnedNonObject { type_name } => {
+                write!(f, "Script main must return an object, got {type_name}")
+            }
+            Self::ScriptTimeout { secs } => write!(f, "Script execution timed out after {secs}s"),
+        }
+    }
+}
+
+impl std::error::Error for ValidateErrorCode {}
+
+impl ValidateErrorCode {
+    /// 对应的前端通知类型
+    pub fn notice_status(&self) -> &'static str {
+        match self {
+            Self::FileNotFound { .. } => "config_validate::file_not_found",
+            Self::FileReadFailed { .. }
+            | Self::InvalidYaml { .. }
+            | Self::SubscriptionForbiddenKeys { .. }
+            | Self::CoreRejected { .. } => "config_validate::error",
+            Self::ValidateProcessExited { .. }
+            | Self::ValidateProcessKilled
+            | Self::ValidateTimeout { .. } => "config_validate::process_terminated",
+            Self::ScriptSyntaxError { .. } => "config_validate::script_syntax_error",
+            Self::ScriptMissingMain => "config_validate::script_missing_main",
+            Self::ScriptReadFailed { .. }
+            | Self::ScriptRuntimeError { .. }
+            | Self::ScriptReturnedNothing
+            | Self::ScriptReturnedNonObject { .. }
+            | Self::ScriptTimeout { .. } => "config_validate::script_error",
+        }
+    }
+}
+
+/// 验证结果，验证过程本身出错时由外层的 `anyhow::Result` 表示
+pub type ValidateResult = Result<(), ValidateErrorCode>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub errors: Vec<ValidationError>,
+    /// 内核的原始输出，解析不出错误时由前端直接显示
+    pub raw: String,
+    /// 内核输出中 error 及以上级别的日志
+    pub entries: Vec<CoreLogEntry>,
+    /// 验证失败的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ValidateErrorCode>,
+}
+
+impl ValidationReport {
+    pub fn ok() -> Self {
+        Self {
+            valid: true,
+            errors: vec![],
+            raw: String::new(),
+            entries: vec![],
+            error: None,
+        }
+    }
+
+    pub fn process_error(error: ValidateErrorCode) -> Self {
+        let message = error.to_string();
+        Self {
+            valid: false,
+            errors: vec![ValidationError {
+                file: None,
+                line: None,
+                message: message.clone(),
+                kind: ValidationErrorKind::Process,
+            }],
+            raw: message,
+            entries: vec![],
+            error: Some(error),
+        }
+    }
+
+    pub fn result(self) -> ValidateResult {
+        match self.error {
+            Some(error) if !self.valid => Err(error),
+            None if !self.valid => Err(ValidateErrorCode::CoreRejected { detail: self.raw }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 从内核输出中提取错误，结构化的日志只看 error 及以上级别，
+/// 完全没有结构化日志时退回到按关键字查找
+pub fn parse_core_output(output: &CoreOutput, config_path: &str) -> Vec<ValidationError> {
+    let mut errors = if output.is_structured() {
+        output
+            .problems()
+            .iter()
+            .filter_map(|entry| classify(&entry.message, config_path))
+            .collect::<Vec<_>>()
+    } else {
+        output
+            .unstructured
+            .iter()
+            .filter(|line| line.to_lowercase().contains("error"))
+            .filter_map(|line| classify(line, config_path))
+            .collect::<Vec<_>>()
+    };
+    errors.dedup();
+    errors
+}
+
+fn classify(message: &str, config_path: &str) -> Option<ValidationError> {
+    let lower = message.to_lowercase();
+    // `configuration file xxx test failed` 只是汇总，不单独作为错误
+    if message.is_empty() || lower.ends_with("test failed") {
+        return None;
+    }
+
+    let file = yaml_file(message);
+    let kind = if lower.contains("provider") || file.as_deref().is_some_and(|f| f != config_path) {
+        ValidationErrorKind::Provider
+    } else if lower.contains("yaml:") {
+        ValidationErrorKind::Yaml
+    } else if lower.contains("parse config error") {
+        ValidationErrorKind::Parse
+    } else {
+        ValidationErrorKind::Other
+    };
+
+    Some(ValidationError {
+        file: file.or_else(|| Some(config_path.to_string())),
+        line: line_number(message),
+        message: message.to_string(),
+        kind,
+    })
+}
+
+/// `yaml: line 42: ...` 中的行号
+fn line_number(message: &str) -> Option<u32> {
+    let start = message.find("line ")? + 5;
+    let digits = message[start..]
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect::<String>();
+    digits.parse().ok()
+}
+
+/// 消息中出现的 yaml 文件路径
+fn yaml_file(message: &str) -> Option<String> {
+    message
+        .split(|c: char| c.is_whitespace() || c == '"' || c == '\'' || c == '[' || c == ']')
+        .map(|token| token.trim_end_matches([':', ',']))
+        .find(|token| token.ends_with(".yaml") || token.ends_with(".yml"))
+        .map(String::from)
+}
+
+#[test]
+fn test_parse_core_output() {
+    let output = r#"time="2024-05-01T10:00:00+08:00" level=info msg="Start initial configuration in progress"
+time="2024-05-01T10:00:00+08:00" level=error msg="Parse config error: yaml: line 42: did not find expected key"
+time="2024-05-01T10:00:00+08:00" level=error msg="initial rule provider \"ads\" error: ./rules/ads.yaml: yaml: line 3: mapping values are not allowed"
+time="2024-05-01T10:00:00+08:00" level=error msg="Parse config error: rules[3] [RULE-SET,x,DIRECT] error: rule set [x] not found"
+configuration file /tmp/check.yaml test failed
+"#;
+    let errors = parse_core_output(&CoreOutput::parse(output), "/tmp/check.yaml");
+    assert_eq!(errors.len(), 3);
+
+    assert_eq!(errors[0].kind, ValidationErrorKind::Yaml);
+    assert_eq!(errors[0].line, Some(42));
+    assert_eq!(errors[0].file.as_deref(), Some("/tmp/check.yaml"));
+
+    assert_eq!(errors[1].kind, ValidationErrorKind::Provider);
+    assert_eq!(errors[1].file.as_deref(), Some("./rules/ads.yaml"));
+    assert_eq!(errors[1].line, Some(3));
+    assert!(errors[1].message.contains(r#""ads""#));
+
+    assert_eq!(errors[2].kind, ValidationErrorKind::Parse);
+    assert_eq!(errors[2].line, None);
+
+    let lossy = String::from_utf8_lossy(b"FATA[0000] Parse config error: bad \xff value");
+    let errors = parse_core_output(&CoreOutput::parse(&lossy), "/tmp/check.yaml");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ValidationErrorKind::Parse);
+}
+
+#[test]
+fn test_core_output() {
+    // info 级别的日志里出现 error、fatal 等字样不算错误
+    let output = r#"time="2024-05-01T10:00:00+08:00" level=info msg="Start initial configuration in progress"
+time="2024-05-01T10:00:00+08:00" level=info msg="rule payload: DOMAIN-KEYWORD,fatal-error,REJECT"
+time="2024-05-01T10:00:00+08:00" level=warning msg="Parse config error is deprecated"
+"#;
+    let parsed = CoreOutput::parse(output);
+    assert_eq!(parsed.entries.len(), 3);
+    assert!(!parsed.has_fatal());
+    assert!(parsed.problems().is_empty());
+    assert!(parse_core_output(&parsed, "/tmp/check.yaml").is_empty());
+
+    let output = r#"time="2024-05-01T10:00:00+08:00" level=error msg="proxy 0: missing \"server\""
+time="2024-05-01T10:00:00+08:00" level=fatal msg="Parse config error: proxy 0: missing \"server\""
+configuration file /tmp/check.yaml test failed
+"#;
+    let parsed = CoreOutput::parse(output);
+    assert!(parsed.has_fatal());
+    assert_eq!(parsed.unstructured.len(), 1);
+    let problems = parsed.problems();
+    assert_eq!(problems.len(), 2);
+    assert_eq!(problems[0].level, CoreLogLevel::Error);
+    assert_eq!(problems[0].message, r#"proxy 0: missing "server""#);
+    assert_eq!(problems[1].time.as_deref(), Some("2024-05-01T10:00:00+08:00"));
+
+    // 完全无法解析的输出按关键字查找
+    let parsed = CoreOutput::parse("unexpected error: bad config\nall done\n");
+    assert!(!parsed.is_structured());
+    let errors = parse_core_output(&parsed, "/tmp/check.yaml");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "unexpected error: bad config");
+}
+
+#[test]
+fn test_validate_error_code() {
+    let error = ValidateErrorCode::ValidateProcessExited { exit_code: 2 };
+    assert_eq!(
+        serde_json::to_value(&error).unwrap(),
+        serde_json::json!({ "code": "validate_process_exited", "exit_code": 2 })
+    );
+    assert_eq!(error.to_string(), "Validation process exited with code 2");
+    assert_eq!(error.notice_status(), "config_validate::process_terminated");
+
+    let report = ValidationReport::process_error(ValidateErrorCode::ValidateTimeout { secs: 20 });
+    assert_eq!(report.raw, "Validation timed out after 20s");
+    assert_eq!(report.result(), Err(ValidateErrorCode::ValidateTimeout { secs: 20 }));
+}
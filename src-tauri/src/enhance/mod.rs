@@ -1,7 +1,7 @@
 mod chain;
 pub mod field;
 mod merge;
-mod script;
+pub mod script;
 pub mod seq;
 mod tun;
 
@@ -12,6 +12,7 @@ use self::script::*;
 use self::seq::*;
 use self::tun::*;
 use crate::config::Config;
+use crate::core::controller;
 use crate::utils::tmpl;
 use serde_yaml::Mapping;
 use std::collections::HashMap;
@@ -22,33 +23,30 @@ type ResultLog = Vec<(String, String)>;
 /// Enhance mode
 /// 返回最终订阅、该订阅包含的键、和script执行的结果
 pub async fn enhance() -> (Mapping, Vec<String>, HashMap<String, ResultLog>) {
+    enhance_with(&HashSet::new()).await
+}
+
+/// 跳过 `skip` 中的增强项，用于安全模式下查找出错的增强项
+pub async fn enhance_with(
+    skip: &HashSet<String>,
+) -> (Mapping, Vec<String>, HashMap<String, ResultLog>) {
     // config.yaml 的订阅
     let clash_config = { Config::clash().latest().0.clone() };
 
-    let (clash_core, enable_tun, enable_builtin, socks_enabled, http_enabled) = {
+    let (clash_core, enable_tun, enable_builtin) = {
         let verge = Config::verge();
         let verge = verge.latest();
         (
             verge.clash_core.clone(),
             verge.enable_tun_mode.unwrap_or(false),
             verge.enable_builtin_enhanced.unwrap_or(true),
-            verge.verge_socks_enabled.unwrap_or(false),
-            verge.verge_http_enabled.unwrap_or(false),
         )
     };
-    #[cfg(not(target_os = "windows"))]
-    let redir_enabled = {
-        let verge = Config::verge();
-        let verge = verge.latest();
-        verge.verge_redir_enabled.unwrap_or(false)
-    };
-    #[cfg(target_os = "linux")]
-    let tproxy_enabled = {
+    let (store_selected, store_fake_ip) = {
         let verge = Config::verge();
         let verge = verge.latest();
-        verge.verge_tproxy_enabled.unwrap_or(false)
+        (verge.selection_store(), verge.core_store_fake_ip)
     };
-
     // 从profiles里拿东西
     let (
         mut config,
@@ -69,6 +67,7 @@ pub async fn enhance() -> (Mapping, Vec<String>, HashMap<String, ResultLog>) {
             .get_item(&profiles.current_merge().unwrap_or_default())
             .ok()
             .and_then(<Option<ChainItem>>::from)
+            .filter(|item| !skip.contains(&item.uid))
             .unwrap_or_else(|| ChainItem {
                 uid: "".into(),
                 data: ChainType::Merge(Mapping::new()),
@@ -77,6 +76,7 @@ pub async fn enhance() -> (Mapping, Vec<String>, HashMap<String, ResultLog>) {
             .get_item(&profiles.current_script().unwrap_or_default())
             .ok()
             .and_then(<Option<ChainItem>>::from)
+            .filter(|item| !skip.contains(&item.uid))
             .unwrap_or_else(|| ChainItem {
                 uid: "".into(),
                 data: ChainType::Script(tmpl::ITEM_SCRIPT.into()),
@@ -85,6 +85,7 @@ pub async fn enhance() -> (Mapping, Vec<String>, HashMap<String, ResultLog>) {
             .get_item(&profiles.current_rules().unwrap_or_default())
             .ok()
             .and_then(<Option<ChainItem>>::from)
+            .filter(|item| !skip.contains(&item.uid))
             .unwrap_or_else(|| ChainItem {
                 uid: "".into(),
                 data: ChainType::Rules(SeqMap::default()),
@@ -93,6 +94,7 @@ pub async fn enhance() -> (Mapping, Vec<String>, HashMap<String, ResultLog>) {
             .get_item(&profiles.current_proxies().unwrap_or_default())
             .ok()
             .and_then(<Option<ChainItem>>::from)
+            .filter(|item| !skip.contains(&item.uid))
             .unwrap_or_else(|| ChainItem {
                 uid: "".into(),
                 data: ChainType::Proxies(SeqMap::default()),
@@ -101,6 +103,7 @@ pub async fn enhance() -> (Mapping, Vec<String>, HashMap<String, ResultLog>) {
             .get_item(&profiles.current_groups().unwrap_or_default())
             .ok()
             .and_then(<Option<ChainItem>>::from)
+            .filter(|item| !skip.contains(&item.uid))
             .unwrap_or_else(|| ChainItem {
                 uid: "".into(),
                 data: ChainType::Groups(SeqMap::default()),
@@ -110,6 +113,7 @@ pub async fn enhance() -> (Mapping, Vec<String>, HashMap<String, ResultLog>) {
             .get_item(&"Merge".to_string())
             .ok()
             .and_then(<Option<ChainItem>>::from)
+            .filter(|item| !skip.contains(&item.uid))
             .unwrap_or_else(|| ChainItem {
                 uid: "Merge".into(),
                 data: ChainType::Merge(Mapping::new()),
@@ -119,6 +123,7 @@ pub async fn enhance() -> (Mapping, Vec<String>, HashMap<String, ResultLog>) {
             .get_item(&"Script".to_string())
             .ok()
             .and_then(<Option<ChainItem>>::from)
+            .filter(|item| !skip.contains(&item.uid))
             .unwrap_or_else(|| ChainItem {
                 uid: "Script".into(),
                 data: ChainType::Script(tmpl::ITEM_SCRIPT.into()),
@@ -201,6 +206,149 @@ pub async fn enhance() -> (Mapping, Vec<String>, HashMap<String, ResultLog>) {
     }
 
     // 合并默认的config
+    config = use_clash_config(config, clash_config);
+    config = use_controller_endpoint(config);
+
+    // 内建脚本最后跑
+    if enable_builtin {
+        ChainItem::builtin()
+            .into_iter()
+            .filter(|(s, _)| s.is_support(clash_core.as_ref()))
+            .map(|(_, c)| c)
+            .for_each(|item| {
+                log::debug!(target: "app", "run builtin script {}", item.uid);
+                if let ChainType::Script(script) = item.data {
+                    match use_script(script, config.to_owned(), "".to_string()) {
+                        Ok((res_config, _)) => {
+                            config = res_config;
+                        }
+                        Err(err) => {
+                            log::error!(target: "app", "builtin script error `{err}`");
+                        }
+                    }
+                }
+            });
+    }
+
+    config = use_profile_store(config, store_selected, store_fake_ip);
+
+    #[cfg(any(debug_assertions, feature = "mock-mode"))]
+    if let Some(port) = crate::core::mock::mock_port() {
+        config = crate::core::mock::use_mock(config, port);
+    }
+
+    config = use_tun(config, enable_tun).await;
+    config = use_sort(config);
+
+    let mut exists_set = HashSet::new();
+    exists_set.extend(exists_keys);
+    exists_keys = exists_set.into_iter().collect();
+
+    (config, exists_keys, result_map)
+}
+
+/// 当前订阅生效的增强项，按应用的顺序排列
+pub fn chain_uids() -> Vec<String> {
+    let profiles = Config::profiles();
+    let profiles = profiles.latest();
+    [
+        Some("Merge".to_string()),
+        Some("Script".to_string()),
+        profiles.current_rules(),
+        profiles.current_proxies(),
+        profiles.current_groups(),
+        profiles.current_merge(),
+        profiles.current_script(),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|uid| {
+        profiles
+            .get_item(uid)
+            .ok()
+            .and_then(<Option<ChainItem>>::from)
+            .is_some()
+    })
+    .collect()
+}
+
+/// 配置验证失败时使用的运行时配置
+/// 只保留 config.yaml 的默认配置和 verge 注入的端口、控制接口、密钥、tun 等字段，
+/// 不使用订阅和 Merge/Script 等增强，返回配置和保留的字段
+pub async fn enhance_fallback() -> (Mapping, Vec<String>) {
+    let clash_config = { Config::clash().latest().0.clone() };
+    let enable_tun = { Config::verge().latest().enable_tun_mode.unwrap_or(false) };
+
+    let config = use_clash_config(Mapping::new(), clash_config);
+    let config = use_controller_endpoint(config);
+    let config = use_sort(use_tun(config, enable_tun).await);
+    let keys = config
+        .keys()
+        .filter_map(|key| key.as_str().map(String::from))
+        .collect();
+    (config, keys)
+}
+
+/// 选择本地连接时注入 unix socket / 命名管道的地址，并清空 TCP 的 `external-controller`
+/// 应用和前端都通过本地连接访问内核，不再监听端口
+fn use_controller_endpoint(mut config: Mapping) -> Mapping {
+    match controller::local_endpoint() {
+        Ok(Some((key, endpoint))) => {
+            config.insert(key.into(), endpoint.into());
+            config.insert("external-controller".into(), "".into());
+        }
+        Ok(None) => {}
+        Err(err) => log::error!(target: "app", "failed to get the controller endpoint: {err}"),
+    }
+    config
+}
+
+/// 按设置覆盖 `profile` 中的 `store-selected` 和 `store-fake-ip`，未设置的项保留订阅自身的值
+fn use_profile_store(
+    mut config: Mapping,
+    store_selected: Option<bool>,
+    store_fake_ip: Option<bool>,
+) -> Mapping {
+    if store_selected.is_none() && store_fake_ip.is_none() {
+        return config;
+    }
+    let mut profile = config
+        .get("profile")
+        .and_then(|val| val.as_mapping().cloned())
+        .unwrap_or_default();
+    if let Some(store_selected) = store_selected {
+        profile.insert("store-selected".into(), store_selected.into());
+    }
+    if let Some(store_fake_ip) = store_fake_ip {
+        profile.insert("store-fake-ip".into(), store_fake_ip.into());
+    }
+    config.insert("profile".into(), profile.into());
+    config
+}
+
+/// 将 config.yaml 的默认配置合并到订阅上，tun 逐项合并，未启用的端口会被移除
+fn use_clash_config(mut config: Mapping, clash_config: Mapping) -> Mapping {
+    let (socks_enabled, http_enabled) = {
+        let verge = Config::verge();
+        let verge = verge.latest();
+        (
+            verge.verge_socks_enabled.unwrap_or(false),
+            verge.verge_http_enabled.unwrap_or(false),
+        )
+    };
+    #[cfg(not(target_os = "windows"))]
+    let redir_enabled = {
+        let verge = Config::verge();
+        let verge = verge.latest();
+        verge.verge_redir_enabled.unwrap_or(false)
+    };
+    #[cfg(target_os = "linux")]
+    let tproxy_enabled = {
+        let verge = Config::verge();
+        let verge = verge.latest();
+        verge.verge_tproxy_enabled.unwrap_or(false)
+    };
+
     for (key, value) in clash_config.into_iter() {
         if key.as_str() == Some("tun") {
             let mut tun = config.get_mut("tun").map_or(Mapping::new(), |val| {
@@ -237,34 +385,72 @@ pub async fn enhance() -> (Mapping, Vec<String>, HashMap<String, ResultLog>) {
             config.insert(key, value);
         }
     }
+    config
+}
 
-    // 内建脚本最后跑
-    if enable_builtin {
-        ChainItem::builtin()
-            .into_iter()
-            .filter(|(s, _)| s.is_support(clash_core.as_ref()))
-            .map(|(_, c)| c)
-            .for_each(|item| {
-                log::debug!(target: "app", "run builtin script {}", item.uid);
-                if let ChainType::Script(script) = item.data {
-                    match use_script(script, config.to_owned(), "".to_string()) {
-                        Ok((res_config, _)) => {
-                            config = res_config;
-                        }
-                        Err(err) => {
-                            log::error!(target: "app", "builtin script error `{err}`");
-                        }
-                    }
-                }
-            });
-    }
+#[test]
+fn test_use_profile_store() {
+    let config = serde_yaml::from_str::<Mapping>(
+        "profile:\n  store-selected: true\n  tracing: true\nmode: rule\n",
+    )
+    .unwrap();
+    use serde_yaml::Value;
+    let get_profile = |config: &Mapping| config.get("profile").cloned().unwrap_or_default();
 
-    config = use_tun(config, enable_tun).await;
-    config = use_sort(config);
+    // 未设置时保留订阅自身的 store-selected，不添加 profile
+    let generated = use_profile_store(config.clone(), None, None);
+    assert_eq!(generated, config);
+    let empty = use_profile_store(Mapping::new(), None, None);
+    assert!(empty.get("profile").is_none());
 
-    let mut exists_set = HashSet::new();
-    exists_set.extend(exists_keys);
-    exists_keys = exists_set.into_iter().collect();
+    // 设置后只覆盖对应的键，其他键保持不变
+    let generated = use_profile_store(config.clone(), Some(false), None);
+    let profile = get_profile(&generated);
+    assert_eq!(profile["store-selected"], Value::Bool(false));
+    assert_eq!(profile["tracing"], Value::Bool(true));
+    assert!(profile.get("store-fake-ip").is_none());
 
-    (config, exists_keys, result_map)
+    let generated = use_profile_store(Mapping::new(), None, Some(true));
+    assert_eq!(generated["profile"]["store-fake-ip"], Value::Bool(true));
+    assert!(generated["profile"].get("store-selected").is_none());
+}
+
+#[test]
+fn test_selection_restored_once() {
+    use crate::config::{IRuntime, IVerge};
+
+    // 订阅自身开启了 store-selected 和没有设置 profile 的两个订阅之间来回切换
+    let stored = serde_yaml::from_str::<Mapping>("profile:\n  store-selected: true\n").unwrap();
+    let plain = serde_yaml::from_str::<Mapping>("mode: rule\n").unwrap();
+
+    let settings = [None, Some(false), Some(true)];
+    for prefer in settings {
+        for store in settings {
+            let verge = IVerge {
+                prefer_selection_replay: prefer,
+                core_store_selected: store,
+                ..IVerge::default()
+            };
+            for subscription in [&stored, &plain, &stored] {
+                let config = use_profile_store(subscription.clone(), verge.selection_store(), None);
+                let runtime = IRuntime {
+                    config: Some(config),
+                    ..IRuntime::default()
+                };
+                let core_restores = runtime
+                    .config
+                    .as_ref()
+                    .and_then(|config| config.get("profile"))
+                    .and_then(|profile| profile.get("store-selected"))
+                    .and_then(|value| value.as_bool())
+                    .unwrap_or(false);
+                let app_restores = runtime.selection_replay();
+                let restores = usize::from(core_restores) + usize::from(app_restores);
+                assert_eq!(restores, 1, "prefer: {prefer:?}, store: {store:?}");
+                if prefer == Some(true) {
+                    assert!(app_restores);
+                }
+            }
+        }
+    }
 }
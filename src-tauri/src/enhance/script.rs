@@ -1,6 +1,7 @@
 use super::use_lowercase;
 use anyhow::{Error, Result};
 use serde_yaml::Mapping;
+use crate::core::validation::{ValidateErrorCode, ValidateResult};
 
 pub fn use_script(
     script: String,
@@ -77,6 +78,99 @@ pub fn use_script(
     }
 }
 
+/// 校验时传给 main 的示例配置
+const SAMPLE_CONFIG: &str = r#"{
+  "proxies": [{ "name": "sample", "type": "ss", "server": "127.0.0.1", "port": 8388, "cipher": "aes-128-gcm", "password": "sample" }],
+  "proxy-groups": [{ "name": "Proxy", "type": "select", "proxies": ["sample"] }],
+  "rules": ["MATCH,Proxy"]
+}"#;
+
+/// 用示例配置实际执行一次 main，检查运行时异常和返回值
+/// `loop_limit` 限制单个循环的迭代次数，避免脚本中的死循环卡住校验
+pub fn check_script(script: &str, loop_limit: u64) -> ValidateResult {
+    use boa_engine::{Context, Source};
+    use ValidateErrorCode::*;
+
+    let mut context = Context::default();
+    context
+        .runtime_limits_mut()
+        .set_loop_iteration_limit(loop_limit);
+    let _ = context.eval(Source::from_bytes(
+        "var console = Object.freeze({ log(){}, info(){}, error(){}, debug(){} });",
+    ));
+
+    context
+        .eval(Source::from_bytes(script))
+        .map_err(|err| ScriptSyntaxError { detail: err.to_string() })?;
+
+    let code = format!(
+        r#"(function () {{
+        if (typeof main !== "function") return "__no_main__";
+        let result;
+        try {{
+          result = main({SAMPLE_CONFIG}, "validate");
+        }} catch (err) {{
+          const line = err && err.lineNumber ? ` (line ${{err.lineNumber}})` : "";
+          return `__error__${{String(err)}}${{line}}`;
+        }}
+        if (result === null || result === undefined) return "__nullish__";
+        if (Array.isArray(result)) return "__type__array";
+        if (typeof result !== "object") return `__type__${{typeof result}}`;
+        return "__ok__";
+      }})()"#
+    );
+    let result = context
+        .eval(Source::from_bytes(code.as_str()))
+        .map_err(|err| ScriptRuntimeError { detail: err.to_string() })?;
+    let result = result
+        .to_string(&mut context)
+        .map_err(|err| ScriptRuntimeError { detail: err.to_string() })?
+        .to_std_string_escaped();
+
+    match result.as_str() {
+        "__ok__" => Ok(()),
+        "__no_main__" => Err(ScriptMissingMain),
+        "__nullish__" => Err(ScriptReturnedNothing),
+        _ => match (result.strip_prefix("__type__"), result.strip_prefix("__error__")) {
+            (Some(ty), _) => Err(ScriptReturnedNonObject { type_name: ty.into() }),
+            (_, Some(err)) => Err(ScriptRuntimeError { detail: err.into() }),
+            _ => Err(ScriptRuntimeError { detail: result }),
+        },
+    }
+}
+
+#[test]
+fn test_check_script() {
+    let limit = 100_000;
+    assert!(check_script("function main(config) { return config; }", limit).is_ok());
+    assert!(check_script("const main = (config) => ({ ...config });", limit).is_ok());
+
+    use ValidateErrorCode::*;
+
+    let err = check_script("function helper(config) { return config; }", limit).unwrap_err();
+    assert_eq!(err, ScriptMissingMain);
+
+    let err = check_script("function main(config) { config.foo.bar = 1; }", limit).unwrap_err();
+    assert!(
+        matches!(&err, ScriptRuntimeError { detail } if detail.starts_with("TypeError")),
+        "{err}"
+    );
+
+    let err = check_script("function main(config) { return null; }", limit).unwrap_err();
+    assert_eq!(err, ScriptReturnedNothing);
+
+    let err = check_script("function main(config) { return 1; }", limit).unwrap_err();
+    assert_eq!(err, ScriptReturnedNonObject { type_name: "number".into() });
+
+    let err = check_script("function main(config) { return []; }", limit).unwrap_err();
+    assert_eq!(err, ScriptReturnedNonObject { type_name: "array".into() });
+
+    let err = check_script("function main(config) {", limit).unwrap_err();
+    assert!(matches!(err, ScriptSyntaxError { .. }));
+
+    assert!(check_script("function main(config) { while (true) {} }", limit).is_err());
+}
+
 #[test]
 fn test_script() {
     let script = r#"
@@ -9,71 +9,91 @@ use crate::config::*;
 use crate::core::*;
 use crate::log_err;
 use crate::utils::dirs::app_home_dir;
-use crate::utils::resolve;
-use anyhow::{bail, Result};
+use crate::utils::{dirs, help, resolve};
+use anyhow::{bail, Context, Result};
 use reqwest_dav::list_cmd::ListFile;
 use serde_yaml::{Mapping, Value};
 use std::fs;
 use tauri::Manager;
 use tauri_plugin_clipboard_manager::ClipboardExt;
-use tauri_plugin_window_state::{AppHandleExt, StateFlags};
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_window_state::{AppHandleExt, StateFlags, WindowExt};
 use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
 
-// 打开面板
-#[allow(dead_code)]
-pub fn open_or_close_dashboard() {
-    println!("Attempting to open/close dashboard");
-    log::info!(target: "app", "Attempting to open/close dashboard");
+// 打开或关闭面板，快捷键和托盘点击共用
+// 窗口可见时隐藏（轻量模式下销毁），隐藏或最小化时显示，不存在时才创建
+// 点击托盘时窗口会失去焦点，所以不以焦点判断
+pub fn toggle_dashboard() {
+    let Some(window) = handle::Handle::global().get_window() else {
+        log::info!(target: "app", "no main window, create it");
+        resolve::create_window();
+        return;
+    };
 
-    if let Some(window) = handle::Handle::global().get_window() {
-        println!("Found existing window");
-        log::info!(target: "app", "Found existing window");
-
-        // 如果窗口存在，则切换其显示状态
-        match window.is_visible() {
-            Ok(visible) => {
-                println!("Window visibility status: {}", visible);
-                log::info!(target: "app", "Window visibility status: {}", visible);
-
-                if visible {
-                    println!("Attempting to hide window");
-                    log::info!(target: "app", "Attempting to hide window");
-                    let _ = window.hide();
-                } else {
-                    println!("Attempting to show and focus window");
-                    log::info!(target: "app", "Attempting to show and focus window");
-                    if window.is_minimized().unwrap_or(false) {
-                        let _ = window.unminimize();
-                    }
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                }
-            }
-            Err(e) => {
-                println!("Failed to get window visibility: {:?}", e);
-                log::error!(target: "app", "Failed to get window visibility: {:?}", e);
-            }
+    let visible = window.is_visible().unwrap_or(false);
+    let minimized = window.is_minimized().unwrap_or(false);
+    if visible && !minimized {
+        if Config::verge().latest().enable_lite_mode.unwrap_or(false) {
+            log::info!(target: "app", "lite mode enabled, destroy the main window");
+            log_err!(destroy_window(window));
+        } else {
+            log::info!(target: "app", "hide the main window");
+            let _ = window.hide();
         }
-    } else {
-        println!("No existing window found, creating new window");
-        log::info!(target: "app", "No existing window found, creating new window");
-        resolve::create_window();
+        return;
+    }
+
+    log::info!(target: "app", "show the main window");
+    if minimized {
+        let _ = window.unminimize();
     }
+    let _ = window.show();
+    // macOS 下应用使用 Accessory 策略，没有 Dock 图标，需要先激活应用窗口才能获得焦点
+    #[cfg(target_os = "macos")]
+    if let Some(app_handle) = handle::Handle::global().app_handle() {
+        let _ = app_handle.show();
+    }
+    let _ = window.set_focus();
+}
+
+/// 销毁主窗口，退出 webview 进程，只保留托盘和内核
+fn destroy_window(window: tauri::WebviewWindow) -> Result<()> {
+    let app_handle = handle::Handle::global().app_handle().context("app handle is not ready")?;
+    // 销毁前保存窗口位置，退出轻量模式时恢复
+    let _ = app_handle.save_window_state(StateFlags::all());
+    // 完全退出 webview 进程
+    window.close()?;  // 先关闭窗口
+    if let Some(webview) = app_handle.get_webview_window("main") {
+        webview.destroy()?;  // 销毁 webview 进程
+    }
+    Ok(())
 }
 
+/// 正在通过托盘或快捷键重启内核
+static RESTARTING_CORE: AtomicBool = AtomicBool::new(false);
+
 // 重启clash
+/// 重启内核，已有重启在进行时忽略本次请求，避免连续按快捷键时重复通知
 pub fn restart_clash_core() {
+    if RESTARTING_CORE.swap(true, Ordering::SeqCst) {
+        log::info!(target: "app", "core restart already in progress, ignored");
+        return;
+    }
     tauri::async_runtime::spawn(async {
         match CoreManager::global().restart_core().await {
             Ok(_) => {
                 handle::Handle::refresh_clash();
-                handle::Handle::notice_message("set_config::ok", "ok");
+                handle::Handle::notice_message("core_restart::ok", "ok");
             }
             Err(err) => {
-                handle::Handle::notice_message("set_config::error", format!("{err}"));
+                handle::Handle::notice_message("core_restart::error", format!("{err}"));
                 log::error!(target:"app", "{err}");
             }
         }
+        RESTARTING_CORE.store(false, Ordering::SeqCst);
     });
 }
 
@@ -121,45 +141,195 @@ pub fn setup_window_state_monitor(app_handle: &tauri::AppHandle) {
 
 // 切换模式 rule/global/direct/script mode
 pub fn change_clash_mode(mode: String) {
-    let mut mapping = Mapping::new();
-    mapping.insert(Value::from("mode"), mode.clone().into());
     tauri::async_runtime::spawn(async move {
-        log::debug!(target: "app", "change clash mode to {mode}");
+        if let Err(err) = apply_clash_mode(mode).await {
+            log::error!(target: "app", "{err}");
+        }
+    });
+}
 
-        match clash_api::patch_configs(&mapping).await {
-            Ok(_) => {
-                // 更新订阅
-                Config::clash().data().patch_config(mapping);
+async fn apply_clash_mode(mode: String) -> Result<()> {
+    log::debug!(target: "app", "change clash mode to {mode}");
+    patch_clash_runtime_option("mode", Value::from(mode)).await
+}
 
-                if Config::clash().data().save_config().is_ok() {
-                    handle::Handle::refresh_clash();
-                    log_err!(tray::Tray::global().update_menu());
-                    log_err!(tray::Tray::global().update_icon(None));
-                }
+/// 可以直接 PATCH 内核并保存到 clash 配置的选项，以及允许的值
+/// 这些字段都在 `HANDLE_FIELDS` 中，下次生成配置时会使用保存的值
+const RUNTIME_OPTIONS: [(&str, RuntimeOptionValue); 5] = [
+    ("allow-lan", RuntimeOptionValue::Bool),
+    ("ipv6", RuntimeOptionValue::Bool),
+    ("unified-delay", RuntimeOptionValue::Bool),
+    (
+        "log-level",
+        RuntimeOptionValue::OneOf(&["debug", "info", "warning", "error", "silent"]),
+    ),
+    ("mode", RuntimeOptionValue::OneOf(&["rule", "global", "direct"])),
+];
+
+enum RuntimeOptionValue {
+    Bool,
+    OneOf(&'static [&'static str]),
+}
+
+/// 检查选项是否在 `RUNTIME_OPTIONS` 中，以及值的类型
+fn check_runtime_option(key: &str, value: &Value) -> Result<()> {
+    let Some((_, expected)) = RUNTIME_OPTIONS.iter().find(|(name, _)| *name == key) else {
+        bail!("unsupported runtime option \"{key}\"");
+    };
+    let valid = match expected {
+        RuntimeOptionValue::Bool => value.is_bool(),
+        RuntimeOptionValue::OneOf(values) => {
+            value.as_str().is_some_and(|value| values.contains(&value))
+        }
+    };
+    if !valid {
+        bail!("invalid value for \"{key}\": {value:?}");
+    }
+    Ok(())
+}
+
+/// 修改内核中的单个选项并保存到 clash 配置
+/// 内核修改失败时不保存；保存失败时把内核恢复为原来的值
+pub async fn patch_clash_runtime_option(key: &str, value: Value) -> Result<()> {
+    check_runtime_option(key, &value)?;
+    let previous = { Config::clash().latest().0.get(key).cloned() };
+
+    let mut mapping = Mapping::new();
+    mapping.insert(key.into(), value.clone());
+    clash_api::patch_configs(&mapping).await?;
+
+    Config::clash().draft().patch_config(mapping);
+    let saved = { Config::clash().draft().save_config() };
+    if let Err(err) = saved {
+        Config::clash().discard();
+        match previous {
+            Some(previous) => {
+                let mut mapping = Mapping::new();
+                mapping.insert(key.into(), previous);
+                log_err!(clash_api::patch_configs(&mapping).await);
+            }
+            None => log::warn!(target: "app", "no previous value of {key} to restore"),
+        }
+        return Err(err.context(format!("failed to save {key}")));
+    }
+    Config::clash().apply();
+
+    // 运行时配置与内核保持一致，界面从这里读取当前值
+    if let Some(config) = Config::runtime().latest().config.as_mut() {
+        config.insert(key.into(), value);
+    }
+    handle::Handle::refresh_clash();
+    if key == "mode" {
+        log_err!(tray::Tray::global().update_menu());
+        log_err!(tray::Tray::global().update_icon(None));
+    }
+    Ok(())
+}
+
+// 按 rule → global → direct 的顺序切换到下一个模式，当前模式从内核读取
+pub fn cycle_clash_mode() {
+    tauri::async_runtime::spawn(async {
+        let result = async {
+            let mode = clash_api::get_mode().await.context("failed to get the current mode")?;
+            let next = next_clash_mode(&mode);
+            apply_clash_mode(next.to_string()).await?;
+            anyhow::Ok(next)
+        }
+        .await;
+        match result {
+            Ok(mode) => notice_toggle(true, format!("Clash mode: {mode}")),
+            Err(err) => {
+                log::error!(target: "app", "failed to cycle clash mode: {err:#}");
+                notice_toggle(false, format!("Failed to switch clash mode: {err:#}"));
             }
-            Err(err) => log::error!(target: "app", "{err}"),
         }
     });
 }
 
+/// 下一个代理模式，其他模式（如 script）切换到 rule
+fn next_clash_mode(mode: &str) -> &'static str {
+    match mode {
+        "rule" => "global",
+        "global" => "direct",
+        _ => "rule",
+    }
+}
+
 // 切换系统代理
 pub fn toggle_system_proxy() {
+    switch_system_proxy(false);
+}
+
+/// 快捷键切换系统代理，完成后通知切换结果
+pub fn toggle_system_proxy_with_notice() {
+    switch_system_proxy(true);
+}
+
+fn switch_system_proxy(notify: bool) {
     let enable = Config::verge().draft().enable_system_proxy;
     let enable = enable.unwrap_or(false);
 
     tauri::async_runtime::spawn(async move {
-        match patch_verge(IVerge {
+        let result = patch_verge(IVerge {
             enable_system_proxy: Some(!enable),
             ..IVerge::default()
         })
-        .await
-        {
+        .await;
+        match &result {
             Ok(_) => handle::Handle::refresh_verge(),
             Err(err) => log::error!(target: "app", "{err}"),
         }
+        if notify {
+            // 失败时 patch_verge 已经丢弃草稿，读到的是实际状态
+            let enabled = Config::verge().latest().enable_system_proxy.unwrap_or(false);
+            let message = match &result {
+                Ok(_) => format!("System proxy: {}", on_off(enabled)),
+                Err(err) => format!("System proxy failed: {err}"),
+            };
+            notice_toggle(result.is_ok(), message);
+        }
     });
 }
 
+fn on_off(enabled: bool) -> &'static str {
+    if enabled {
+        "ON"
+    } else {
+        "OFF"
+    }
+}
+
+/// 通知快捷键切换的结果，窗口关闭、隐藏或最小化时按设置发送系统通知
+fn notice_toggle(ok: bool, message: String) {
+    let status = if ok { "toggle::ok" } else { "toggle::error" };
+    handle::Handle::notice_message(status, message.clone());
+
+    let enable = Config::verge().latest().enable_hotkey_notification.unwrap_or(true);
+    if !enable {
+        return;
+    }
+    // 窗口可见时页面中已经有提示
+    if let Some(window) = handle::Handle::global().get_window() {
+        let visible = window.is_visible().unwrap_or(false);
+        let minimized = window.is_minimized().unwrap_or(false);
+        if visible && !minimized {
+            return;
+        }
+    }
+    let Some(app_handle) = handle::Handle::global().app_handle() else {
+        return;
+    };
+    if let Err(err) = app_handle
+        .notification()
+        .builder()
+        .title("Clash Verge")
+        .body(message)
+        .show()
+    {
+        log::warn!(target: "app", "failed to show notification: {err}");
+    }
+}
+
 // 切换代理文件
 pub fn toggle_proxy_profile(profile_index: String) {
     tauri::async_runtime::spawn(async move {
@@ -175,16 +345,170 @@ pub fn toggle_proxy_profile(profile_index: String) {
     });
 }
 
+// 切换到下一个或上一个订阅，激活失败时 patch_profiles_config 会恢复原来的订阅并通知
+fn switch_profile(forward: bool) {
+    tauri::async_runtime::spawn(async move {
+        let (uid, name) = {
+            let profiles = Config::profiles();
+            let profiles = profiles.latest();
+            let Some(uid) = profiles.neighbor_profile(forward) else {
+                return;
+            };
+            if profiles.current.as_ref() == Some(&uid) {
+                return;
+            }
+            let name = profiles.get_item(&uid).ok().and_then(|item| item.name.clone());
+            (uid.clone(), name.unwrap_or(uid))
+        };
+
+        let profiles = IProfiles {
+            current: Some(uid),
+            items: None,
+        };
+        match cmds::patch_profiles_config(profiles).await {
+            Ok(true) => {
+                handle::Handle::refresh_profiles();
+                handle::Handle::notice_message("profile_switch::ok", name);
+                log_err!(tray::Tray::global().update_menu());
+            }
+            Ok(false) => {}
+            Err(err) => log::error!(target: "app", "{err}"),
+        }
+    });
+}
+
+pub fn next_profile() {
+    switch_profile(true);
+}
+
+pub fn prev_profile() {
+    switch_profile(false);
+}
+
+// 切换策略组的节点，和界面上切换一样保存到当前订阅的 selected 中
+pub fn select_proxy(group: String, proxy: String) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = change_proxy(group, proxy).await {
+            log::error!(target: "app", "{err}");
+        }
+    });
+}
+
+/// 切换策略组的节点，成功后保存到当前订阅的 `selected` 中
+/// 开启 `auto_close_connection` 时关闭受影响的连接，返回关闭的数量
+pub async fn change_proxy(group: String, proxy: String) -> Result<usize> {
+    clash_api::select_proxy(&group, &proxy).await?;
+    log_err!(save_selected(group.clone(), proxy));
+    handle::Handle::refresh_clash();
+
+    // 节点已经切换成功，关闭连接失败不影响结果
+    let closed = match close_connections_after_select(&group).await {
+        Ok(count) => count,
+        Err(err) => {
+            log::warn!(target: "app", "failed to close connections of {group}: {err:#}");
+            0
+        }
+    };
+    if closed > 0 {
+        log::info!(target: "app", "closed {closed} connections after switching {group}");
+    }
+    Ok(closed)
+}
+
+/// 已建立的连接不会随节点切换，需要关闭后让应用重新连接
+async fn close_connections_after_select(group: &str) -> Result<usize> {
+    let (enable, scope) = {
+        let verge = Config::verge();
+        let verge = verge.latest();
+        (
+            verge.auto_close_connection.unwrap_or(true),
+            verge.auto_close_connection_scope.clone(),
+        )
+    };
+    if !enable {
+        return Ok(0);
+    }
+    match scope.as_deref() {
+        Some("all") => clash_api::close_all_connections().await,
+        _ => {
+            clash_api::close_connections_where(|conn| conn.chains.iter().any(|c| c == group))
+                .await
+        }
+    }
+}
+
+fn save_selected(group: String, proxy: String) -> Result<()> {
+    let profiles = Config::profiles();
+    let mut profiles = profiles.data();
+    let current = profiles.get_current().context("no profile is selected")?;
+    let mut selected = profiles.get_item(&current)?.selected.clone().unwrap_or_default();
+
+    let now = PrfSelected {
+        name: Some(group.clone()),
+        now: Some(proxy),
+    };
+    match selected.iter_mut().find(|item| item.name.as_ref() == Some(&group)) {
+        Some(item) => *item = now,
+        None => selected.push(now),
+    }
+
+    let item = PrfItem {
+        selected: Some(selected),
+        ..PrfItem::default()
+    };
+    profiles.patch_item(current, item)
+}
+
 // 切换tun模式
 pub fn toggle_tun_mode() {
+    switch_tun_mode(false);
+}
+
+/// 快捷键切换 TUN 模式，完成后通知切换结果
+pub fn toggle_tun_mode_with_notice() {
+    switch_tun_mode(true);
+}
+
+fn switch_tun_mode(notify: bool) {
     let enable = Config::verge().data().enable_tun_mode;
     let enable = enable.unwrap_or(false);
 
     tauri::async_runtime::spawn(async move {
-        match patch_verge(IVerge {
+        let result = patch_verge(IVerge {
             enable_tun_mode: Some(!enable),
             ..IVerge::default()
         })
+        .await;
+        match &result {
+            Ok(_) => handle::Handle::refresh_verge(),
+            Err(err) => log::error!(target: "app", "{err}"),
+        }
+        if !notify {
+            return;
+        }
+        let enabled = Config::verge().latest().enable_tun_mode.unwrap_or(false);
+        let message = match &result {
+            // 没有服务时内核可能没有权限创建 TUN 网卡
+            Ok(_) if enabled && service::check_service().await.is_err() => {
+                "TUN mode: ON (service not installed, TUN may not work)".to_string()
+            }
+            Ok(_) => format!("TUN mode: {}", on_off(enabled)),
+            Err(err) => format!("TUN mode failed: {err}"),
+        };
+        notice_toggle(result.is_ok(), message);
+    });
+}
+
+// 切换轻量模式，开启时销毁主窗口只保留托盘和内核，关闭时重新创建窗口
+pub fn toggle_lightweight_mode() {
+    let enable = Config::verge().data().enable_lite_mode;
+    let enable = enable.unwrap_or(false);
+
+    tauri::async_runtime::spawn(async move {
+        match patch_verge(IVerge {
+            enable_lite_mode: Some(!enable),
+            ..IVerge::default()
+        })
         .await
         {
             Ok(_) => handle::Handle::refresh_verge(),
@@ -193,6 +517,482 @@ pub fn toggle_tun_mode() {
     });
 }
 
+// 断开并重连所有连接
+pub fn force_reconnect_all() {
+    tauri::async_runtime::spawn(async {
+        log_err!(force_reconnect_all_connections().await);
+    });
+}
+
+/// 关闭所有活动连接，并检查代理是否仍然可用
+pub async fn force_reconnect_all_connections() -> Result<usize> {
+    let count = clash_api::close_all_connections().await?;
+    log::info!(target: "app", "force reconnect: closed {count} connections");
+
+    if let Err(err) = CoreManager::global().connectivity_test(None).await {
+        log::warn!(target: "app", "connectivity test after reconnect failed: {err}");
+    }
+
+    handle::Handle::emit_event(
+        "connections::force_reconnected",
+        serde_json::json!({ "count": count }),
+    );
+    Ok(count)
+}
+
+// 关闭所有活动连接并通知关闭的数量，控制接口不可用时通知错误
+pub fn clear_connections() {
+    tauri::async_runtime::spawn(async {
+        match close_connections(None).await {
+            Ok(count) => {
+                handle::Handle::notice_message("clear_connections::ok", count.to_string());
+            }
+            Err(err) => {
+                log::error!(target: "app", "failed to close connections: {err:#}");
+                handle::Handle::notice_message("clear_connections::error", format!("{err:#}"));
+            }
+        }
+    });
+}
+
+/// 关闭所有活动连接，指定 `filter` 时只关闭域名或代理链包含它的连接，返回关闭的数量
+pub async fn close_connections(filter: Option<String>) -> Result<usize> {
+    let filter = filter.filter(|filter| !filter.trim().is_empty());
+    let count = match filter {
+        Some(filter) => clash_api::close_connections_matching(&filter).await,
+        None => clash_api::close_all_connections().await,
+    }
+    .context("failed to close connections")?;
+    log::info!(target: "app", "closed {count} connections");
+    Ok(count)
+}
+
+// 轮转内核日志
+pub fn rotate_sidecar_log() {
+    tauri::async_runtime::spawn(async {
+        log_err!(CoreManager::global().rotate_core_log().await);
+    });
+}
+
+// 立即检查连通性并通知前端
+pub fn check_connectivity_now() {
+    tauri::async_runtime::spawn(async {
+        let _ = check_connectivity().await;
+    });
+}
+
+/// 测试连通性，结果（延迟或错误）通过通知显示
+pub async fn check_connectivity() -> Result<u32> {
+    match CoreManager::global().connectivity_test(None).await {
+        Ok(delay) => {
+            handle::Handle::notice_message("check_connectivity::ok", delay.to_string());
+            Ok(delay)
+        }
+        Err(err) => {
+            handle::Handle::notice_message("check_connectivity::error", err.to_string());
+            Err(err)
+        }
+    }
+}
+
+// 切换IPv6路由
+pub fn toggle_ipv6_routing() {
+    tauri::async_runtime::spawn(async {
+        log_err!(toggle_ipv6_routing_config().await);
+    });
+}
+
+/// 反转clash配置中的 `ipv6`
+/// TUN网卡的IPv6路由由内核的 `auto-route` 管理，开启TUN时重新加载配置让内核重建路由
+pub async fn toggle_ipv6_routing_config() -> Result<bool> {
+    let enable = !Config::clash()
+        .latest()
+        .0
+        .get("ipv6")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    patch_clash_runtime_option("ipv6", Value::from(enable)).await?;
+
+    if Config::verge().latest().enable_tun_mode.unwrap_or(false) {
+        CoreManager::global()
+            .update_config()
+            .await?
+            .context("failed to apply the ipv6 routes of tun")?;
+    }
+
+    handle::Handle::emit_event(
+        "core::ipv6_routing_changed",
+        serde_json::json!({ "enabled": enable }),
+    );
+    Ok(enable)
+}
+
+/// 更新GEO数据库，立即返回任务id
+/// 由应用下载，取消后不会留下不完整的文件，也不会在后台继续下载
+pub fn update_geo_data(operation_id: Option<String>) -> String {
+    operation::spawn(operation_id, |id, token| async move {
+        let result = update_geo_data_files(&id, &token).await;
+        match &result {
+            Ok(updated) => handle::Handle::notice_message("update_geo::ok", updated.join(", ")),
+            Err(err) if err.is::<operation::Cancelled>() => {
+                log::info!(target: "app", "geo data update cancelled");
+            }
+            Err(err) => handle::Handle::notice_message("update_geo::error", format!("{err}")),
+        }
+        result
+    })
+}
+
+/// 下载全部GEO文件后再一起替换，取消或失败时不会留下不完整的文件
+/// 返回更新了的文件
+async fn update_geo_data_files(id: &str, token: &CancellationToken) -> Result<Vec<String>> {
+    const GEO_BASE: &str = "https://github.com/MetaCubeX/meta-rules-dat/releases/download/latest";
+
+    let geox = Config::clash()
+        .latest()
+        .0
+        .get("geox-url")
+        .and_then(Value::as_mapping)
+        .cloned()
+        .unwrap_or_default();
+    let url = |key: &str, file: &str| {
+        geox.get(key)
+            .and_then(Value::as_str)
+            .map(String::from)
+            .unwrap_or(format!("{GEO_BASE}/{file}"))
+    };
+    let home = app_home_dir()?;
+    let files = [
+        (home.join("Country.mmdb"), url("mmdb", "country.mmdb")),
+        (home.join("geoip.dat"), url("geoip", "geoip.dat")),
+        (home.join("geosite.dat"), url("geosite", "geosite.dat")),
+    ];
+
+    let client = reqwest::Client::new();
+    operation::download_files(&client, &files, token, |target, current, total| {
+        let name = target.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        operation::emit_progress(id, name, current, total)
+    })
+    .await?;
+
+    log::info!(target: "app", "geo data updated");
+    CoreManager::global().update_config().await??;
+    let updated = files
+        .iter()
+        .filter_map(|(target, _)| target.file_name()?.to_str().map(String::from))
+        .collect();
+    Ok(updated)
+}
+
+/// 内核数据目录中的 GEO 数据库
+const GEO_FILES: [&str; 5] = [
+    "Country.mmdb",
+    "geoip.dat",
+    "geosite.dat",
+    "GeoLite2-ASN.mmdb",
+    "ASN.mmdb",
+];
+
+/// 由内核更新GEO数据库，立即返回任务id
+/// 开始时发送 `operation::progress`，结束后通知更新了哪些文件
+/// 取消后不再等待结果，内核已经开始的下载会继续在后台完成
+pub fn update_geo_by_core(operation_id: Option<String>) -> String {
+    static UPDATING: AtomicBool = AtomicBool::new(false);
+
+    operation::spawn(operation_id, |id, token| async move {
+        if UPDATING.swap(true, Ordering::SeqCst) {
+            handle::Handle::notice_message("update_geo::error", clash_api::GEO_UPDATING);
+            bail!(clash_api::GEO_UPDATING);
+        }
+        operation::emit_progress(&id, "core_geo", 0, None);
+
+        let before = geo_file_mtimes();
+        let result = operation::cancellable(&token, clash_api::update_geo()).await;
+        UPDATING.store(false, Ordering::SeqCst);
+
+        match &result {
+            Ok(_) => {
+                let after = geo_file_mtimes();
+                let updated = after
+                    .iter()
+                    .filter(|(name, mtime)| before.get(*name) != Some(mtime))
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>();
+                log::info!(target: "app", "geo data updated by the core: {updated:?}");
+                handle::Handle::notice_message("update_geo::ok", updated.join(", "));
+            }
+            Err(err) if err.is::<operation::Cancelled>() => {
+                log::info!(target: "app", "stopped waiting for the core to update geo data");
+            }
+            Err(err) => handle::Handle::notice_message("update_geo::error", format!("{err}")),
+        }
+        result
+    })
+}
+
+fn geo_file_mtimes() -> std::collections::HashMap<&'static str, std::time::SystemTime> {
+    let Ok(home) = app_home_dir() else {
+        return Default::default();
+    };
+    GEO_FILES
+        .into_iter()
+        .filter_map(|name| {
+            let mtime = fs::metadata(home.join(name)).ok()?.modified().ok()?;
+            Some((name, mtime))
+        })
+        .collect()
+}
+
+// 切换代理的多路复用
+pub fn toggle_mux(proxy_name: Option<String>) {
+    tauri::async_runtime::spawn(async move {
+        log_err!(toggle_proxy_mux(proxy_name).await);
+    });
+}
+
+/// 切换当前订阅中代理的 `smux.enabled`，返回切换后的状态
+/// 不指定代理时切换全部代理，只要有一个未开启就全部开启
+pub async fn toggle_proxy_mux(proxy_name: Option<String>) -> Result<bool> {
+    let path = {
+        let profiles = Config::profiles();
+        let profiles = profiles.latest();
+        let current = profiles.get_current().context("no profile is selected")?;
+        let file = profiles.get_item(&current)?.file.clone();
+        dirs::app_profiles_dir()?.join(file.context("failed to get the file field")?)
+    };
+
+    let original = fs::read_to_string(&path)?;
+    let mut config = serde_yaml::from_str::<Mapping>(&original)?;
+    let proxies = config
+        .get_mut("proxies")
+        .and_then(Value::as_sequence_mut)
+        .context("profile does not contain `proxies`")?;
+
+    let mut targets = proxies
+        .iter_mut()
+        .filter_map(Value::as_mapping_mut)
+        .filter(|proxy| match proxy_name.as_deref() {
+            Some(name) => proxy.get("name").and_then(Value::as_str) == Some(name),
+            None => true,
+        })
+        .collect::<Vec<_>>();
+    if targets.is_empty() {
+        bail!("proxy \"{}\" not found", proxy_name.unwrap_or_default());
+    }
+
+    let enable = !targets.iter().all(|proxy| {
+        proxy
+            .get("smux")
+            .and_then(|smux| smux.get("enabled"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    });
+    for proxy in targets.iter_mut() {
+        let mut smux = proxy
+            .get("smux")
+            .and_then(Value::as_mapping)
+            .cloned()
+            .unwrap_or_default();
+        smux.insert("enabled".into(), enable.into());
+        proxy.insert("smux".into(), smux.into());
+    }
+
+    profile_watcher::ProfileWatcher::global().note_own_write(&path);
+    help::save_yaml(&path, &config, None)?;
+    let res = CoreManager::global().update_config().await;
+    if let Err(err) = res.and_then(|res| Ok(res?)) {
+        // 配置无效时恢复订阅文件
+        profile_watcher::ProfileWatcher::global().note_own_write(&path);
+        fs::write(&path, original)?;
+        bail!("failed to toggle mux: {err}");
+    }
+
+    handle::Handle::refresh_clash();
+    handle::Handle::emit_event(
+        "proxy::mux_toggled",
+        serde_json::json!({ "proxy_name": proxy_name, "enabled": enable }),
+    );
+    Ok(enable)
+}
+
+// 重新加载订阅的脚本
+pub fn reload_script(uid: Option<String>) {
+    tauri::async_runtime::spawn(async move {
+        match uid {
+            Some(uid) => log_err!(reload_profile_script(uid).await),
+            None => log::error!(target: "app", "reload_script requires a profile uid"),
+        }
+    });
+}
+
+/// 检查订阅关联的脚本，通过后重新加载配置
+/// `uid` 可以是订阅本身，也可以直接是脚本
+pub async fn reload_profile_script(uid: String) -> Result<()> {
+    let path = {
+        let profiles = Config::profiles();
+        let profiles = profiles.latest();
+        let item = profiles.get_item(&uid)?;
+        let item = match item.itype.as_deref() {
+            Some("script") => item,
+            _ => {
+                let script = item.option.as_ref().and_then(|o| o.script.clone());
+                profiles.get_item(&script.context("profile has no script")?)?
+            }
+        };
+        let file = item.file.clone().context("failed to get the file field")?;
+        dirs::app_profiles_dir()?.join(file)
+    };
+
+    let result = CoreManager::global()
+        .validate_config_file(dirs::path_to_str(&path)?)
+        .await?;
+    if let Err(error) = result {
+        handle::Handle::notice_error(error.notice_status(), &error);
+        bail!(error);
+    }
+
+    CoreManager::global().update_config().await??;
+    handle::Handle::refresh_clash();
+    handle::Handle::emit_event("profile::script_reloaded", serde_json::json!({ "uid": uid }));
+    Ok(())
+}
+
+// 导出诊断信息
+pub fn export_diagnostics() {
+    export_diagnostics_operation(None);
+}
+
+/// 在后台收集诊断信息并弹出保存对话框，立即返回任务id
+/// 任务结果为保存的路径，取消保存时为 null
+pub fn export_diagnostics_operation(operation_id: Option<String>) -> String {
+    operation::spawn(operation_id, |_id, token| async move {
+        export_diagnostics_dialog(&token).await
+    })
+}
+
+/// 收集诊断信息并弹出保存对话框，返回保存的路径，取消时返回 None
+async fn export_diagnostics_dialog(token: &CancellationToken) -> Result<Option<String>> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let app_handle = handle::Handle::global().app_handle().context("app handle is not ready")?;
+    handle::Handle::notice_message("export_diagnostics::progress", "");
+    let collected = operation::cancellable(token, CoreManager::global().collect_diagnostics());
+    let zip_path = match collected.await {
+        Ok(path) => path,
+        Err(err) => {
+            if !err.is::<operation::Cancelled>() {
+                handle::Handle::notice_message("export_diagnostics::error", format!("{err}"));
+            }
+            return Err(err);
+        }
+    };
+
+    let file_name = zip_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let target = tauri::async_runtime::spawn_blocking(move || {
+        app_handle
+            .dialog()
+            .file()
+            .set_file_name(file_name)
+            .add_filter("zip", &["zip"])
+            .blocking_save_file()
+    })
+    .await?;
+
+    let res = match target.map(|path| path.into_path()) {
+        Some(Ok(target)) => fs::copy(&zip_path, &target)
+            .map(|_| Some(target.to_string_lossy().to_string()))
+            .map_err(anyhow::Error::from),
+        Some(Err(err)) => Err(err.into()),
+        None => Ok(None),
+    };
+    let _ = fs::remove_file(&zip_path);
+
+    match &res {
+        Ok(Some(path)) => handle::Handle::notice_message("export_diagnostics::ok", path),
+        Ok(None) => {}
+        Err(err) => handle::Handle::notice_message("export_diagnostics::error", format!("{err}")),
+    }
+    res
+}
+
+// 切换模拟代理模式
+#[cfg(any(debug_assertions, feature = "mock-mode"))]
+pub fn toggle_mock_mode(port: Option<u16>) {
+    tauri::async_runtime::spawn(async move {
+        log_err!(mock::toggle_mock_mode(port).await);
+    });
+}
+
+/// 等待前端保存未提交修改的通道
+static PENDING_SAVE: parking_lot::Mutex<Option<oneshot::Sender<Result<(), String>>>> =
+    parking_lot::const_mutex(None);
+
+// 保存并重载配置
+pub fn save_and_reload() {
+    tauri::async_runtime::spawn(async {
+        log_err!(save_and_reload_config().await);
+    });
+}
+
+/// 通知前端保存未提交的修改，保存完成后重新加载配置
+pub async fn save_and_reload_config() -> Result<()> {
+    // 窗口未打开时没有需要保存的修改，也没有页面响应，直接重载
+    let res = match handle::Handle::global().get_window() {
+        Some(_) => request_pending_save().await,
+        None => Ok(()),
+    };
+    let res = match res {
+        Ok(()) => match CoreManager::global().update_config().await {
+            Ok(res) => res.map_err(anyhow::Error::from),
+            Err(err) => Err(err),
+        },
+        Err(err) => Err(err),
+    };
+
+    match res {
+        Ok(()) => {
+            handle::Handle::refresh_clash();
+            handle::Handle::notice_message("save_and_reload::ok", "ok");
+            Ok(())
+        }
+        Err(err) => {
+            handle::Handle::notice_message("save_and_reload::error", format!("{err}"));
+            Err(err)
+        }
+    }
+}
+
+/// 请求前端保存未提交的修改并等待结果
+/// 页面没有响应（如还在加载）时超时后继续重载
+async fn request_pending_save() -> Result<()> {
+    let (tx, rx) = oneshot::channel();
+    if let Some(old) = PENDING_SAVE.lock().replace(tx) {
+        let _ = old.send(Err("superseded by a new save request".into()));
+    }
+    handle::Handle::emit_event("config::request_save", ());
+
+    match tokio::time::timeout(std::time::Duration::from_secs(10), rx).await {
+        Ok(Ok(Err(err))) => Err(anyhow::anyhow!(err)),
+        Ok(_) => Ok(()),
+        Err(_) => {
+            PENDING_SAVE.lock().take();
+            log::warn!(target: "app", "no response to the save request, reload anyway");
+            Ok(())
+        }
+    }
+}
+
+/// 前端保存完成后调用
+pub fn complete_config_save(error: Option<String>) {
+    if let Some(tx) = PENDING_SAVE.lock().take() {
+        let _ = tx.send(error.map_or(Ok(()), Err));
+    }
+}
+
 pub fn quit(code: Option<i32>) {
     let app_handle = handle::Handle::global().app_handle().unwrap();
     handle::Handle::global().set_is_exiting();
@@ -269,6 +1069,9 @@ pub async fn patch_verge(patch: IVerge) -> Result<()> {
     let http_port = patch.verge_port;
     let enable_tray_speed = patch.enable_tray_speed;
     let enable_global_hotkey = patch.enable_global_hotkey;
+    let enable_in_app_hotkey = patch.enable_in_app_hotkey;
+    let controller_transport = patch.external_controller_transport;
+    let controller_secret = patch.external_controller_secret;
 
     let res: std::result::Result<(), anyhow::Error> = {
         let mut should_restart_core = false;
@@ -288,7 +1091,7 @@ pub async fn patch_verge(patch: IVerge) -> Result<()> {
             should_update_systray_tooltip = true;
             should_update_systray_icon = true;
         }
-        if enable_global_hotkey.is_some() {
+        if enable_global_hotkey.is_some() || enable_in_app_hotkey.is_some() {
             should_update_verge_config = true;
         }
         #[cfg(not(target_os = "windows"))]
@@ -307,6 +1110,14 @@ pub async fn patch_verge(patch: IVerge) -> Result<()> {
         {
             should_restart_core = true;
         }
+        // 本地控制接口需要重新生成配置并由内核重新监听
+        if controller_transport.is_some() {
+            should_restart_core = true;
+        }
+        // 重启时使用新的密钥
+        if controller_secret.is_some() {
+            should_restart_core = true;
+        }
         if auto_launch.is_some() {
             should_update_launch = true;
         }
@@ -342,6 +1153,10 @@ pub async fn patch_verge(patch: IVerge) -> Result<()> {
             should_update_systray_icon = true;
         }
 
+        if patch.enable_profile_hot_reload.is_some() {
+            profile_watcher::ProfileWatcher::global().refresh()?;
+        }
+
         if should_restart_core {
             CoreManager::global().restart_core().await?;
         }
@@ -350,7 +1165,11 @@ pub async fn patch_verge(patch: IVerge) -> Result<()> {
             handle::Handle::refresh_clash();
         }
         if should_update_verge_config {
-            Config::verge().draft().enable_global_hotkey = enable_global_hotkey;
+            if enable_global_hotkey.is_some() {
+                Config::verge().draft().enable_global_hotkey = enable_global_hotkey;
+            }
+            // 立即按新的设置注册或注销热键，不需要重启应用
+            hotkey::Hotkey::global().refresh_from_config();
             handle::Handle::refresh_verge();
         }
         if should_update_launch {
@@ -378,17 +1197,16 @@ pub async fn patch_verge(patch: IVerge) -> Result<()> {
         }
 
         // 处理轻量模式切换
-        if lite_mode.is_some() {
-            if let Some(window) = handle::Handle::global().get_window() {
-                if lite_mode.unwrap() {
-                    // 完全退出 webview 进程
-                    window.close()?;  // 先关闭窗口
-                    let app_handle = handle::Handle::global().app_handle().unwrap();
-                    if let Some(webview) = app_handle.get_webview_window("main") {
-                        webview.destroy()?;  // 销毁 webview 进程
-                    }
-                } else {
-                    resolve::create_window();  // 重新创建窗口
+        if let Some(lite_mode) = lite_mode {
+            if lite_mode {
+                if let Some(window) = handle::Handle::global().get_window() {
+                    destroy_window(window)?;
+                }
+            } else {
+                // 轻量模式下窗口已经销毁，需要重新创建
+                resolve::create_window();
+                if let Some(window) = handle::Handle::global().get_window() {
+                    let _ = window.restore_state(StateFlags::all());
                 }
             }
         }
@@ -469,14 +1287,31 @@ pub async fn update_profile(uid: String, option: Option<PrfOption>) -> Result<()
 }
 
 /// copy env variable
-pub fn copy_clash_env() {
+pub fn copy_clash_env() -> bool {
     // 从环境变量获取IP地址，默认127.0.0.1
     let clash_verge_rev_ip = env::var("CLASH_VERGE_REV_IP").unwrap_or_else(|_| "127.0.0.1".to_string());
     
     let app_handle = handle::Handle::global().app_handle().unwrap();
-    let port = { Config::verge().latest().verge_mixed_port.unwrap_or(7897) };
+    // 使用已经应用的运行时配置中的端口，切换订阅后也是准确的
+    let (mixed_port, socks_port) = {
+        let runtime = Config::runtime();
+        let runtime = runtime.data();
+        let port = |key: &str| {
+            runtime
+                .config
+                .as_ref()
+                .and_then(|config| config.get(key))
+                .and_then(Value::as_u64)
+                .and_then(|port| u16::try_from(port).ok())
+                .filter(|port| *port != 0)
+        };
+        (port("mixed-port"), port("socks-port"))
+    };
+    let port = mixed_port
+        .or(Config::verge().latest().verge_mixed_port)
+        .unwrap_or(7897);
     let http_proxy = format!("http://{clash_verge_rev_ip}:{}", port);
-    let socks5_proxy = format!("socks5://{clash_verge_rev_ip}:{}", port);
+    let socks5_proxy = format!("socks5://{clash_verge_rev_ip}:{}", socks_port.unwrap_or(port));
 
     let sh =
         format!("export https_proxy={http_proxy} http_proxy={http_proxy} all_proxy={socks5_proxy}");
@@ -498,13 +1333,38 @@ pub fn copy_clash_env() {
             default.to_string()
         }
     };
-    match env_type.as_str() {
-        "bash" => cliboard.write_text(sh).unwrap_or_default(),
-        "cmd" => cliboard.write_text(cmd).unwrap_or_default(),
-        "powershell" => cliboard.write_text(ps).unwrap_or_default(),
-        "nushell" => cliboard.write_text(nu).unwrap_or_default(),
-        _ => log::error!(target: "app", "copy_clash_env: Invalid env type! {env_type}"),
+    let text = match env_type.as_str() {
+        "bash" => sh,
+        "cmd" => cmd,
+        "powershell" => ps,
+        "nushell" => nu,
+        _ => {
+            log::error!(target: "app", "copy_clash_env: Invalid env type! {env_type}");
+            return false;
+        }
     };
+    match cliboard.write_text(text) {
+        Ok(_) => true,
+        Err(err) => {
+            log::error!(target: "app", "copy_clash_env: {err}");
+            false
+        }
+    }
+}
+
+// 复制代理环境变量并通知，供没有打开窗口时的快捷键使用
+pub fn copy_proxy_env() {
+    if copy_clash_env() {
+        handle::Handle::notice_message("copy_env::ok", "");
+    }
+}
+
+/// 快捷键打开应用目录或内核目录，失败时（没有可用的打开方式）记录日志并提示
+pub fn reveal_dir(open: fn() -> Result<(), String>) {
+    if let Err(err) = open() {
+        log::error!(target: "app", "failed to open directory: {err}");
+        handle::Handle::notice_message("open_dir::error", err);
+    }
 }
 
 pub async fn test_delay(url: String) -> Result<u32> {
@@ -554,8 +1414,8 @@ pub async fn test_delay(url: String) -> Result<u32> {
     }
 }
 
-pub async fn create_backup_and_upload_webdav() -> Result<()> {
-    let (file_name, temp_file_path) = backup::create_backup().map_err(|err| {
+pub async fn create_backup_and_upload_webdav(include_tls_options: bool) -> Result<()> {
+    let (file_name, temp_file_path) = backup::create_backup(include_tls_options).map_err(|err| {
         log::error!(target: "app", "Failed to create backup: {:#?}", err);
         err
     })?;
@@ -625,3 +1485,139 @@ pub async fn restore_webdav_backup(filename: String) -> Result<()> {
     fs::remove_file(backup_storage_path)?;
     Ok(())
 }
+
+/// 超过这个值的到期时间按毫秒处理，秒级时间戳要到 5138 年才会超过
+const EXPIRE_MILLIS_THRESHOLD: u64 = 100_000_000_000;
+
+/// 订阅的流量和到期信息，来自当前订阅或内核中的 proxy provider
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionUsage {
+    /// `profile` 或 `provider`
+    pub source: &'static str,
+    pub name: String,
+    pub upload: u64,
+    pub download: u64,
+    pub total: u64,
+    /// 剩余流量，没有提供总量时为 None
+    pub remaining: Option<u64>,
+    /// 到期时间的秒级时间戳，不过期时为 None
+    pub expire: Option<u64>,
+}
+
+impl SubscriptionUsage {
+    /// 全为 0 时视为没有提供订阅信息
+    fn new(source: &'static str, name: String, info: clash_api::SubscriptionInfo) -> Option<Self> {
+        if info.is_empty() {
+            return None;
+        }
+        let used = info.upload.saturating_add(info.download);
+        let expire = match info.expire {
+            0 => None,
+            expire if expire >= EXPIRE_MILLIS_THRESHOLD => Some(expire / 1000),
+            expire => Some(expire),
+        };
+        Some(Self {
+            source,
+            name,
+            upload: info.upload,
+            download: info.download,
+            total: info.total,
+            remaining: (info.total > 0).then(|| info.total.saturating_sub(used)),
+            expire,
+        })
+    }
+}
+
+/// 汇总当前订阅和所有 proxy provider 的流量信息
+/// 订阅以 provider 的形式写在本地配置中时，只能从内核返回的 provider 中拿到
+/// 内核未运行时只返回当前订阅的
+pub async fn get_subscription_usage() -> Result<Vec<SubscriptionUsage>> {
+    let current = {
+        let profiles = Config::profiles();
+        let profiles = profiles.latest();
+        profiles
+            .get_current()
+            .and_then(|uid| profiles.get_item(&uid).ok().cloned())
+    };
+
+    let mut usage = Vec::new();
+    if let Some(PrfItem {
+        uid,
+        name,
+        extra: Some(extra),
+        ..
+    }) = current
+    {
+        let info = clash_api::SubscriptionInfo {
+            upload: extra.upload,
+            download: extra.download,
+            total: extra.total,
+            expire: extra.expire,
+        };
+        let name = name.or(uid).unwrap_or_default();
+        usage.extend(SubscriptionUsage::new("profile", name, info));
+    }
+
+    match clash_api::get_proxy_providers().await {
+        Ok(providers) => usage.extend(providers.into_iter().filter_map(|provider| {
+            let info = provider.subscription_info?;
+            SubscriptionUsage::new("provider", provider.name, info)
+        })),
+        Err(err) => log::warn!(target: "app", "failed to get provider subscription info: {err}"),
+    }
+    Ok(usage)
+}
+
+#[test]
+fn test_next_clash_mode() {
+    assert_eq!(next_clash_mode("rule"), "global");
+    assert_eq!(next_clash_mode("global"), "direct");
+    assert_eq!(next_clash_mode("direct"), "rule");
+    assert_eq!(next_clash_mode("script"), "rule");
+}
+
+#[test]
+fn test_check_runtime_option() {
+    assert!(check_runtime_option("allow-lan", &Value::from(true)).is_ok());
+    assert!(check_runtime_option("log-level", &Value::from("warning")).is_ok());
+    assert!(check_runtime_option("mode", &Value::from("global")).is_ok());
+
+    assert!(check_runtime_option("ipv6", &Value::from("true")).is_err());
+    assert!(check_runtime_option("log-level", &Value::from("verbose")).is_err());
+    assert!(check_runtime_option("mode", &Value::from(1)).is_err());
+    assert!(check_runtime_option("mixed-port", &Value::from(7897)).is_err());
+}
+
+#[test]
+fn test_subscription_usage() {
+    let info = clash_api::SubscriptionInfo {
+        upload: 100,
+        download: 200,
+        total: 1000,
+        expire: 1_700_000_000_000,
+    };
+    let usage = SubscriptionUsage::new("provider", "sub".into(), info).unwrap();
+    assert_eq!(usage.remaining, Some(700));
+    assert_eq!(usage.expire, Some(1_700_000_000));
+
+    let info = clash_api::SubscriptionInfo {
+        upload: 600,
+        download: 600,
+        total: 1000,
+        expire: 0,
+    };
+    let usage = SubscriptionUsage::new("profile", "sub".into(), info).unwrap();
+    assert_eq!(usage.remaining, Some(0));
+    assert_eq!(usage.expire, None);
+
+    let info = clash_api::SubscriptionInfo {
+        download: 1,
+        ..Default::default()
+    };
+    let usage = SubscriptionUsage::new("provider", "sub".into(), info).unwrap();
+    assert_eq!(usage.remaining, None);
+
+    let empty = clash_api::SubscriptionInfo::default();
+    assert!(SubscriptionUsage::new("provider", "sub".into(), empty).is_none());
+}
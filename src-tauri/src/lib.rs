@@ -84,6 +84,9 @@ pub fn run() {
             cmds::get_clash_info,
             cmds::patch_clash_config,
             cmds::patch_clash_mode,
+            cmds::patch_clash_runtime_option,
+            cmds::compare_core_validation,
+            cmds::validate_chain,
             cmds::change_clash_core,
             cmds::get_runtime_config,
             cmds::get_runtime_yaml,
@@ -101,6 +104,7 @@ pub fn run() {
             cmds::open_devtools,
             cmds::exit_app,
             cmds::get_network_interfaces_info,
+            cmds::get_tun_stats,
             // profile
             cmds::get_profiles,
             cmds::enhance_profiles,
@@ -109,6 +113,7 @@ pub fn run() {
             cmds::patch_profile,
             cmds::create_profile,
             cmds::import_profile,
+            cmds::import_config_base64,
             cmds::reorder_profile,
             cmds::update_profile,
             cmds::delete_profile,
@@ -117,8 +122,69 @@ pub fn run() {
             // script validation
             cmds::script_validate_notice,
             cmds::validate_script_file,
+            cmds::reload_profile_script,
+            cmds::validate_dns_config,
+            cmds::start_certificate_watch,
+            cmds::stop_certificate_watch,
             // clash api
-            cmds::clash_api_get_proxy_delay,
+            cmds::select_proxy,
+            cmds::test_proxy_delay,
+            cmds::test_group_delay,
+            cmds::get_proxies,
+            cmds::get_providers_proxies,
+            cmds::get_proxy_providers,
+            cmds::get_subscription_usage,
+            cmds::update_proxy_provider,
+            cmds::healthcheck_proxy_provider,
+            cmds::update_all_proxy_providers,
+            cmds::get_clash_version,
+            cmds::get_clash_configs,
+            cmds::is_core_debug_enabled,
+            cmds::core_gc,
+            cmds::get_rule_providers,
+            cmds::update_rule_provider,
+            cmds::update_all_rule_providers,
+            cmds::start_core_logs,
+            cmds::stop_core_logs,
+            cmds::get_subscription_state,
+            cmds::start_memory_usage,
+            cmds::stop_memory_usage,
+            cmds::dns_query,
+            cmds::flush_fakeip,
+            cmds::get_rules,
+            cmds::get_rules_for_proxy,
+            cmds::start_connection_recording,
+            cmds::stop_connection_recording,
+            cmds::replay_connections,
+            cmds::force_reconnect_all_connections,
+            cmds::clear_connections,
+            cmds::close_connection,
+            cmds::get_connections,
+            cmds::toggle_ipv6_routing,
+            cmds::toggle_proxy_mux,
+            cmds::toggle_mock_proxy_mode,
+            cmds::set_upstream_proxy,
+            cmds::check_connectivity_now,
+            cmds::validate_subscription_yaml,
+            cmds::rotate_core_log,
+            cmds::get_core_state,
+            cmds::validate_config_detailed,
+            cmds::get_core_version,
+            cmds::get_core_status,
+            cmds::upgrade_core,
+            cmds::get_core_usage,
+            cmds::get_core_history,
+            cmds::get_hotkey_status,
+            cmds::validate_hotkey,
+            cmds::suspend_hotkeys,
+            cmds::resume_hotkeys,
+            cmds::save_and_reload_config,
+            cmds::export_diagnostics_dialog,
+            cmds::complete_config_save,
+            // long-running operations
+            cmds::update_geo_data,
+            cmds::update_geo_by_core,
+            cmds::cancel_operation,
             // backup
             cmds::create_webdav_backup,
             cmds::save_webdav_config,
@@ -164,10 +230,10 @@ pub fn run() {
                         {
                             log_err!(hotkey::Hotkey::global().register("Control+Q", "quit"));
                         };
-                        {   
+                        {
                             let is_enable_global_hotkey = Config::verge().latest().enable_global_hotkey.unwrap_or(true);
                             if !is_enable_global_hotkey {
-                                log_err!(hotkey::Hotkey::global().init())
+                                hotkey::Hotkey::global().enable();
                             }
                         }
                     }
@@ -180,14 +246,16 @@ pub fn run() {
                         {
                             log_err!(hotkey::Hotkey::global().unregister("Control+Q"));
                         };
-                        {   
+                        {
                             let is_enable_global_hotkey = Config::verge().latest().enable_global_hotkey.unwrap_or(true);
                             if !is_enable_global_hotkey {
-                                log_err!(hotkey::Hotkey::global().reset())
+                                hotkey::Hotkey::global().disable();
                             }
                         }
                     }
                     tauri::WindowEvent::Destroyed => {
+                        // 前端已经销毁，不会再恢复暂停的热键
+                        hotkey::Hotkey::global().resume_all();
                         #[cfg(target_os = "macos")]
                         {
                             log_err!(hotkey::Hotkey::global().unregister("CMD+Q"));
@@ -3,7 +3,11 @@ use anyhow::{anyhow, bail, Context, Result};
 use nanoid::nanoid;
 use serde::{de::DeserializeOwned, Serialize};
 use serde_yaml::{Mapping, Value};
-use std::{fs, path::PathBuf, str::FromStr};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 /// read data from yaml as struct T
 pub fn read_yaml<T: DeserializeOwned>(path: &PathBuf) -> Result<T> {
@@ -223,6 +227,67 @@ pub fn format_bytes_speed(speed: u64) -> String {
     }
 }
 
+/// 解码base64，兼容标准和URL安全两种字符集，填充可有可无
+/// 会忽略其中的空白字符，方便直接粘贴论坛中换行的内容
+pub fn decode_base64(data: &str) -> Result<Vec<u8>> {
+    use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+    use base64::Engine;
+
+    let data = data.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+    [STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD]
+        .iter()
+        .find_map(|engine| engine.decode(&data).ok())
+        .context("invalid base64 string")
+}
+
+/// 目标文件不存在或比源文件旧时复制，返回是否复制
+/// 复制后把目标文件的修改时间设为源文件的修改时间，之后按源文件本身的时间比较，而不是复制的时间
+pub fn copy_if_newer(src: &Path, dest: &Path) -> Result<bool> {
+    let src_modified = fs::metadata(src)?.modified()?;
+    let dest_modified = fs::metadata(dest).and_then(|meta| meta.modified());
+    if dest_modified.is_ok_and(|dest_modified| dest_modified >= src_modified) {
+        return Ok(false);
+    }
+    fs::copy(src, dest)?;
+    fs::File::options()
+        .write(true)
+        .open(dest)?
+        .set_modified(src_modified)?;
+    Ok(true)
+}
+
+/// 写入zip文件，写入过程中出错或被中断时删除未完成的文件
+pub fn write_zip(
+    path: &Path,
+    write: impl FnOnce(&mut zip::ZipWriter<fs::File>) -> Result<()>,
+) -> Result<()> {
+    struct RemoveOnDrop<'a>(Option<&'a Path>);
+    impl Drop for RemoveOnDrop<'_> {
+        fn drop(&mut self) {
+            if let Some(path) = self.0 {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+
+    let mut zip = zip::ZipWriter::new(fs::File::create(path)?);
+    let mut guard = RemoveOnDrop(Some(path));
+    write(&mut zip)?;
+    zip.finish()?;
+    guard.0 = None;
+    Ok(())
+}
+
+#[test]
+fn test_decode_base64() {
+    // "mode: rule\n" 以及带 `?>` 的内容分别覆盖标准和URL安全字符集
+    assert_eq!(decode_base64("bW9kZTogcnVsZQo=").unwrap(), b"mode: rule\n");
+    assert_eq!(decode_base64("bW9kZTog\ncnVsZQo").unwrap(), b"mode: rule\n");
+    assert_eq!(decode_base64("Pz8-").unwrap(), b"??>");
+    assert_eq!(decode_base64("Pz8+").unwrap(), b"??>");
+    assert!(decode_base64("not base64!").is_err());
+}
+
 #[cfg(target_os = "macos")]
 #[test]
 fn test_format_bytes_speed() {
@@ -234,3 +299,64 @@ fn test_format_bytes_speed() {
     assert_eq!(format_bytes_speed(1024 * 500), "500.0KB/s");
     assert_eq!(format_bytes_speed(1024 * 1024 * 2), "2.0MB/s");
 }
+
+#[test]
+fn test_copy_if_newer() {
+    use std::time::{Duration, SystemTime};
+
+    let dir = std::env::temp_dir().join(get_uid("copy-if-newer"));
+    fs::create_dir_all(&dir).unwrap();
+    let (src, dest) = (dir.join("src.dat"), dir.join("dest.dat"));
+    let set_modified = |path: &Path, time: SystemTime| {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(time).unwrap();
+    };
+    let old = SystemTime::now() - Duration::from_secs(3600);
+
+    // 目标不存在时复制，复制后沿用源文件的修改时间
+    fs::write(&src, "v1").unwrap();
+    set_modified(&src, old);
+    assert!(copy_if_newer(&src, &dest).unwrap());
+    assert_eq!(fs::metadata(&dest).unwrap().modified().unwrap(), old);
+    assert!(!copy_if_newer(&src, &dest).unwrap());
+
+    // 源文件更新后再次复制
+    fs::write(&src, "v2").unwrap();
+    assert!(copy_if_newer(&src, &dest).unwrap());
+    assert_eq!(fs::read_to_string(&dest).unwrap(), "v2");
+
+    // 目标文件比源文件新时保留目标文件，如内核更新过的 geo 数据
+    fs::write(&dest, "updated").unwrap();
+    set_modified(&src, old);
+    assert!(!copy_if_newer(&src, &dest).unwrap());
+    assert_eq!(fs::read_to_string(&dest).unwrap(), "updated");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_write_zip_removes_partial_file() {
+    use std::io::Write;
+
+    let dir = std::env::temp_dir().join(get_uid("write-zip"));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("archive.zip");
+
+    let result = write_zip(&path, |zip| {
+        zip.start_file("a.txt", zip::write::SimpleFileOptions::default())?;
+        zip.write_all(b"partial")?;
+        bail!("forced error")
+    });
+    assert!(result.is_err());
+    assert!(!path.exists());
+
+    write_zip(&path, |zip| {
+        zip.start_file("a.txt", zip::write::SimpleFileOptions::default())?;
+        zip.write_all(b"done")?;
+        Ok(())
+    })
+    .unwrap();
+    assert!(path.exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
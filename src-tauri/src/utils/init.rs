@@ -212,8 +212,11 @@ pub fn init_resources() -> Result<()> {
             };
         };
 
-        if src_path.exists() && !test_dest_path.exists() {
-            handle_copy(&test_dest_path);
+        // 验证目录的数据只从内置资源复制，应用更新后内置资源较新时同样复制
+        if src_path.exists() {
+            if let Err(err) = help::copy_if_newer(&src_path, &test_dest_path) {
+                log::error!(target: "app", "failed to copy resources '{file}' to test dir, {err}");
+            }
         }
         if src_path.exists() && !dest_path.exists() {
             handle_copy(&dest_path);
@@ -1,3 +1,4 @@
+pub mod cert;
 pub mod dirs;
 pub mod error;
 pub mod help;
@@ -109,6 +109,7 @@ pub async fn resolve_setup(app: &mut App) {
 
     log_err!(tray::Tray::global().update_part());
     log_err!(timer::Timer::global().init());
+    log_err!(profile_watcher::ProfileWatcher::global().refresh());
 }
 
 /// reset system proxy
@@ -116,6 +117,8 @@ pub fn resolve_reset() {
     tauri::async_runtime::block_on(async move {
         #[cfg(target_os = "macos")]
         tray::Tray::global().unsubscribe_traffic();
+        clash_api::stop_traffic_subscription();
+        clash_api::stop_core_log_subscription();
 
         log_err!(sysopt::Sysopt::global().reset_sysproxy().await);
         log_err!(CoreManager::global().stop_core().await);
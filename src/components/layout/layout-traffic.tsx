@@ -9,11 +9,13 @@ import { useClashInfo } from "@/hooks/use-clash";
 import { useVerge } from "@/hooks/use-verge";
 import { TrafficGraph, type TrafficRef } from "./traffic-graph";
 import { useVisibility } from "@/hooks/use-visibility";
+import { useSubscriptionState } from "@/hooks/use-subscription-state";
 import parseTraffic from "@/utils/parse-traffic";
 import useSWRSubscription from "swr/subscription";
-import { createSockette } from "@/utils/websocket";
 import { useTranslation } from "react-i18next";
 import { isDebugEnabled, gc } from "@/services/api";
+import { startMemoryUsage, stopMemoryUsage } from "@/services/cmds";
+import { listen } from "@tauri-apps/api/event";
 
 interface MemoryUsage {
   inuse: number;
@@ -45,25 +47,17 @@ export const LayoutTraffic = () => {
   >(
     clashInfo && pageVisible ? "getRealtimeTraffic" : null,
     (_key, { next }) => {
-      const { server = "", secret = "" } = clashInfo!;
-
-      const s = createSockette(
-        `ws://${server}${secret ? `/traffic?token=${encodeURIComponent(secret)}` : "/traffic"}`,
-        {
-          onmessage(event) {
-            const data = JSON.parse(event.data) as ITrafficItem;
-            trafficRef.current?.appendData(data);
-            next(null, data);
-          },
-          onerror(event) {
-            this.close();
-            next(event, { up: 0, down: 0 });
-          },
+      // 后端订阅内核的 /traffic 后转发，本地连接时前端无法直接连接内核
+      const unlisten = listen<ITrafficItem>(
+        "verge://traffic",
+        ({ payload }) => {
+          trafficRef.current?.appendData(payload);
+          next(null, payload);
         },
       );
 
       return () => {
-        s.close();
+        unlisten.then((fn) => fn());
       };
     },
     {
@@ -76,6 +70,9 @@ export const LayoutTraffic = () => {
 
   const displayMemory = verge?.enable_memory_usage ?? true;
 
+  // 旧版内核没有 /memory 接口，后端检测到后隐藏内存占用
+  const [memoryUnsupported, setMemoryUnsupported] = useState(false);
+
   const { data: memory = { inuse: 0 } } = useSWRSubscription<
     MemoryUsage,
     any,
@@ -83,24 +80,20 @@ export const LayoutTraffic = () => {
   >(
     clashInfo && pageVisible && displayMemory ? "getRealtimeMemory" : null,
     (_key, { next }) => {
-      const { server = "", secret = "" } = clashInfo!;
-
-      const s = createSockette(
-        `ws://${server}${secret ? `/memory?token=${encodeURIComponent(secret)}` : "/memory"}`,
-        {
-          onmessage(event) {
-            const data = JSON.parse(event.data) as MemoryUsage;
-            next(null, data);
-          },
-          onerror(event) {
-            this.close();
-            next(event, { inuse: 0 });
-          },
-        },
+      const unlisten = listen<MemoryUsage>("verge://memory", ({ payload }) =>
+        next(null, payload),
       );
+      const unlistenUnsupported = listen("verge://memory-unsupported", () =>
+        setMemoryUnsupported(true),
+      );
+      startMemoryUsage()
+        .then((supported) => setMemoryUnsupported(!supported))
+        .catch(() => {});
 
       return () => {
-        s.close();
+        stopMemoryUsage().catch(() => {});
+        unlisten.then((fn) => fn());
+        unlistenUnsupported.then((fn) => fn());
       };
     },
     {
@@ -109,6 +102,10 @@ export const LayoutTraffic = () => {
     },
   );
 
+  // 内核重启等断开期间不显示上一次的内存占用
+  const memoryState = useSubscriptionState("memory", displayMemory);
+  const memoryStale = memoryState !== null && memoryState !== "connected";
+
   const [up, upUnit] = parseTraffic(traffic.up);
   const [down, downUnit] = parseTraffic(traffic.down);
   const [inuse, inuseUnit] = parseTraffic(memory.inuse);
@@ -168,7 +165,7 @@ export const LayoutTraffic = () => {
           <Typography {...unitStyle}>{downUnit}/s</Typography>
         </Box>
 
-        {displayMemory && (
+        {displayMemory && !memoryUnsupported && (
           <Box
             title={t(isDebug ? "Memory Cleanup" : "Memory Usage")}
             {...boxStyle}
@@ -179,8 +176,10 @@ export const LayoutTraffic = () => {
             }}
           >
             <MemoryRounded {...iconStyle} />
-            <Typography {...valStyle}>{inuse}</Typography>
-            <Typography {...unitStyle}>{inuseUnit}</Typography>
+            <Typography {...valStyle}>{memoryStale ? "-" : inuse}</Typography>
+            <Typography {...unitStyle}>
+              {memoryStale ? "" : inuseUnit}
+            </Typography>
           </Box>
         )}
       </Box>
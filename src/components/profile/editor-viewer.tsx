@@ -16,6 +16,8 @@ import {
   CloseFullscreenRounded,
 } from "@mui/icons-material";
 import { useThemeMode } from "@/services/states";
+import { registerPendingSave } from "@/services/pending-save";
+import { resumeHotkeys, suspendHotkeys } from "@/services/cmds";
 import { Notice } from "@/components/base";
 import { nanoid } from "nanoid";
 import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
@@ -100,6 +102,14 @@ export const EditorViewer = <T extends Language>(props: Props<T>) => {
   const editorRef = useRef<monaco.editor.IStandaloneCodeEditor>();
   const prevData = useRef<string | undefined>("");
   const currData = useRef<string | undefined>("");
+  // 编辑器获得焦点时暂停热键，避免输入时触发
+  const hotkeysSuspended = useRef(false);
+
+  const setHotkeysSuspended = (suspended: boolean) => {
+    if (hotkeysSuspended.current === suspended) return;
+    hotkeysSuspended.current = suspended;
+    (suspended ? suspendHotkeys() : resumeHotkeys()).catch(() => {});
+  };
 
   const editorWillMount = () => {
     monacoInitialization(); // initialize monaco
@@ -109,6 +119,8 @@ export const EditorViewer = <T extends Language>(props: Props<T>) => {
     editor: monaco.editor.IStandaloneCodeEditor,
   ) => {
     editorRef.current = editor;
+    editor.onDidFocusEditorText(() => setHotkeysSuspended(true));
+    editor.onDidBlurEditorText(() => setHotkeysSuspended(false));
 
     // retrieve initial data
     await initialData.then((data) => {
@@ -148,6 +160,16 @@ export const EditorViewer = <T extends Language>(props: Props<T>) => {
     }
   });
 
+  // 快捷键 save_and_reload 触发时保存当前修改
+  useEffect(() => {
+    if (!open || readOnly) return;
+    return registerPendingSave(async () => {
+      if (currData.current === prevData.current) return;
+      await onSave?.(prevData.current, currData.current);
+      prevData.current = currData.current;
+    });
+  }, [open, readOnly, onSave]);
+
   const editorResize = debounce(() => {
     editorRef.current?.layout();
     setTimeout(() => editorRef.current?.layout(), 500);
@@ -164,6 +186,7 @@ export const EditorViewer = <T extends Language>(props: Props<T>) => {
 
     return () => {
       unlistenResized.then((fn) => fn());
+      setHotkeysSuspended(false);
       editorRef.current?.dispose();
       editorRef.current = undefined;
     };
@@ -46,11 +46,21 @@ interface Props {
   onEdit: () => void;
   onSave?: (prev?: string, curr?: string) => void;
   onDelete: () => void;
+  /// 链验证失败时的错误描述
+  chainError?: string;
 }
 
 export const ProfileItem = (props: Props) => {
-  const { selected, activating, itemData, onSelect, onEdit, onSave, onDelete } =
-    props;
+  const {
+    selected,
+    activating,
+    itemData,
+    onSelect,
+    onEdit,
+    onSave,
+    onDelete,
+    chainError,
+  } = props;
   const {
     attributes,
     listeners,
@@ -67,6 +77,10 @@ export const ProfileItem = (props: Props) => {
   const setLoadingCache = useSetLoadingCache();
 
   const { uid, name = "Profile", extra, updated = 0, option } = itemData;
+  // 跳过证书校验只有经过确认才生效
+  const insecureTls =
+    (option?.insecure_skip_verify || option?.danger_accept_invalid_certs) &&
+    option?.insecure_skip_verify_confirmed;
 
   // local file mode
   // remote file mode
@@ -314,6 +328,12 @@ export const ProfileItem = (props: Props) => {
     >
       <ProfileBox
         aria-selected={selected}
+        title={chainError}
+        sx={
+          chainError
+            ? (theme) => ({ outline: `1px solid ${theme.palette.error.main}` })
+            : undefined
+        }
         onClick={() => onSelect(false)}
         onContextMenu={(event) => {
           const { clientX, clientY } = event;
@@ -361,6 +381,7 @@ export const ProfileItem = (props: Props) => {
             <Typography
               width="calc(100% - 36px)"
               sx={{ fontSize: "18px", fontWeight: "600", lineHeight: "26px" }}
+              color={chainError ? "error" : undefined}
               variant="h6"
               component="h2"
               noWrap
@@ -368,6 +389,18 @@ export const ProfileItem = (props: Props) => {
             >
               {name}
             </Typography>
+
+            {insecureTls && (
+              <Typography
+                noWrap
+                flexShrink={0}
+                fontSize={12}
+                color="error"
+                title={t("Insecure TLS")}
+              >
+                {t("Insecure TLS")}
+              </Typography>
+            )}
           </Box>
 
           {/* only if has url can it be updated */}
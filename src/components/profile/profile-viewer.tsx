@@ -75,6 +75,7 @@ export const ProfileViewer = forwardRef<ProfileViewerRef, Props>(
 
     const selfProxy = watch("option.self_proxy");
     const withProxy = watch("option.with_proxy");
+    const skipVerifyConfirmed = watch("option.insecure_skip_verify_confirmed");
 
     useEffect(() => {
       if (selfProxy) formIns.setValue("option.with_proxy", false);
@@ -101,6 +102,17 @@ export const ProfileViewer = forwardRef<ProfileViewerRef, Props>(
           if (form.option?.user_agent === "") {
             delete form.option.user_agent;
           }
+          for (const key of [
+            "custom_ca_pem",
+            "client_cert",
+            "client_key",
+          ] as const) {
+            if (form.option?.[key]?.trim() === "") delete form.option[key];
+          }
+          // 未经确认的跳过证书校验不会生效，保存时关闭
+          if (form.option && !form.option.insecure_skip_verify_confirmed) {
+            form.option.danger_accept_invalid_certs = false;
+          }
           const name = form.name || `${form.type} file`;
           const item = { ...form, name };
 
@@ -213,6 +225,51 @@ export const ProfileViewer = forwardRef<ProfileViewerRef, Props>(
                 />
               )}
             />
+
+            <Controller
+              name="option.custom_ca_pem"
+              control={control}
+              render={({ field }) => (
+                <TextField
+                  {...text}
+                  {...field}
+                  multiline
+                  maxRows={4}
+                  placeholder={t("File Path or PEM")}
+                  label={t("Custom CA")}
+                />
+              )}
+            />
+
+            <Controller
+              name="option.client_cert"
+              control={control}
+              render={({ field }) => (
+                <TextField
+                  {...text}
+                  {...field}
+                  multiline
+                  maxRows={4}
+                  placeholder={t("File Path or PEM")}
+                  label={t("Client Certificate")}
+                />
+              )}
+            />
+
+            <Controller
+              name="option.client_key"
+              control={control}
+              render={({ field }) => (
+                <TextField
+                  {...text}
+                  {...field}
+                  multiline
+                  maxRows={4}
+                  placeholder={t("File Path or PEM")}
+                  label={t("Client Private Key")}
+                />
+              )}
+            />
           </>
         )}
 
@@ -275,7 +332,21 @@ export const ProfileViewer = forwardRef<ProfileViewerRef, Props>(
               render={({ field }) => (
                 <StyledBox>
                   <InputLabel>{t("Accept Invalid Certs (Danger)")}</InputLabel>
-                  <Switch checked={field.value} {...field} color="primary" />
+                  <Switch
+                    {...field}
+                    checked={!!field.value && !!skipVerifyConfirmed}
+                    onChange={async (_, checked) => {
+                      // 开启前需要确认，确认后才会跳过证书校验
+                      const message = t("Accept Invalid Certs Confirm");
+                      if (checked && !(await window.confirm(message))) return;
+                      field.onChange(checked);
+                      formIns.setValue(
+                        "option.insecure_skip_verify_confirmed",
+                        checked,
+                      );
+                    }}
+                    color="primary"
+                  />
                 </StyledBox>
               )}
             />
@@ -17,7 +17,11 @@ import {
 } from "@mui/material";
 import { RefreshRounded } from "@mui/icons-material";
 import { useTranslation } from "react-i18next";
-import { getProxyProviders, proxyProviderUpdate } from "@/services/api";
+import {
+  getProxies,
+  getProxyProviders,
+  proxyProviderUpdate,
+} from "@/services/api";
 import { BaseDialog } from "../base";
 import parseTraffic from "@/utils/parse-traffic";
 
@@ -28,7 +32,7 @@ const round = keyframes`
 
 export const ProviderButton = () => {
   const { t } = useTranslation();
-  const { data } = useSWR("getProxyProviders", getProxyProviders);
+  const { data } = useSWR("getProxyProviders", () => getProxyProviders());
 
   const [open, setOpen] = useState(false);
 
@@ -44,18 +48,21 @@ export const ProviderButton = () => {
       return next;
     });
   };
+  // 直接请求内核更新，后端的缓存不会失效，需要重新获取
+  const refresh = async () => {
+    await mutate("getProxies", getProxies(true));
+    await mutate("getProxyProviders", getProxyProviders(true));
+  };
   const handleUpdate = async (key: string, index: number) => {
     setUpdatingAt(true, index);
     proxyProviderUpdate(key)
       .then(async () => {
         setUpdatingAt(false, index);
-        await mutate("getProxies");
-        await mutate("getProxyProviders");
+        await refresh();
       })
       .catch(async () => {
         setUpdatingAt(false, index);
-        await mutate("getProxies");
-        await mutate("getProxyProviders");
+        await refresh();
       });
   };
 
@@ -1,13 +1,8 @@
 import { useRef, useState, useEffect, useCallback, useMemo } from "react";
 import { useLockFn } from "ahooks";
 import { Virtuoso, type VirtuosoHandle } from "react-virtuoso";
-import {
-  getConnections,
-  providerHealthCheck,
-  updateProxy,
-  deleteConnection,
-  getGroupProxyDelays,
-} from "@/services/api";
+import { providerHealthCheck } from "@/services/api";
+import { selectProxy } from "@/services/cmds";
 import { useProfiles } from "@/hooks/use-profiles";
 import { useVerge } from "@/hooks/use-verge";
 import { BaseEmpty } from "../base";
@@ -195,7 +190,7 @@ export const ProxyGroups = (props: Props) => {
   const { renderList, onProxies, onHeadState } = useRenderList(mode);
 
   const { verge } = useVerge();
-  const { current, patchCurrent } = useProfiles();
+  const { mutateProfiles } = useProfiles();
   const timeout = verge?.default_latency_timeout || 10000;
 
   const virtuosoRef = useRef<VirtuosoHandle>(null);
@@ -327,35 +322,10 @@ export const ProxyGroups = (props: Props) => {
     async (group: IProxyGroupItem, proxy: IProxyItem) => {
       if (!["Selector", "URLTest", "Fallback"].includes(group.type)) return;
 
-      const { name, now } = group;
-      await updateProxy(name, proxy.name);
+      // 由后端切换并保存到当前订阅的selected中，同时按设置断开连接
+      await selectProxy(group.name, proxy.name);
       onProxies();
-
-      // 断开连接
-      if (verge?.auto_close_connection) {
-        getConnections().then(({ connections }) => {
-          connections.forEach((conn) => {
-            if (conn.chains.includes(now!)) {
-              deleteConnection(conn.id);
-            }
-          });
-        });
-      }
-
-      // 保存到selected中
-      if (!current) return;
-      if (!current.selected) current.selected = [];
-
-      const index = current.selected.findIndex(
-        (item) => item.name === group.name,
-      );
-
-      if (index < 0) {
-        current.selected.push({ name, now: proxy.name });
-      } else {
-        current.selected[index] = { name, now: proxy.name };
-      }
-      await patchCurrent({ selected: current.selected });
+      mutateProfiles();
     },
   );
 
@@ -373,17 +343,15 @@ export const ProxyGroups = (props: Props) => {
     if (providers.size) {
       Promise.allSettled(
         [...providers].map((p) => providerHealthCheck(p)),
-      ).then(() => onProxies());
+      ).then(() => onProxies(true));
     }
 
     const names = proxies.filter((p) => !p!.provider).map((p) => p!.name);
 
-    await Promise.race([
-      delayManager.checkListDelay(names, groupName, timeout),
-      getGroupProxyDelays(groupName, delayManager.getUrl(groupName), timeout), // 查询group delays 将清除fixed(不关注调用结果)
-    ]);
+    // 后端优先使用 group delay 接口，会同时清除 fixed
+    await delayManager.checkGroupDelay(names, groupName, timeout);
 
-    onProxies();
+    onProxies(true);
   });
 
   // 滚到对应的节点
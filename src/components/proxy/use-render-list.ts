@@ -53,7 +53,7 @@ const groupProxies = <T = any>(list: T[], size: number): T[][] => {
 export const useRenderList = (mode: string) => {
   const { data: proxiesData, mutate: mutateProxies } = useSWR(
     "getProxies",
-    getProxies,
+    () => getProxies(),
     {
       refreshInterval: 2000,
       revalidateOnFocus: false,
@@ -161,9 +161,13 @@ export const useRenderList = (mode: string) => {
     return retList.filter((item) => !item.group.hidden);
   }, [headStates, proxiesData, mode, col]);
 
+  // 测完延迟等需要最新结果时跳过后端缓存
+  const onProxies = (fresh = false) =>
+    fresh ? mutateProxies(getProxies(true)) : mutateProxies();
+
   return {
     renderList,
-    onProxies: mutateProxies,
+    onProxies,
     onHeadState: setHeadState,
     currentColumns: col,
   };
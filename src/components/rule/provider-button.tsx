@@ -47,12 +47,10 @@ export const ProviderButton = () => {
     ruleProviderUpdate(key)
       .then(async () => {
         setUpdatingAt(false, index);
-        await mutate("getRules");
         await mutate("getRuleProviders");
       })
       .catch(async () => {
         setUpdatingAt(false, index);
-        await mutate("getRules");
         await mutate("getRuleProviders");
       });
   };
@@ -13,6 +13,8 @@ import {
   Stack,
   IconButton,
   InputAdornment,
+  Checkbox,
+  FormControlLabel,
 } from "@mui/material";
 import Visibility from "@mui/icons-material/Visibility";
 import VisibilityOff from "@mui/icons-material/VisibilityOff";
@@ -38,6 +40,8 @@ export const BackupConfigViewer = memo(
     const { verge } = useVerge();
     const { webdav_url, webdav_username, webdav_password } = verge || {};
     const [showPassword, setShowPassword] = useState(false);
+    // 订阅的证书和私钥默认不备份，需要用户主动选择
+    const [includeTlsOptions, setIncludeTlsOptions] = useState(false);
     const usernameRef = useRef<HTMLInputElement>(null);
     const passwordRef = useRef<HTMLInputElement>(null);
     const urlRef = useRef<HTMLInputElement>(null);
@@ -125,7 +129,7 @@ export const BackupConfigViewer = memo(
       checkForm();
       try {
         setLoading(true);
-        await createWebdavBackup().then(async () => {
+        await createWebdavBackup(includeTlsOptions).then(async () => {
           await onBackupSuccess();
           Notice.success(t("Backup Created"));
         });
@@ -193,6 +197,18 @@ export const BackupConfigViewer = memo(
                   }}
                 />
               </Grid2>
+              <Grid2 size={{ xs: 12 }}>
+                <FormControlLabel
+                  control={
+                    <Checkbox
+                      size="small"
+                      checked={includeTlsOptions}
+                      onChange={(e) => setIncludeTlsOptions(e.target.checked)}
+                    />
+                  }
+                  label={t("Include Subscription TLS Options")}
+                />
+              </Grid2>
             </Grid2>
           </Grid2>
           <Grid2 size={{ xs: 12, sm: 3 }}>
@@ -17,8 +17,13 @@ import {
   ListItemButton,
   ListItemText,
 } from "@mui/material";
-import { changeClashCore, restartCore } from "@/services/cmds";
-import { closeAllConnections, upgradeCore } from "@/services/api";
+import {
+  changeClashCore,
+  compareCoreValidation,
+  restartCore,
+  upgradeCore,
+} from "@/services/cmds";
+import { closeAllConnections } from "@/services/api";
 
 const VALID_CORE = [
   { name: "Mihomo", core: "verge-mihomo", chip: "Release Version" },
@@ -44,8 +49,23 @@ export const ClashCoreViewer = forwardRef<DialogRef>((props, ref) => {
     if (core === clash_core) return;
 
     try {
+      // 目标内核不接受当前配置时，由用户决定是否使用默认配置切换
+      const { current, target } = await compareCoreValidation(core);
+      let useDefaultConfig = false;
+      if (target.error) {
+        const lines = [
+          `${t("Target Core Rejects Config")}: ${target.message}`,
+          current.error
+            ? t("Current Core Rejects Config Too")
+            : t("Current Core Accepts Config"),
+          t("Switch Core with Default Config?"),
+        ];
+        if (!(await window.confirm(lines.join("\n\n")))) return;
+        useDefaultConfig = true;
+      }
+
       closeAllConnections();
-      await changeClashCore(core);
+      await changeClashCore(core, useDefaultConfig);
       mutateVerge();
       setTimeout(() => {
         mutate("getClashConfig");
@@ -69,15 +89,44 @@ export const ClashCoreViewer = forwardRef<DialogRef>((props, ref) => {
   const onUpgrade = useLockFn(async () => {
     try {
       setUpgrading(true);
-      await upgradeCore();
+      const res = await upgradeCore();
       setUpgrading(false);
-      Notice.success(t(`Core Version Updated`), 1000);
+      if (res.cancelled) return;
+      const result = res.result;
+      if ("Err" in result) {
+        Notice.error(upgradeErrorMessage(result.Err), 3000);
+        return;
+      }
+      const version = result.Ok.new_version;
+      Notice.success(
+        version
+          ? `${t("Core Version Updated")}: ${version}`
+          : t("Core Version Updated"),
+        1500,
+      );
     } catch (err: any) {
       setUpgrading(false);
-      Notice.error(err?.response.data.message || err.toString());
+      Notice.error(err?.message || err.toString());
     }
   });
 
+  const upgradeErrorMessage = (err: ICoreUpgradeError) => {
+    switch (err.kind) {
+      case "service_mode":
+        return t("Core Upgrade Service Mode");
+      case "no_permission":
+        return `${t("Core Upgrade No Permission")}: ${err.path}`;
+      case "unsupported":
+        return t("Core Upgrade Unsupported");
+      case "already_latest":
+        return t("Core Already Latest");
+      case "binary_unchanged":
+        return t("Core Binary Unchanged");
+      default:
+        return err.detail;
+    }
+  };
+
   return (
     <BaseDialog
       open={open}
@@ -1,8 +1,17 @@
 import { forwardRef, useImperativeHandle, useState } from "react";
 import { useLockFn } from "ahooks";
 import { useTranslation } from "react-i18next";
-import { List, ListItem, ListItemText, TextField } from "@mui/material";
+import {
+  List,
+  ListItem,
+  ListItemText,
+  MenuItem,
+  Select,
+  TextField,
+} from "@mui/material";
 import { useClashInfo } from "@/hooks/use-clash";
+import { useVerge } from "@/hooks/use-verge";
+import getSystem from "@/utils/get-system";
 import { BaseDialog, DialogRef, Notice } from "@/components/base";
 
 export const ControllerViewer = forwardRef<DialogRef>((props, ref) => {
@@ -10,22 +19,36 @@ export const ControllerViewer = forwardRef<DialogRef>((props, ref) => {
   const [open, setOpen] = useState(false);
 
   const { clashInfo, patchInfo } = useClashInfo();
+  const { verge, patchVerge } = useVerge();
 
   const [controller, setController] = useState(clashInfo?.server || "");
   const [secret, setSecret] = useState(clashInfo?.secret || "");
+  const [transport, setTransport] = useState<"tcp" | "socket">("tcp");
 
   useImperativeHandle(ref, () => ({
     open: () => {
       setOpen(true);
       setController(clashInfo?.server || "");
-      setSecret(clashInfo?.secret || "");
+      setSecret(verge?.external_controller_secret || "");
+      setTransport(verge?.external_controller_transport ?? "tcp");
     },
     close: () => setOpen(false),
   }));
 
   const onSave = useLockFn(async () => {
     try {
-      await patchInfo({ "external-controller": controller, secret });
+      if (controller !== clashInfo?.server) {
+        await patchInfo({ "external-controller": controller });
+      }
+      // 修改密钥或连接方式会重启内核
+      const patch: Partial<IVergeConfig> = {};
+      if (secret !== (verge?.external_controller_secret || "")) {
+        patch.external_controller_secret = secret;
+      }
+      if (transport !== (verge?.external_controller_transport ?? "tcp")) {
+        patch.external_controller_transport = transport;
+      }
+      if (Object.keys(patch).length > 0) await patchVerge(patch);
       Notice.success(t("External Controller Address Modified"), 1000);
       setOpen(false);
     } catch (err: any) {
@@ -64,12 +87,30 @@ export const ControllerViewer = forwardRef<DialogRef>((props, ref) => {
             size="small"
             sx={{ width: 175 }}
             value={secret}
-            placeholder={t("Recommended")}
+            placeholder={t("Random on Every Launch")}
             onChange={(e) =>
               setSecret(e.target.value?.replace(/[^\x00-\x7F]/g, ""))
             }
           />
         </ListItem>
+
+        <ListItem sx={{ padding: "5px 2px" }}>
+          <ListItemText
+            primary={t("Controller Transport")}
+            secondary={t("Controller Transport Info")}
+          />
+          <Select
+            size="small"
+            sx={{ width: 175, "> div": { py: "7.5px" } }}
+            value={transport}
+            onChange={(e) => setTransport(e.target.value as "tcp" | "socket")}
+          >
+            <MenuItem value="tcp">TCP</MenuItem>
+            <MenuItem value="socket">
+              {getSystem() === "windows" ? "Named Pipe" : "Unix Socket"}
+            </MenuItem>
+          </Select>
+        </ListItem>
       </List>
     </BaseDialog>
   );
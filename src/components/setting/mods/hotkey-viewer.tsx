@@ -1,9 +1,10 @@
-import { forwardRef, useImperativeHandle, useState } from "react";
+import { forwardRef, useImperativeHandle, useRef, useState } from "react";
 import { useTranslation } from "react-i18next";
 import { useLockFn } from "ahooks";
 import { styled, Typography, Switch } from "@mui/material";
 import { useVerge } from "@/hooks/use-verge";
 import { BaseDialog, DialogRef, Notice } from "@/components/base";
+import { getHotkeyStatus, validateHotkey } from "@/services/cmds";
 import { HotkeyInput } from "./hotkey-input";
 
 const ItemWrapper = styled("div")`
@@ -18,10 +19,48 @@ const HOTKEY_FUNC = [
   "clash_mode_rule",
   "clash_mode_global",
   "clash_mode_direct",
+  "cycle_clash_mode",
   "toggle_system_proxy",
   "toggle_tun_mode",
+  "toggle_lightweight_mode",
+  "restart_core",
+  "next_profile",
+  "prev_profile",
+  "copy_proxy_env",
+  "open_app_dir",
+  "open_core_dir",
+  "force_reconnect_all",
+  "clear_connections",
+  "toggle_ipv6_routing",
+  "save_and_reload",
+  "toggle_mux",
+  "export_diagnostics",
+  "check_connectivity_now",
+  "rotate_sidecar_log",
+  // 模拟代理模式只在开发构建中可用
+  ...(import.meta.env.DEV ? ["toggle_mock_mode"] : []),
 ];
 
+const joinKeys = (keys: string[]) =>
+  keys
+    .map((k) => k.trim())
+    .filter(Boolean)
+    .map((k) => (k === "+" ? "PLUS" : k))
+    .join("+");
+
+// 生成保存用的热键列表 `{func},{key}`
+const buildHotkeys = (map: Record<string, string[]>, extra: string[]) =>
+  Object.entries(map)
+    .map(([func, keys]) => {
+      if (!func || !keys?.length) return "";
+
+      const key = joinKeys(keys);
+      if (!key) return "";
+      return `${func},${key}`;
+    })
+    .filter(Boolean)
+    .concat(extra);
+
 export const HotkeyViewer = forwardRef<DialogRef>((props, ref) => {
   const { t } = useTranslation();
   const [open, setOpen] = useState(false);
@@ -29,20 +68,40 @@ export const HotkeyViewer = forwardRef<DialogRef>((props, ref) => {
   const { verge, patchVerge } = useVerge();
 
   const [hotkeyMap, setHotkeyMap] = useState<Record<string, string[]>>({});
+  // 带参数的热键（如 toggle_mux,<proxy>,<key>、select_proxy:<group>:<proxy>,<key>）
+  // 和同一功能的第二个及之后的快捷键无法在这里编辑，保存时原样保留
+  const [argHotkeys, setArgHotkeys] = useState<string[]>([]);
+  // 注册失败的热键，功能名 -> 错误原因
+  const [hotkeyErrors, setHotkeyErrors] = useState<Record<string, string>>({});
+  // 每次修改都重新检查全部热键，只采用最后一次检查的结果
+  const validateSeq = useRef(0);
   const [enableGlobalHotkey, setEnableHotkey] = useState(
     verge?.enable_global_hotkey ?? true,
   );
+  const [enableInAppHotkey, setEnableInAppHotkey] = useState(
+    verge?.enable_in_app_hotkey ?? false,
+  );
+  const [enableNotification, setEnableNotification] = useState(
+    verge?.enable_hotkey_notification ?? true,
+  );
 
   useImperativeHandle(ref, () => ({
     open: () => {
       setOpen(true);
 
       const map = {} as typeof hotkeyMap;
+      const withArgs: string[] = [];
 
       verge?.hotkeys?.forEach((text) => {
-        const [func, key] = text.split(",").map((e) => e.trim());
+        const index = text.lastIndexOf(",");
+        const func = text.slice(0, index).trim();
+        const key = text.slice(index + 1).trim();
 
-        if (!func || !key) return;
+        if (index < 0 || !func || !key) return;
+        if (func.includes(",") || func.includes(":") || func in map) {
+          withArgs.push(text);
+          return;
+        }
 
         map[func] = key
           .split("+")
@@ -51,30 +110,60 @@ export const HotkeyViewer = forwardRef<DialogRef>((props, ref) => {
       });
 
       setHotkeyMap(map);
+      setArgHotkeys(withArgs);
+
+      getHotkeyStatus()
+        .then((status) =>
+          setHotkeyErrors(
+            Object.fromEntries(
+              status
+                .filter((s) => !s.active && !s.pending)
+                .map((s) => [s.func, s.error ?? ""]),
+            ),
+          ),
+        )
+        .catch(() => setHotkeyErrors({}));
     },
     close: () => setOpen(false),
   }));
 
-  const onSave = useLockFn(async () => {
-    const hotkeys = Object.entries(hotkeyMap)
-      .map(([func, keys]) => {
-        if (!func || !keys?.length) return "";
-
-        const key = keys
-          .map((k) => k.trim())
-          .filter(Boolean)
-          .map((k) => (k === "+" ? "PLUS" : k))
-          .join("+");
-
-        if (!key) return "";
-        return `${func},${key}`;
+  // 修改后立即按未保存的热键检查，在功能名称上显示错误
+  // 一个热键的修改可能产生或消除其他热键的冲突，所以全部重新检查
+  const onChangeHotkey = (func: string, keys: string[]) => {
+    const map = { ...hotkeyMap, [func]: keys };
+    setHotkeyMap(map);
+
+    const seq = ++validateSeq.current;
+    const pending = buildHotkeys(map, argHotkeys);
+    const checks = Object.entries(map)
+      .map(([func, keys]) => [func, joinKeys(keys)] as const)
+      .filter(([, key]) => key)
+      .map(async ([func, key]) => {
+        const verdict = await validateHotkey(key, func, pending);
+        if (verdict.valid) return [];
+        const error = verdict.conflict
+          ? t("Hotkey Conflict", { func: t(verdict.conflict) })
+          : (verdict.func_error ?? verdict.key_error ?? verdict.os_error ?? "");
+        return [[func, error] as const];
+      });
+
+    Promise.all(checks)
+      .then((results) => {
+        if (seq !== validateSeq.current) return;
+        setHotkeyErrors(Object.fromEntries(results.flat()));
       })
-      .filter(Boolean);
+      .catch(() => {});
+  };
+
+  const onSave = useLockFn(async () => {
+    const hotkeys = buildHotkeys(hotkeyMap, argHotkeys);
 
     try {
       await patchVerge({
         hotkeys,
         enable_global_hotkey: enableGlobalHotkey,
+        enable_in_app_hotkey: enableInAppHotkey,
+        enable_hotkey_notification: enableNotification,
       });
       setOpen(false);
     } catch (err: any) {
@@ -102,12 +191,37 @@ export const HotkeyViewer = forwardRef<DialogRef>((props, ref) => {
         />
       </ItemWrapper>
 
+      {!enableGlobalHotkey && (
+        <ItemWrapper style={{ marginBottom: 16 }}>
+          <Typography>{t("Enable In-App Hotkey")}</Typography>
+          <Switch
+            edge="end"
+            checked={enableInAppHotkey}
+            onChange={(e) => setEnableInAppHotkey(e.target.checked)}
+          />
+        </ItemWrapper>
+      )}
+
+      <ItemWrapper style={{ marginBottom: 16 }}>
+        <Typography>{t("Hotkey Toggle Notification")}</Typography>
+        <Switch
+          edge="end"
+          checked={enableNotification}
+          onChange={(e) => setEnableNotification(e.target.checked)}
+        />
+      </ItemWrapper>
+
       {HOTKEY_FUNC.map((func) => (
         <ItemWrapper key={func}>
-          <Typography>{t(func)}</Typography>
+          <Typography
+            color={func in hotkeyErrors ? "error" : undefined}
+            title={hotkeyErrors[func]}
+          >
+            {t(func)}
+          </Typography>
           <HotkeyInput
             value={hotkeyMap[func] ?? []}
-            onChange={(v) => setHotkeyMap((m) => ({ ...m, [func]: v }))}
+            onChange={(v) => onChangeHotkey(func, v)}
           />
         </ItemWrapper>
       ))}
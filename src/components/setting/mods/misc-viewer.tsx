@@ -22,12 +22,19 @@ export const MiscViewer = forwardRef<DialogRef>((props, ref) => {
   const [values, setValues] = useState({
     appLogLevel: "info",
     autoCloseConnection: true,
+    autoCloseConnectionScope: "group",
     autoCheckUpdate: true,
+    enableProfileHotReload: true,
+    orphanCoreAction: "restart",
+    portConflictAction: "fail",
+    forceFullCoreRestart: false,
     enableBuiltinEnhanced: true,
     proxyLayoutColumn: 6,
     defaultLatencyTest: "",
     autoLogClean: 0,
     defaultLatencyTimeout: 10000,
+    coreExtraArgs: "",
+    coreRunOnlyArgs: "",
   });
 
   useImperativeHandle(ref, () => ({
@@ -36,28 +43,47 @@ export const MiscViewer = forwardRef<DialogRef>((props, ref) => {
       setValues({
         appLogLevel: verge?.app_log_level ?? "info",
         autoCloseConnection: verge?.auto_close_connection ?? true,
+        autoCloseConnectionScope: verge?.auto_close_connection_scope ?? "group",
         autoCheckUpdate: verge?.auto_check_update ?? true,
+        enableProfileHotReload: verge?.enable_profile_hot_reload ?? true,
+        orphanCoreAction: verge?.orphan_core_action ?? "restart",
+        portConflictAction: verge?.port_conflict_action ?? "fail",
+        forceFullCoreRestart: verge?.force_full_core_restart ?? false,
         enableBuiltinEnhanced: verge?.enable_builtin_enhanced ?? true,
         proxyLayoutColumn: verge?.proxy_layout_column || 6,
         defaultLatencyTest: verge?.default_latency_test || "",
         autoLogClean: verge?.auto_log_clean || 0,
         defaultLatencyTimeout: verge?.default_latency_timeout || 10000,
+        coreExtraArgs: (verge?.clash_core_extra_args ?? []).join(" "),
+        coreRunOnlyArgs: (verge?.clash_core_run_only_args ?? []).join(" "),
       });
     },
     close: () => setOpen(false),
   }));
 
+  // 以空白分隔参数
+  const splitArgs = (args: string) => args.split(/\s+/).filter((arg) => arg);
+
   const onSave = useLockFn(async () => {
     try {
       await patchVerge({
         app_log_level: values.appLogLevel,
         auto_close_connection: values.autoCloseConnection,
+        auto_close_connection_scope: values.autoCloseConnectionScope as
+          | "group"
+          | "all",
         auto_check_update: values.autoCheckUpdate,
+        enable_profile_hot_reload: values.enableProfileHotReload,
+        orphan_core_action: values.orphanCoreAction as "restart" | "adopt",
+        port_conflict_action: values.portConflictAction as "fail" | "auto",
+        force_full_core_restart: values.forceFullCoreRestart,
         enable_builtin_enhanced: values.enableBuiltinEnhanced,
         proxy_layout_column: values.proxyLayoutColumn,
         default_latency_test: values.defaultLatencyTest,
         default_latency_timeout: values.defaultLatencyTimeout,
         auto_log_clean: values.autoLogClean as any,
+        clash_core_extra_args: splitArgs(values.coreExtraArgs),
+        clash_core_run_only_args: splitArgs(values.coreRunOnlyArgs),
       });
       setOpen(false);
     } catch (err: any) {
@@ -117,6 +143,25 @@ export const MiscViewer = forwardRef<DialogRef>((props, ref) => {
           />
         </ListItem>
 
+        <ListItem sx={{ padding: "5px 2px" }}>
+          <ListItemText primary={t("Close Connections Scope")} />
+          <Select
+            size="small"
+            disabled={!values.autoCloseConnection}
+            sx={{ width: 135, "> div": { py: "7.5px" } }}
+            value={values.autoCloseConnectionScope}
+            onChange={(e) =>
+              setValues((v) => ({
+                ...v,
+                autoCloseConnectionScope: e.target.value as string,
+              }))
+            }
+          >
+            <MenuItem value="group">{t("Switched Group Only")}</MenuItem>
+            <MenuItem value="all">{t("All Connections")}</MenuItem>
+          </Select>
+        </ListItem>
+
         <ListItem sx={{ padding: "5px 2px" }}>
           <ListItemText primary={t("Auto Check Update")} />
           <Switch
@@ -128,6 +173,25 @@ export const MiscViewer = forwardRef<DialogRef>((props, ref) => {
           />
         </ListItem>
 
+        <ListItem sx={{ padding: "5px 2px" }}>
+          <ListItemText
+            primary={t("Profile Hot Reload")}
+            sx={{ maxWidth: "fit-content" }}
+          />
+          <TooltipIcon
+            title={t("Profile Hot Reload Info")}
+            sx={{ opacity: "0.7" }}
+          />
+          <Switch
+            edge="end"
+            checked={values.enableProfileHotReload}
+            onChange={(_, c) =>
+              setValues((v) => ({ ...v, enableProfileHotReload: c }))
+            }
+            sx={{ marginLeft: "auto" }}
+          />
+        </ListItem>
+
         <ListItem sx={{ padding: "5px 2px" }}>
           <ListItemText
             primary={t("Enable Builtin Enhanced")}
@@ -147,6 +211,61 @@ export const MiscViewer = forwardRef<DialogRef>((props, ref) => {
           />
         </ListItem>
 
+        <ListItem sx={{ padding: "5px 2px" }}>
+          <ListItemText primary={t("Leftover Core")} />
+          <Select
+            size="small"
+            sx={{ width: 135, "> div": { py: "7.5px" } }}
+            value={values.orphanCoreAction}
+            onChange={(e) =>
+              setValues((v) => ({
+                ...v,
+                orphanCoreAction: e.target.value as string,
+              }))
+            }
+          >
+            <MenuItem value="restart">{t("Restart Leftover Core")}</MenuItem>
+            <MenuItem value="adopt">{t("Adopt Leftover Core")}</MenuItem>
+          </Select>
+        </ListItem>
+
+        <ListItem sx={{ padding: "5px 2px" }}>
+          <ListItemText primary={t("Port Conflict")} />
+          <Select
+            size="small"
+            sx={{ width: 135, "> div": { py: "7.5px" } }}
+            value={values.portConflictAction}
+            onChange={(e) =>
+              setValues((v) => ({
+                ...v,
+                portConflictAction: e.target.value as string,
+              }))
+            }
+          >
+            <MenuItem value="fail">{t("Stop on Port Conflict")}</MenuItem>
+            <MenuItem value="auto">{t("Pick Free Port")}</MenuItem>
+          </Select>
+        </ListItem>
+
+        <ListItem sx={{ padding: "5px 2px" }}>
+          <ListItemText
+            primary={t("Full Core Restart")}
+            sx={{ maxWidth: "fit-content" }}
+          />
+          <TooltipIcon
+            title={t("Full Core Restart Info")}
+            sx={{ opacity: "0.7" }}
+          />
+          <Switch
+            edge="end"
+            checked={values.forceFullCoreRestart}
+            onChange={(_, c) =>
+              setValues((v) => ({ ...v, forceFullCoreRestart: c }))
+            }
+            sx={{ marginLeft: "auto" }}
+          />
+        </ListItem>
+
         <ListItem sx={{ padding: "5px 2px" }}>
           <ListItemText primary={t("Proxy Layout Columns")} />
           <Select
@@ -246,6 +365,53 @@ export const MiscViewer = forwardRef<DialogRef>((props, ref) => {
             }}
           />
         </ListItem>
+
+        <ListItem sx={{ padding: "5px 2px" }}>
+          <ListItemText
+            primary={t("Core Extra Args")}
+            sx={{ maxWidth: "fit-content" }}
+          />
+          <TooltipIcon
+            title={t("Core Extra Args Info")}
+            sx={{ opacity: "0.7" }}
+          />
+          <TextField
+            autoComplete="new-password"
+            size="small"
+            autoCorrect="off"
+            autoCapitalize="off"
+            spellCheck="false"
+            sx={{ width: 250, marginLeft: "auto" }}
+            value={values.coreExtraArgs}
+            onChange={(e) =>
+              setValues((v) => ({ ...v, coreExtraArgs: e.target.value }))
+            }
+          />
+        </ListItem>
+
+        <ListItem sx={{ padding: "5px 2px" }}>
+          <ListItemText
+            primary={t("Core Run-Only Args")}
+            sx={{ maxWidth: "fit-content" }}
+          />
+          <TooltipIcon
+            title={t("Core Run-Only Args Info")}
+            sx={{ opacity: "0.7" }}
+          />
+          <TextField
+            autoComplete="new-password"
+            size="small"
+            autoCorrect="off"
+            autoCapitalize="off"
+            spellCheck="false"
+            sx={{ width: 250, marginLeft: "auto" }}
+            value={values.coreRunOnlyArgs}
+            placeholder="-ext-ctl-unix /tmp/mihomo.sock"
+            onChange={(e) =>
+              setValues((v) => ({ ...v, coreRunOnlyArgs: e.target.value }))
+            }
+          />
+        </ListItem>
       </List>
     </BaseDialog>
   );
@@ -1,4 +1,5 @@
-import { useRef } from "react";
+import { useEffect, useRef } from "react";
+import useSWR from "swr";
 import { useTranslation } from "react-i18next";
 import { TextField, Select, MenuItem, Typography } from "@mui/material";
 import {
@@ -14,15 +15,42 @@ import { ClashPortViewer } from "./mods/clash-port-viewer";
 import { ControllerViewer } from "./mods/controller-viewer";
 import { SettingList, SettingItem } from "./mods/setting-comp";
 import { ClashCoreViewer } from "./mods/clash-core-viewer";
-import { invoke_uwp_tool } from "@/services/cmds";
+import {
+  getCoreStatus,
+  getCoreUsage,
+  invoke_uwp_tool,
+  updateGeoData,
+} from "@/services/cmds";
+import { listen } from "@tauri-apps/api/event";
 import getSystem from "@/utils/get-system";
 import { useVerge } from "@/hooks/use-verge";
-import { updateGeoData } from "@/services/api";
 import { TooltipIcon } from "@/components/base/base-tooltip-icon";
 import { NetworkInterfaceViewer } from "./mods/network-interface-viewer";
 
 const isWIN = getSystem() === "windows";
 
+// 例如 "verge-mihomo-alpha · sidecar · up 3h 12m · 85.2 MB · CPU 1.5%"
+const formatCoreStatus = (
+  status: ICoreStatus | undefined,
+  usage: ICoreUsage | null | undefined,
+  t: (key: string) => string,
+) => {
+  if (!status?.core_name || !status.run_mode || !status.started_at) return;
+
+  const name = status.core_name.split(/[\\/]/).pop();
+  const seconds = Math.max(0, Date.now() / 1000 - status.started_at);
+  const days = Math.floor(seconds / 86400);
+  const hours = Math.floor((seconds % 86400) / 3600);
+  const minutes = Math.floor((seconds % 3600) / 60);
+  const uptime = days > 0 ? `${days}d ${hours}h` : `${hours}h ${minutes}m`;
+
+  const text = `${name} · ${t(status.run_mode)} · ${t("Uptime")} ${uptime}`;
+  if (!usage) return text;
+
+  const memory = (usage.rss / 1024 / 1024).toFixed(1);
+  return `${text} · ${memory} MB · CPU ${usage.cpu_percent.toFixed(1)}%`;
+};
+
 interface Props {
   onError: (err: Error) => void;
 }
@@ -30,8 +58,25 @@ interface Props {
 const SettingClash = ({ onError }: Props) => {
   const { t } = useTranslation();
 
-  const { clash, version, mutateClash, patchClash } = useClash();
+  const { clash, version, mutateClash, patchClashOption } = useClash();
   const { verge, mutateVerge, patchVerge } = useVerge();
+  const { data: coreStatus } = useSWR("getCoreStatus", getCoreStatus, {
+    refreshInterval: 60000,
+  });
+  const { data: coreUsage, mutate: mutateCoreUsage } = useSWR(
+    "getCoreUsage",
+    getCoreUsage,
+  );
+
+  // 后端定时采样内核的资源占用
+  useEffect(() => {
+    const unlisten = listen<ICoreUsage>("verge://core-usage", (e) =>
+      mutateCoreUsage(e.payload, false),
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
 
   const {
     ipv6,
@@ -55,12 +100,13 @@ const SettingClash = ({ onError }: Props) => {
   const onChangeVerge = (patch: Partial<IVergeConfig>) => {
     mutateVerge({ ...verge, ...patch }, false);
   };
+  // 由应用下载，可以取消，结果通过 update_geo 通知
   const onUpdateGeo = async () => {
     try {
       await updateGeoData();
-      Notice.success(t("GeoData Updated"));
+      Notice.info(t("Updating GeoData"), 2000);
     } catch (err: any) {
-      Notice.error(err?.response.data.message || err.toString());
+      Notice.error(err?.message || err.toString());
     }
   };
 
@@ -91,7 +137,7 @@ const SettingClash = ({ onError }: Props) => {
           onCatch={onError}
           onFormat={onSwitchFormat}
           onChange={(e) => onChangeData({ "allow-lan": e })}
-          onGuard={(e) => patchClash({ "allow-lan": e })}
+          onGuard={(e) => patchClashOption("allow-lan", e)}
         >
           <Switch edge="end" />
         </GuardState>
@@ -104,7 +150,7 @@ const SettingClash = ({ onError }: Props) => {
           onCatch={onError}
           onFormat={onSwitchFormat}
           onChange={(e) => onChangeData({ ipv6: e })}
-          onGuard={(e) => patchClash({ ipv6: e })}
+          onGuard={(e) => patchClashOption("ipv6", e)}
         >
           <Switch edge="end" />
         </GuardState>
@@ -125,7 +171,7 @@ const SettingClash = ({ onError }: Props) => {
           onCatch={onError}
           onFormat={onSwitchFormat}
           onChange={(e) => onChangeData({ "unified-delay": e })}
-          onGuard={(e) => patchClash({ "unified-delay": e })}
+          onGuard={(e) => patchClashOption("unified-delay", e)}
         >
           <Switch edge="end" />
         </GuardState>
@@ -143,7 +189,7 @@ const SettingClash = ({ onError }: Props) => {
           onCatch={onError}
           onFormat={(e: any) => e.target.value}
           onChange={(e) => onChangeData({ "log-level": e })}
-          onGuard={(e) => patchClash({ "log-level": e })}
+          onGuard={(e) => patchClashOption("log-level", e)}
         >
           <Select size="small" sx={{ width: 100, "> div": { py: "7.5px" } }}>
             <MenuItem value="debug">Debug</MenuItem>
@@ -195,6 +241,7 @@ const SettingClash = ({ onError }: Props) => {
 
       <SettingItem
         label={t("Clash Core")}
+        secondary={formatCoreStatus(coreStatus, coreUsage, t)}
         extra={
           <TooltipIcon
             icon={SettingsRounded}
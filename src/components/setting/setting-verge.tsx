@@ -1,4 +1,5 @@
 import { useCallback, useRef } from "react";
+import useSWR from "swr";
 import { useTranslation } from "react-i18next";
 import { open } from "@tauri-apps/plugin-dialog";
 import { Button, MenuItem, Select, Input, Typography } from "@mui/material";
@@ -9,6 +10,7 @@ import {
   openLogsDir,
   openDevTools,
   copyClashEnv,
+  getCoreStatus,
 } from "@/services/cmds";
 import { check as checkUpdate } from "@tauri-apps/plugin-updater";
 import { useVerge } from "@/hooks/use-verge";
@@ -53,6 +55,7 @@ const SettingVerge = ({ onError }: Props) => {
   const { t } = useTranslation();
 
   const { verge, patchVerge, mutateVerge } = useVerge();
+  const { data: coreStatus } = useSWR("getCoreStatus", getCoreStatus);
   const {
     theme_mode,
     language,
@@ -315,6 +318,14 @@ const SettingVerge = ({ onError }: Props) => {
       <SettingItem label={t("Verge Version")}>
         <Typography sx={{ py: "7px", pr: 1 }}>v{version}</Typography>
       </SettingItem>
+
+      {coreStatus?.api_version && (
+        <SettingItem label={t("Core API Version")}>
+          <Typography sx={{ py: "7px", pr: 1 }}>
+            {coreStatus.api_version}
+          </Typography>
+        </SettingItem>
+      )}
     </SettingList>
   );
 };
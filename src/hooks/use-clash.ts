@@ -1,9 +1,10 @@
 import useSWR, { mutate } from "swr";
 import { useLockFn } from "ahooks";
-import { getAxios, getVersion } from "@/services/api";
+import { getVersion } from "@/services/api";
 import {
   getClashInfo,
   patchClashConfig,
+  patchClashRuntimeOption,
   getRuntimeConfig,
 } from "@/services/cmds";
 
@@ -23,11 +24,16 @@ export const useClash = () => {
     mutateClash();
   });
 
-  const version = versionData?.premium
-    ? `${versionData.version} Premium`
-    : versionData?.meta
-      ? `${versionData.version} Mihomo`
-      : versionData?.version || "-";
+  const patchClashOption = useLockFn(
+    async (...args: Parameters<typeof patchClashRuntimeOption>) => {
+      await patchClashRuntimeOption(...args);
+      mutateClash();
+    },
+  );
+
+  const version = versionData?.meta
+    ? `${versionData.version} Mihomo`
+    : versionData?.version || "-";
 
   return {
     clash,
@@ -35,6 +41,7 @@ export const useClash = () => {
     mutateClash,
     mutateVersion,
     patchClash,
+    patchClashOption,
   };
 };
 
@@ -122,8 +129,6 @@ export const useClashInfo = () => {
     await patchClashConfig(patch);
     mutateInfo();
     mutate("getClashConfig");
-    // 刷新接口
-    getAxios(true);
   };
 
   return {
@@ -1,8 +1,7 @@
 import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
 import { useEnableLog } from "../services/states";
-import { createSockette } from "../utils/websocket";
-import { useClashInfo } from "./use-clash";
-import dayjs from "dayjs";
+import { startCoreLogs, stopCoreLogs } from "../services/cmds";
 import { create } from "zustand";
 import { useVisibility } from "./use-visibility";
 
@@ -17,26 +16,10 @@ interface ILogItem {
   [key: string]: any;
 }
 
-const buildWSUrl = (server: string, secret: string, logLevel: LogLevel) => {
-  const baseUrl = `ws://${server}/logs`;
-  const params = new URLSearchParams();
-
-  if (secret) {
-    params.append("token", secret);
-  }
-  if (logLevel === "all") {
-    params.append("level", "debug");
-  } else {
-    params.append("level", logLevel);
-  }
-  const queryString = params.toString();
-  return queryString ? `${baseUrl}?${queryString}` : baseUrl;
-};
-
 interface LogStore {
   logs: ILogItem[];
   clearLogs: () => void;
-  appendLog: (log: ILogItem) => void;
+  appendLogs: (logs: ILogItem[]) => void;
 }
 
 const useLogStore = create<LogStore>(
@@ -46,48 +29,32 @@ const useLogStore = create<LogStore>(
       set(() => ({
         logs: [],
       })),
-    appendLog: (log: ILogItem) =>
-      set((state: LogStore) => {
-        const newLogs =
-          state.logs.length >= MAX_LOG_NUM
-            ? [...state.logs.slice(1), log]
-            : [...state.logs, log];
-        return { logs: newLogs };
-      }),
+    appendLogs: (logs: ILogItem[]) =>
+      set((state: LogStore) => ({
+        logs: [...state.logs, ...logs].slice(-MAX_LOG_NUM),
+      })),
   }),
 );
 
 export const useLogData = (logLevel: LogLevel) => {
-  const { clashInfo } = useClashInfo();
   const [enableLog] = useEnableLog();
-  const { logs, appendLog } = useLogStore();
+  const { logs, appendLogs } = useLogStore();
   const pageVisible = useVisibility();
 
+  // 由后端订阅内核日志并合并发送，只在页面可见时订阅
   useEffect(() => {
-    if (!enableLog || !clashInfo || !pageVisible) return;
-
-    const { server = "", secret = "" } = clashInfo;
-    const wsUrl = buildWSUrl(server, secret, logLevel);
+    if (!enableLog || !pageVisible) return;
 
-    let isActive = true;
-    const socket = createSockette(wsUrl, {
-      onmessage(event) {
-        if (!isActive) return;
-        const data = JSON.parse(event.data) as ILogItem;
-        const time = dayjs().format("MM-DD HH:mm:ss");
-        appendLog({ ...data, time });
-      },
-      onerror() {
-        if (!isActive) return;
-        socket.close();
-      },
-    });
+    const unlisten = listen<ILogItem[]>("verge://core-log", ({ payload }) =>
+      appendLogs(payload),
+    );
+    startCoreLogs(logLevel === "all" ? "debug" : logLevel).catch(() => {});
 
     return () => {
-      isActive = false;
-      socket.close();
+      stopCoreLogs().catch(() => {});
+      unlisten.then((fn) => fn());
     };
-  }, [clashInfo, enableLog, logLevel]);
+  }, [enableLog, logLevel, pageVisible]);
 
   // 根据当前选择的日志等级过滤日志
   return logLevel === "all"
@@ -0,0 +1,31 @@
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { getSubscriptionState } from "@/services/cmds";
+
+// 后端到内核的 WebSocket 订阅的连接状态，断开时不再显示过时的数据
+export const useSubscriptionState = (
+  name: ISubscriptionName,
+  enabled = true,
+) => {
+  const [state, setState] = useState<ISubscriptionState | null>(null);
+
+  useEffect(() => {
+    if (!enabled) return;
+
+    const unlisten = listen<{ name: string; state: ISubscriptionState }>(
+      "verge://subscription-state",
+      ({ payload }) => {
+        if (payload.name === name) setState(payload.state);
+      },
+    );
+    getSubscriptionState(name)
+      .then(setState)
+      .catch(() => {});
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [name, enabled]);
+
+  return state;
+};
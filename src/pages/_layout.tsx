@@ -5,10 +5,9 @@ import { SWRConfig, mutate } from "swr";
 import { useEffect, useCallback } from "react";
 import { useTranslation } from "react-i18next";
 import { useLocation, useRoutes, useNavigate } from "react-router-dom";
-import { List, Paper, ThemeProvider, SvgIcon } from "@mui/material";
+import { Box, List, Paper, ThemeProvider, SvgIcon } from "@mui/material";
 import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
 import { routers } from "./_routers";
-import { getAxios } from "@/services/api";
 import { useVerge } from "@/hooks/use-verge";
 import LogoSvg from "@/assets/image/logo.svg?react";
 import iconLight from "@/assets/image/icon_light.svg?react";
@@ -24,6 +23,7 @@ import getSystem from "@/utils/get-system";
 import "dayjs/locale/ru";
 import "dayjs/locale/zh-cn";
 import { getPortableFlag } from "@/services/cmds";
+import { flushPendingSave } from "@/services/pending-save";
 import React from "react";
 import { TransitionGroup, CSSTransition } from "react-transition-group";
 import { useListen } from "@/hooks/use-listen";
@@ -36,6 +36,42 @@ dayjs.extend(relativeTime);
 
 const OS = getSystem();
 
+// 按错误码生成本地化的验证错误描述，通知类型本身已经说明了错误类别
+const describeValidateError = (
+  error: IValidateErrorCode,
+  t: (key: string) => string,
+): string => {
+  switch (error.code) {
+    case "file_not_found":
+      return error.path;
+    case "file_read_failed":
+      return `${t("Failed to Read File")} ${error.path}: ${error.detail}`;
+    case "invalid_yaml":
+      return `${t("Invalid YAML")}: ${error.detail}`;
+    case "subscription_forbidden_keys":
+      return `${t("Subscription Contains Forbidden Keys")}: ${error.keys.join(", ")}`;
+    case "validate_process_exited":
+      return `${t("Validation Process Exited")}: ${error.exit_code}`;
+    case "validate_process_killed":
+    case "script_missing_main":
+      return "";
+    case "validate_timeout":
+      return `${t("Config Validation Timed Out")} (${error.secs}s)`;
+    case "script_runtime_error":
+      return `${t("Script Runtime Error")}: ${error.detail}`;
+    case "script_returned_nothing":
+      return t("Script Returned Nothing");
+    case "script_returned_non_object":
+      return `${t("Script Must Return Object")}: ${error.type_name}`;
+    case "script_timeout":
+      return `${t("Script Execution Timed Out")} (${error.secs}s)`;
+    case "core_rejected":
+    case "script_read_failed":
+    case "script_syntax_error":
+      return error.detail;
+  }
+};
+
 // 通知处理函数
 const handleNoticeMessage = (
   status: string,
@@ -54,6 +90,48 @@ const handleNoticeMessage = (
       navigate("/profile");
       Notice.error(msg);
       break;
+    case "hotkey::register_failed":
+      Notice.error(`${t("Failed to Register Hotkeys")}:\n${msg}`, 6000);
+      break;
+    case "toggle::ok":
+      Notice.success(msg, 1000);
+      break;
+    case "toggle::error":
+      Notice.error(msg, 3000);
+      break;
+    case "open_dir::error":
+      Notice.error(`${t("Failed to Open Directory")}: ${msg}`, 3000);
+      break;
+    case "clear_connections::ok":
+      Notice.success(t("Connections Closed", { count: Number(msg) }), 1500);
+      break;
+    case "clear_connections::error":
+      Notice.error(`${t("Failed to Close Connections")}: ${msg}`, 3000);
+      break;
+    case "update_geo::ok":
+      Notice.success(
+        msg ? `${t("GeoData Updated")}: ${msg}` : t("GeoData Updated"),
+        3000,
+      );
+      break;
+    case "update_geo::error":
+      Notice.error(`${t("Failed to Update GeoData")}: ${msg}`, 3000);
+      break;
+    case "core_version::outdated":
+      Notice.info(`${t("Core Version Outdated")}: ${msg}`, 8000);
+      break;
+    case "copy_env::ok":
+      Notice.success(t("Proxy Env Copied"), 1000);
+      break;
+    case "profile_switch::ok":
+      Notice.success(`${t("Switched to Profile")}: ${msg}`, 1500);
+      break;
+    case "core_restart::ok":
+      Notice.success(t("Clash Core Restarted"), 1000);
+      break;
+    case "core_restart::error":
+      Notice.error(`${t("Failed to Restart Core")}: ${msg}`);
+      break;
     case "set_config::error":
       Notice.error(msg);
       break;
@@ -67,7 +145,19 @@ const handleNoticeMessage = (
       Notice.error(`${t("Config Validation Failed")} ${msg}`);
       break;
     case "config_validate::process_terminated":
-      Notice.error(t("Config Validation Process Terminated"));
+      Notice.error(
+        msg
+          ? `${t("Config Validation Process Terminated")} ${msg}`
+          : t("Config Validation Process Terminated"),
+      );
+      break;
+    case "config_validate::safe_mode":
+      Notice.error(`${t("Started in Safe Mode, Disabled")} ${msg}`);
+      break;
+    case "config_validate::fallback":
+      Notice.info(
+        `${t("Running on Fallback Config")}, ${t("Preserved Settings")}: ${msg}`,
+      );
       break;
     case "config_validate::stdout_error":
       Notice.error(`${t("Config Validation Failed")} ${msg}`);
@@ -90,6 +180,75 @@ const handleNoticeMessage = (
     case "config_core::change_error":
       Notice.error(`${t("Failed to Change Core")}: ${msg}`);
       break;
+    case "config_core::rollback":
+      Notice.error(`${t("Core Crashed, Rolled Back")}: ${msg}`);
+      break;
+    case "export_diagnostics::progress":
+      Notice.info(t("Collecting Diagnostics"));
+      break;
+    case "export_diagnostics::ok":
+      Notice.success(`${t("Diagnostics Exported")}: ${msg}`);
+      break;
+    case "export_diagnostics::error":
+      Notice.error(`${t("Failed to Export Diagnostics")}: ${msg}`);
+      break;
+    case "save_and_reload::ok":
+      Notice.success(t("Config Saved and Reloaded"));
+      break;
+    case "save_and_reload::error":
+      Notice.error(`${t("Failed to Save and Reload Config")}: ${msg}`);
+      break;
+    case "core_orphan::adopted":
+      Notice.info(`${t("Adopted Leftover Core")} (PID ${msg})`);
+      break;
+    case "core_orphan::terminated":
+      Notice.info(`${t("Terminated Leftover Core")} (PID ${msg})`);
+      break;
+    case "core_service::fallback":
+      Notice.info(
+        `${t("Service Mode Failed, Using Sidecar Mode")}: ${msg}`,
+        6000,
+      );
+      break;
+    case "core_binary::missing":
+      Notice.error(
+        `${msg}\n${t("Reinstall the App or Switch to Another Core")}`,
+        6000,
+      );
+      break;
+    case "core_port::reassigned":
+      Notice.info(`${t("Core Port Reassigned")}: ${msg}`);
+      break;
+    case "core_watchdog::restarting":
+      Notice.info(`${t("Core Not Responding, Restarting")} (${msg})`);
+      break;
+    case "core_watchdog::restarted":
+      Notice.success(t("Core Restarted"));
+      break;
+    case "core_watchdog::give_up":
+      Notice.error(t("Core Restart Failed"));
+      break;
+    case "core_log::rotate_failed":
+      Notice.error(`${t("Core Log Rotation Failed")}: ${msg}`, 6000);
+      break;
+    case "check_connectivity::ok": {
+      const delay = Number(msg);
+      const color =
+        delay > 1000 ? "error.main" : delay > 500 ? "warning.main" : "success.main";
+      Notice.info(
+        <Box component="span" sx={{ color }}>
+          {t("Connectivity")}: {delay} ms
+        </Box>,
+      );
+      break;
+    }
+    case "check_connectivity::error":
+      Notice.error(
+        <Box component="span" sx={{ color: "error.main" }}>
+          {t("Connectivity Check Failed")}: {msg}
+        </Box>,
+      );
+      break;
   }
 };
 
@@ -106,9 +265,11 @@ const Layout = () => {
   const { addListener, setupCloseListener } = useListen();
 
   const handleNotice = useCallback(
-    (payload: [string, string]) => {
-      const [status, msg] = payload;
-      handleNoticeMessage(status, msg, t, navigate);
+    (payload: [string, string, IValidateErrorCode?]) => {
+      const [status, msg, error] = payload;
+      // 带错误码的通知使用本地化的描述，原始消息为英文
+      const text = error ? describeValidateError(error, t) : msg;
+      handleNoticeMessage(status, text, t, navigate);
     },
     [t, navigate],
   );
@@ -118,7 +279,8 @@ const Layout = () => {
     const listeners = [
       // 配置更新监听
       addListener("verge://refresh-clash-config", async () => {
-        await getAxios(true);
+        // 内核重启后密钥可能已经更换
+        mutate("getClashInfo");
         mutate("getProxies");
         mutate("getVersion");
         mutate("getClashConfig");
@@ -132,8 +294,11 @@ const Layout = () => {
 
       // 通知消息监听
       addListener("verge://notice-message", ({ payload }) =>
-        handleNotice(payload as [string, string]),
+        handleNotice(payload as [string, string, IValidateErrorCode?]),
       ),
+
+      // 保存未提交的修改
+      addListener("config::request_save", () => flushPendingSave()),
     ];
 
     // 设置窗口显示/隐藏监听
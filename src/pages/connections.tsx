@@ -10,6 +10,7 @@ import {
   PauseCircleOutlineRounded,
 } from "@mui/icons-material";
 import { closeAllConnections } from "@/services/api";
+import { getConnectionsPage } from "@/services/cmds";
 import { useConnectionSetting } from "@/services/states";
 import { useClashInfo } from "@/hooks/use-clash";
 import { BaseEmpty, BasePage } from "@/components/base";
@@ -26,7 +27,6 @@ import {
 } from "@/components/base/base-search-box";
 import { BaseStyledSelect } from "@/components/base/base-styled-select";
 import useSWRSubscription from "swr/subscription";
-import { createSockette } from "@/utils/websocket";
 import { useTheme } from "@mui/material/styles";
 import { useVisibility } from "@/hooks/use-visibility";
 
@@ -73,52 +73,53 @@ const ConnectionsPage = () => {
   >(
     clashInfo && pageVisible ? "getClashConnections" : null,
     (_key, { next }) => {
-      const { server = "", secret = "" } = clashInfo!;
-      const s = createSockette(
-        `ws://${server}/connections?token=${encodeURIComponent(secret)}`,
-        {
-          onmessage(event) {
-            const data = JSON.parse(event.data) as IConnections;
-            next(null, (old = initConn) => {
-              const oldConn = old.connections;
-              const maxLen = data.connections?.length;
+      // 由后端获取连接，本地连接时前端无法直接连接内核
+      let active = true;
+      const poll = async () => {
+        try {
+          const data = await getConnectionsPage();
+          if (!active) return;
+          next(null, (old = initConn) => {
+            const oldConn = old.connections;
+            const maxLen = data.connections?.length;
 
-              const connections: IConnectionsItem[] = [];
+            const connections: IConnectionsItem[] = [];
 
-              const rest = (data.connections || []).filter((each) => {
-                const index = oldConn.findIndex((o) => o.id === each.id);
+            const rest = (data.connections || []).filter((each) => {
+              const index = oldConn.findIndex((o) => o.id === each.id);
 
-                if (index >= 0 && index < maxLen) {
-                  const old = oldConn[index];
-                  each.curUpload = each.upload - old.upload;
-                  each.curDownload = each.download - old.download;
+              if (index >= 0 && index < maxLen) {
+                const old = oldConn[index];
+                each.curUpload = each.upload - old.upload;
+                each.curDownload = each.download - old.download;
 
-                  connections[index] = each;
-                  return false;
-                }
-                return true;
-              });
+                connections[index] = each;
+                return false;
+              }
+              return true;
+            });
 
-              for (let i = 0; i < maxLen; ++i) {
-                if (!connections[i] && rest.length > 0) {
-                  connections[i] = rest.shift()!;
-                  connections[i].curUpload = 0;
-                  connections[i].curDownload = 0;
-                }
+            for (let i = 0; i < maxLen; ++i) {
+              if (!connections[i] && rest.length > 0) {
+                connections[i] = rest.shift()!;
+                connections[i].curUpload = 0;
+                connections[i].curDownload = 0;
               }
+            }
 
-              return { ...data, connections };
-            });
-          },
-          onerror(event) {
-            next(event);
-          },
-        },
-        3,
-      );
+            return { ...data, connections };
+          });
+        } catch (err) {
+          if (active) next(err);
+        }
+      };
+
+      poll();
+      const timer = setInterval(poll, 1000);
 
       return () => {
-        s.close();
+        active = false;
+        clearInterval(timer);
       };
     },
   );
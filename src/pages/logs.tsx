@@ -1,5 +1,11 @@
 import { useMemo, useState } from "react";
-import { Box, Button, IconButton, MenuItem } from "@mui/material";
+import {
+  Box,
+  Button,
+  IconButton,
+  MenuItem,
+  Typography,
+} from "@mui/material";
 import { Virtuoso } from "react-virtuoso";
 import { useTranslation } from "react-i18next";
 import { useLocalStorage } from "foxact/use-local-storage";
@@ -10,6 +16,7 @@ import {
 } from "@mui/icons-material";
 import { useLogData, LogLevel, clearLogs } from "@/hooks/use-log-data";
 import { useEnableLog } from "@/services/states";
+import { useSubscriptionState } from "@/hooks/use-subscription-state";
 import { BaseEmpty, BasePage } from "@/components/base";
 import LogItem from "@/components/log/log-item";
 import { useTheme } from "@mui/material/styles";
@@ -28,6 +35,7 @@ const LogPage = () => {
   );
   const [match, setMatch] = useState(() => (_: string) => true);
   const logData = useLogData(logLevel);
+  const logsState = useSubscriptionState("logs", enableLog);
   const [searchState, setSearchState] = useState<SearchState>();
 
   const filterLogs = useMemo(() => {
@@ -56,6 +64,12 @@ const LogPage = () => {
       }}
       header={
         <Box sx={{ display: "flex", alignItems: "center", gap: 2 }}>
+          {enableLog && logsState && logsState !== "connected" && (
+            <Typography variant="body2" color="warning.main">
+              {t(logsState === "stopped" ? "Core Stopped" : "Reconnecting")}
+            </Typography>
+          )}
+
           <IconButton
             title={t("Pause")}
             size="small"
@@ -1,4 +1,4 @@
-import useSWR from "swr";
+import useSWR, { mutate } from "swr";
 import { useEffect, useMemo, useRef, useState } from "react";
 import { useLockFn } from "ahooks";
 import { Box, Button, IconButton, Stack, Grid2 } from "@mui/material";
@@ -19,6 +19,7 @@ import { LoadingButton } from "@mui/lab";
 import {
   ClearRounded,
   ContentPasteRounded,
+  FactCheckOutlined,
   LocalFireDepartmentRounded,
   RefreshRounded,
   TextSnippetOutlined,
@@ -33,6 +34,7 @@ import {
   updateProfile,
   reorderProfile,
   createProfile,
+  validateChain,
 } from "@/services/cmds";
 import { useSetLoadingCache } from "@/services/states";
 import { closeAllConnections } from "@/services/api";
@@ -110,7 +112,6 @@ const ProfilePage = () => {
 
   const {
     profiles = {},
-    activateSelected,
     patchProfiles,
     mutateProfiles,
   } = useProfiles();
@@ -173,7 +174,8 @@ const ProfilePage = () => {
       const success = await patchProfiles({ current: profile });
       await mutateLogs();
       closeAllConnections();
-      await activateSelected();
+      // 节点选择由内核或后端在应用配置后恢复，这里只刷新显示
+      mutate("getProxies");
       if (notifySuccess && success) {
         Notice.success(t("Profile Switched"), 1000);
       }
@@ -201,7 +203,8 @@ const ProfilePage = () => {
   const onEnhance = useLockFn(async (notifySuccess: boolean) => {
     setActivatings(currentActivatings());
     try {
-      await enhanceProfiles();
+      // 手动重新激活时即使配置没有变化也推送给内核
+      await enhanceProfiles(notifySuccess);
       mutateLogs();
       if (notifySuccess) {
         Notice.success(t("Profile Reactivated"), 1000);
@@ -213,6 +216,45 @@ const ProfilePage = () => {
     }
   });
 
+  // 逐项验证增强链，记录出错的链项
+  const [chainErrors, setChainErrors] = useState<Record<string, string>>({});
+  const onValidateChain = useLockFn(async () => {
+    setActivatings(currentActivatings());
+    try {
+      const reports = await validateChain();
+      mutateLogs();
+      const failed = reports.filter((r) => !r.ok && !r.skipped);
+      const describe = (e?: IValidateErrorCode) =>
+        e && "detail" in e ? e.detail : (e?.code ?? "");
+      setChainErrors(
+        Object.fromEntries(failed.map((r) => [r.item_uid, describe(r.error)])),
+      );
+      if (failed.length === 0) {
+        Notice.success(t("Chain Validation Passed"), 1000);
+      } else {
+        Notice.error(`${t("Chain Item Invalid")}: ${failed[0].item_uid}`, 3000);
+      }
+    } catch (err: any) {
+      Notice.error(err?.message || err.toString(), 3000);
+    } finally {
+      setActivatings([]);
+    }
+  });
+
+  // 订阅自身或者它的扩展项出错都标记在订阅上
+  const chainErrorOf = (item: IProfileItem) => {
+    const { option } = item;
+    const uids = [
+      item.uid,
+      option?.merge,
+      option?.script,
+      option?.rules,
+      option?.proxies,
+      option?.groups,
+    ];
+    return uids.map((uid) => uid && chainErrors[uid]).find((e) => e);
+  };
+
   const onDelete = useLockFn(async (uid: string) => {
     const current = profiles.current === uid;
     try {
@@ -287,6 +329,15 @@ const ProfilePage = () => {
             <TextSnippetOutlined />
           </IconButton>
 
+          <IconButton
+            size="small"
+            color="inherit"
+            title={t("Validate Chain")}
+            onClick={onValidateChain}
+          >
+            <FactCheckOutlined />
+          </IconButton>
+
           <IconButton
             size="small"
             color="primary"
@@ -395,6 +446,7 @@ const ProfilePage = () => {
                         }
                       }}
                       onDelete={() => onDelete(item.uid)}
+                      chainError={chainErrorOf(item)}
                     />
                   </Grid2>
                 ))}
@@ -1,28 +1,78 @@
-import useSWR from "swr";
-import { useState, useMemo, useRef } from "react";
+import { mutate } from "swr";
+import useSWRInfinite from "swr/infinite";
+import { useState, useMemo, useRef, useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
 import { useTranslation } from "react-i18next";
 import { Virtuoso, VirtuosoHandle } from "react-virtuoso";
-import { Box } from "@mui/material";
-import { getRules } from "@/services/api";
+import { Box, Typography } from "@mui/material";
+import { getRulesPage } from "@/services/cmds";
 import { BaseEmpty, BasePage } from "@/components/base";
 import RuleItem from "@/components/rule/rule-item";
 import { ProviderButton } from "@/components/rule/provider-button";
-import { BaseSearchBox } from "@/components/base/base-search-box";
+import {
+  BaseSearchBox,
+  SearchState,
+} from "@/components/base/base-search-box";
 import { useTheme } from "@mui/material/styles";
 import { ScrollTopButton } from "@/components/layout/scroll-top-button";
 
+// 每次向后端请求的规则数
+const PAGE_SIZE = 1000;
+
 const RulesPage = () => {
   const { t } = useTranslation();
-  const { data = [] } = useSWR("getRules", getRules);
+  const [match, setMatch] = useState(() => (_: string) => true);
+  // 后端按子串筛选，正则表达式只能在已加载的规则中匹配
+  const [query, setQuery] = useState("");
+
+  const {
+    data: pages = [],
+    size,
+    setSize,
+    mutate: mutateRules,
+  } = useSWRInfinite(
+    (index, prev: IRulesPage | null) => {
+      if (prev && prev.rules.length < PAGE_SIZE) return null;
+      return ["getRules", query, index];
+    },
+    ([_, query, index]) =>
+      getRulesPage({ query, offset: index * PAGE_SIZE, limit: PAGE_SIZE }),
+  );
+
+  // rule provider 更新后规则数量会变化
+  useEffect(() => {
+    const unlisten = listen<string[]>("verge://rule-providers-updated", () => {
+      mutateRules();
+      mutate("getRuleProviders");
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
   const theme = useTheme();
   const isDark = theme.palette.mode === "dark";
-  const [match, setMatch] = useState(() => (_: string) => true);
   const virtuosoRef = useRef<VirtuosoHandle>(null);
   const [showScrollTop, setShowScrollTop] = useState(false);
 
+  const total = pages[0]?.total ?? 0;
+  const filtered = pages[0]?.filtered ?? 0;
   const rules = useMemo(() => {
-    return data.filter((item) => match(item.payload));
-  }, [data, match]);
+    return pages
+      .flatMap((page) => page.rules)
+      .filter((item) => match(`${item.type} ${item.payload} ${item.proxy}`));
+  }, [pages, match]);
+
+  const onSearch = (
+    match: (content: string) => boolean,
+    state: SearchState,
+  ) => {
+    setMatch(() => match);
+    setQuery(state.useRegularExpression ? "" : state.text.trim());
+  };
+
+  const loadMore = () => {
+    if (size * PAGE_SIZE < filtered) setSize(size + 1);
+  };
 
   const scrollToTop = () => {
     virtuosoRef.current?.scrollTo({
@@ -61,7 +111,14 @@ const RulesPage = () => {
           alignItems: "center",
         }}
       >
-        <BaseSearchBox onSearch={(match) => setMatch(() => match)} />
+        <BaseSearchBox onSearch={onSearch} />
+        <Typography
+          variant="body2"
+          color="text.secondary"
+          sx={{ ml: 1, whiteSpace: "nowrap" }}
+        >
+          {filtered === total ? total : `${filtered} / ${total}`}
+        </Typography>
       </Box>
 
       {rules.length > 0 ? (
@@ -72,9 +129,10 @@ const RulesPage = () => {
             style={{
               flex: 1,
             }}
-            itemContent={(index, item) => (
-              <RuleItem index={index + 1} value={item} />
+            itemContent={(_, item) => (
+              <RuleItem index={item.index} value={item} />
             )}
+            endReached={loadMore}
             followOutput={"smooth"}
             scrollerRef={(ref) => {
               if (ref) ref.addEventListener("scroll", handleScroll);
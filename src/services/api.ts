@@ -1,109 +1,47 @@
-import axios, { AxiosInstance } from "axios";
-import { getClashInfo } from "./cmds";
-
-let axiosIns: AxiosInstance = null!;
-
-/// initialize some information
-/// enable force update axiosIns
-export const getAxios = async (force: boolean = false) => {
-  if (axiosIns && !force) return axiosIns;
-
-  let server = "";
-  let secret = "";
-
-  try {
-    const info = await getClashInfo();
-
-    if (info?.server) {
-      server = info.server;
-
-      // compatible width `external-controller`
-      if (server.startsWith(":")) server = `127.0.0.1${server}`;
-      else if (/^\d+$/.test(server)) server = `127.0.0.1:${server}`;
-    }
-    if (info?.secret) secret = info?.secret;
-  } catch {}
-
-  axiosIns = axios.create({
-    baseURL: `http://${server}`,
-    headers: secret ? { Authorization: `Bearer ${secret}` } : {},
-    timeout: 15000,
-  });
-  axiosIns.interceptors.response.use((r) => r.data);
-  return axiosIns;
-};
+import {
+  clearConnections,
+  closeConnection,
+  coreGc,
+  getClashConfigs,
+  getClashVersion,
+  getProvidersSnapshot,
+  getProxiesSnapshot,
+  getRuleProviderList,
+  healthcheckProxyProvider,
+  isCoreDebugEnabled,
+  selectProxy,
+  updateProxyProvider,
+  updateRuleProvider,
+} from "./cmds";
+
+// 所有请求都经由后端转发，控制接口可能只监听本地 socket 或命名管道
 
 /// Get Version
 export const getVersion = async () => {
-  const instance = await getAxios();
-  return instance.get("/version") as Promise<{
-    premium: boolean;
-    meta?: boolean;
-    version: string;
-  }>;
+  return getClashVersion();
 };
 
 /// Get current base configs
 export const getClashConfig = async () => {
-  const instance = await getAxios();
-  return instance.get("/configs") as Promise<IConfigData>;
-};
-
-/// Update geo data
-export const updateGeoData = async () => {
-  const instance = await getAxios();
-  return instance.post("/configs/geo");
-};
-
-/// Upgrade clash core
-export const upgradeCore = async () => {
-  const instance = await getAxios();
-  return instance.post("/upgrade");
-};
-
-/// Get current rules
-export const getRules = async () => {
-  const instance = await getAxios();
-  const response = await instance.get<any, any>("/rules");
-  return response?.rules as IRuleItem[];
-};
-
-/// Get Proxy delay
-export const getProxyDelay = async (
-  name: string,
-  url?: string,
-  timeout?: number,
-) => {
-  const params = {
-    timeout: timeout || 10000,
-    url: url || "http://cp.cloudflare.com/generate_204",
-  };
-  const instance = await getAxios();
-  const result = await instance.get(
-    `/proxies/${encodeURIComponent(name)}/delay`,
-    { params },
-  );
-  return result as any as { delay: number };
+  return getClashConfigs();
 };
 
 /// Update the Proxy Choose
 export const updateProxy = async (group: string, proxy: string) => {
-  const instance = await getAxios();
-  return instance.put(`/proxies/${encodeURIComponent(group)}`, { name: proxy });
+  return selectProxy(group, proxy);
 };
 
 // get proxy
-export const getProxiesInner = async () => {
-  const instance = await getAxios();
-  const response = await instance.get<any, any>("/proxies");
+export const getProxiesInner = async (fresh = false) => {
+  const response = await getProxiesSnapshot(fresh);
   return (response?.proxies || {}) as Record<string, IProxyItem>;
 };
 
 /// Get the Proxy information
-export const getProxies = async () => {
+export const getProxies = async (fresh = false) => {
   const [proxyRecord, providerRecord] = await Promise.all([
-    getProxiesInner(),
-    getProxyProviders(),
+    getProxiesInner(fresh),
+    getProxyProviders(fresh),
   ]);
   // provider name map
   const providerMap = Object.fromEntries(
@@ -180,9 +118,8 @@ export const getProxies = async () => {
 };
 
 // get proxy providers
-export const getProxyProviders = async () => {
-  const instance = await getAxios();
-  const response = await instance.get<any, any>("/providers/proxies");
+export const getProxyProviders = async (fresh = false) => {
+  const response = await getProvidersSnapshot(fresh);
 
   const providers = (response.providers || {}) as Record<
     string,
@@ -198,92 +135,43 @@ export const getProxyProviders = async () => {
 };
 
 export const getRuleProviders = async () => {
-  const instance = await getAxios();
-  const response = await instance.get<any, any>("/providers/rules");
-
-  const providers = (response.providers || {}) as Record<
-    string,
-    IRuleProviderItem
-  >;
-
-  return Object.fromEntries(
-    Object.entries(providers).filter(([key, item]) => {
-      const type = item.vehicleType.toLowerCase();
-      return type === "http" || type === "file";
-    }),
-  );
+  // 后端只返回来自订阅或文件的 provider
+  const providers = await getRuleProviderList();
+  return Object.fromEntries(providers.map((item) => [item.name, item]));
 };
 
 // proxy providers health check
 export const providerHealthCheck = async (name: string) => {
-  const instance = await getAxios();
-  return instance.get(
-    `/providers/proxies/${encodeURIComponent(name)}/healthcheck`,
-  );
+  return healthcheckProxyProvider(name);
 };
 
 export const proxyProviderUpdate = async (name: string) => {
-  const instance = await getAxios();
-  return instance.put(`/providers/proxies/${encodeURIComponent(name)}`);
+  return updateProxyProvider(name);
 };
 
 export const ruleProviderUpdate = async (name: string) => {
-  const instance = await getAxios();
-  return instance.put(`/providers/rules/${encodeURIComponent(name)}`);
-};
-
-export const getConnections = async () => {
-  const instance = await getAxios();
-  const result = await instance.get("/connections");
-  return result as any as IConnections;
+  return updateRuleProvider(name);
 };
 
 // Close specific connection
 export const deleteConnection = async (id: string) => {
-  const instance = await getAxios();
-  await instance.delete<any, any>(`/connections/${encodeURIComponent(id)}`);
+  await closeConnection(id);
 };
 
 // Close all connections
 export const closeAllConnections = async () => {
-  const instance = await getAxios();
-  await instance.delete<any, any>(`/connections`);
-};
-
-// Get Group Proxy Delays
-export const getGroupProxyDelays = async (
-  groupName: string,
-  url?: string,
-  timeout?: number,
-) => {
-  const params = {
-    timeout: timeout || 10000,
-    url: url || "http://cp.cloudflare.com/generate_204",
-  };
-  const instance = await getAxios();
-  const result = await instance.get(
-    `/group/${encodeURIComponent(groupName)}/delay`,
-    { params },
-  );
-  return result as any as Record<string, number>;
+  await clearConnections();
 };
 
 // Is debug enabled
 export const isDebugEnabled = async () => {
-  try {
-    const instance = await getAxios();
-    await instance.get("/debug/pprof");
-    return true;
-  } catch {
-    return false;
-  }
+  return isCoreDebugEnabled().catch(() => false);
 };
 
 // GC
 export const gc = async () => {
   try {
-    const instance = await getAxios();
-    await instance.put("/debug/gc");
+    await coreGc();
   } catch (error) {
     console.error(`Error gcing: ${error}`);
   }
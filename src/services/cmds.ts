@@ -1,6 +1,7 @@
 import dayjs from "dayjs";
 import { invoke } from "@tauri-apps/api/core";
 import { Notice } from "@/components/base";
+import { awaitOperation } from "@/services/operation";
 
 export async function copyClashEnv() {
   return invoke<void>("copy_clash_env");
@@ -10,8 +11,8 @@ export async function getProfiles() {
   return invoke<IProfilesConfig>("get_profiles");
 }
 
-export async function enhanceProfiles() {
-  return invoke<void>("enhance_profiles");
+export async function enhanceProfiles(force = false) {
+  return invoke<void>("enhance_profiles", { force });
 }
 
 export async function patchProfilesConfig(profiles: IProfilesConfig) {
@@ -44,6 +45,10 @@ export async function importProfile(url: string) {
   });
 }
 
+export async function importConfigBase64(b64: string, profileName: string) {
+  return invoke<string>("import_config_base64", { b64, profileName });
+}
+
 export async function reorderProfile(activeId: string, overId: string) {
   return invoke<void>("reorder_profile", {
     activeId,
@@ -95,6 +100,13 @@ export async function patchClashMode(payload: String) {
   return invoke<void>("patch_clash_mode", { payload });
 }
 
+// 立即修改内核并保存，内核修改失败时不会保存
+export async function patchClashRuntimeOption<
+  K extends "allow-lan" | "ipv6" | "log-level" | "mode" | "unified-delay",
+>(key: K, value: IConfigData[K]) {
+  return invoke<void>("patch_clash_runtime_option", { key, value });
+}
+
 export async function getVergeConfig() {
   return invoke<IVergeConfig>("get_verge_config");
 }
@@ -118,8 +130,22 @@ export async function getAutotemProxy() {
   }>("get_auto_proxy");
 }
 
-export async function changeClashCore(clashCore: string) {
-  return invoke<string | null>("change_clash_core", { clashCore });
+export async function compareCoreValidation(clashCore: string) {
+  return invoke<ICoreComparison>("compare_core_validation", { clashCore });
+}
+
+export async function changeClashCore(
+  clashCore: string,
+  useDefaultConfig = false,
+) {
+  return invoke<string | null>("change_clash_core", {
+    clashCore,
+    useDefaultConfig,
+  });
+}
+
+export async function validateChain() {
+  return invoke<IChainItemReport[]>("validate_chain");
 }
 
 export async function restartCore() {
@@ -156,14 +182,122 @@ export async function openWebUrl(url: string) {
   return invoke<void>("open_web_url", { url });
 }
 
-export async function cmdGetProxyDelay(
+// 返回切换后自动关闭的连接数
+export async function selectProxy(group: string, name: string) {
+  return invoke<number>("select_proxy", { group, name });
+}
+
+// 后端会缓存 1.5 秒，需要最新结果（如测完延迟）时传入 fresh
+export async function getProxiesSnapshot(fresh = false) {
+  return invoke<{ proxies?: Record<string, IProxyItem> }>("get_proxies", {
+    fresh,
+  });
+}
+
+export async function getProvidersSnapshot(fresh = false) {
+  return invoke<{ providers?: Record<string, IProxyProviderItem> }>(
+    "get_providers_proxies",
+    { fresh },
+  );
+}
+
+export async function getProxyProviderList() {
+  return invoke<IProxyProviderItem[]>("get_proxy_providers");
+}
+
+export async function getSubscriptionUsage() {
+  return invoke<ISubscriptionUsage[]>("get_subscription_usage");
+}
+
+export async function updateProxyProvider(name: string) {
+  return invoke<void>("update_proxy_provider", { name });
+}
+
+export async function healthcheckProxyProvider(name: string) {
+  return invoke<void>("healthcheck_proxy_provider", { name });
+}
+
+export async function updateAllProxyProviders() {
+  return invoke<IProviderUpdateResult[]>("update_all_proxy_providers");
+}
+
+export async function getClashVersion() {
+  return invoke<{ version: string; meta: boolean }>("get_clash_version");
+}
+
+export async function getClashConfigs() {
+  return invoke<IConfigData>("get_clash_configs");
+}
+
+export async function isCoreDebugEnabled() {
+  return invoke<boolean>("is_core_debug_enabled");
+}
+
+export async function coreGc() {
+  return invoke<void>("core_gc");
+}
+
+export async function getRuleProviderList() {
+  return invoke<IRuleProviderItem[]>("get_rule_providers");
+}
+
+export async function updateRuleProvider(name: string) {
+  return invoke<void>("update_rule_provider", { name });
+}
+
+export async function updateAllRuleProviders() {
+  return invoke<IProviderUpdateResult[]>("update_all_rule_providers");
+}
+
+export async function startCoreLogs(level: string) {
+  return invoke<void>("start_core_logs", { level });
+}
+
+export async function stopCoreLogs() {
+  return invoke<void>("stop_core_logs");
+}
+
+export async function getSubscriptionState(name: ISubscriptionName) {
+  return invoke<ISubscriptionState | null>("get_subscription_state", { name });
+}
+
+export async function startMemoryUsage() {
+  return invoke<boolean>("start_memory_usage");
+}
+
+export async function stopMemoryUsage() {
+  return invoke<void>("stop_memory_usage");
+}
+
+export async function flushFakeip() {
+  return invoke<void>("flush_fakeip");
+}
+
+export async function upgradeCore() {
+  return awaitOperation<ICoreUpgradeResult>(() =>
+    invoke<string>("upgrade_core"),
+  );
+}
+
+export async function dnsQuery(name: string, qtype?: string) {
+  return invoke<IDnsQueryResult>("dns_query", { name, qtype });
+}
+
+export async function cmdTestProxyDelay(
   name: string,
-  timeout: number,
+  timeout?: number,
   url?: string,
 ) {
-  name = encodeURIComponent(name);
-  return invoke<{ delay: number }>("clash_api_get_proxy_delay", {
-    name,
+  return invoke<IProxyDelay>("test_proxy_delay", { name, url, timeout });
+}
+
+export async function cmdTestGroupDelay(
+  group: string,
+  timeout?: number,
+  url?: string,
+) {
+  return invoke<string>("test_group_delay", {
+    group,
     url,
     timeout,
   });
@@ -210,8 +344,12 @@ export async function getNetworkInterfacesInfo() {
   return invoke<INetworkInterface[]>("get_network_interfaces_info");
 }
 
-export async function createWebdavBackup() {
-  return invoke<void>("create_webdav_backup");
+export async function getTunStats() {
+  return invoke<ITunInterfaceStats>("get_tun_stats");
+}
+
+export async function createWebdavBackup(includeTlsOptions?: boolean) {
+  return invoke<void>("create_webdav_backup", { includeTlsOptions });
 }
 
 export async function deleteWebdavBackup(filename: string) {
@@ -249,3 +387,164 @@ export async function scriptValidateNotice(status: string, msg: string) {
 export async function validateScriptFile(filePath: string) {
   return invoke<boolean>("validate_script_file", { filePath });
 }
+
+export async function reloadProfileScript(uid: string) {
+  return invoke<void>("reload_profile_script", { uid });
+}
+
+export async function validateDnsConfig(configPath: string) {
+  return invoke<IDnsConfigWarning[]>("validate_dns_config", { configPath });
+}
+
+export async function getRulesForProxy(proxyName: string) {
+  return invoke<IRuleEntry[]>("get_rules_for_proxy", { proxyName });
+}
+
+export async function startConnectionRecording(outputPath: string) {
+  return invoke<void>("start_connection_recording", { outputPath });
+}
+
+export async function stopConnectionRecording() {
+  return invoke<void>("stop_connection_recording");
+}
+
+export async function replayConnections(inputPath: string) {
+  return invoke<IReplayReport>("replay_connections", { inputPath });
+}
+
+export async function startCertificateWatch(proxyNames: string[]) {
+  return invoke<void>("start_certificate_watch", { proxyNames });
+}
+
+export async function stopCertificateWatch() {
+  return invoke<void>("stop_certificate_watch");
+}
+
+export async function forceReconnectAllConnections() {
+  return invoke<number>("force_reconnect_all_connections");
+}
+
+export async function getConnectionsPage(options?: {
+  host?: string;
+  sortByTraffic?: boolean;
+  limit?: number;
+}) {
+  return invoke<IConnectionsPage>("get_connections", options ?? {});
+}
+
+export async function getRulesPage(options?: {
+  query?: string;
+  offset?: number;
+  limit?: number;
+}) {
+  return invoke<IRulesPage>("get_rules", options ?? {});
+}
+
+export async function clearConnections(filter?: string) {
+  return invoke<number>("clear_connections", { filter });
+}
+
+export async function closeConnection(id: string) {
+  return invoke<void>("close_connection", { id });
+}
+
+export async function toggleIpv6Routing() {
+  return invoke<boolean>("toggle_ipv6_routing");
+}
+
+export async function toggleProxyMux(proxyName?: string) {
+  return invoke<boolean>("toggle_proxy_mux", { proxyName });
+}
+
+// 结果为保存的路径，取消保存时为 null
+export async function exportDiagnosticsDialog() {
+  return awaitOperation<string | null>(() =>
+    invoke<string>("export_diagnostics_dialog"),
+  );
+}
+
+export async function getCoreVersion(clashCore?: string) {
+  return invoke<ICoreVersion>("get_core_version", { clashCore });
+}
+
+export async function validateConfigDetailed(configPath: string) {
+  return invoke<IValidationReport>("validate_config_detailed", { configPath });
+}
+
+export async function getCoreStatus() {
+  return invoke<ICoreStatus>("get_core_status");
+}
+
+export async function getCoreUsage() {
+  return invoke<ICoreUsage | null>("get_core_usage");
+}
+
+export async function getCoreHistory() {
+  return invoke<ICoreEvent[]>("get_core_history");
+}
+
+export async function getHotkeyStatus() {
+  return invoke<IHotkeyStatus[]>("get_hotkey_status");
+}
+
+export async function suspendHotkeys() {
+  return invoke<void>("suspend_hotkeys");
+}
+
+export async function resumeHotkeys() {
+  return invoke<void>("resume_hotkeys");
+}
+
+export async function validateHotkey(
+  key: string,
+  func: string,
+  pending: string[],
+) {
+  return invoke<IHotkeyVerdict>("validate_hotkey", { key, func, pending });
+}
+
+export async function getCoreState() {
+  return invoke<ICoreStateInfo>("get_core_state");
+}
+
+export async function rotateCoreLog() {
+  return invoke<string>("rotate_core_log");
+}
+
+export async function validateSubscriptionYaml(path: string) {
+  return invoke<IValidateErrorCode | null>("validate_subscription_yaml", {
+    path,
+  });
+}
+
+export async function checkConnectivityNow() {
+  return invoke<number>("check_connectivity_now");
+}
+
+export async function setUpstreamProxy(upstreamUrl: string | null) {
+  return invoke<void>("set_upstream_proxy", { upstreamUrl });
+}
+
+export async function toggleMockProxyMode(port?: number) {
+  return invoke<boolean>("toggle_mock_proxy_mode", { port });
+}
+
+export async function saveAndReloadConfig() {
+  return invoke<void>("save_and_reload_config");
+}
+
+export async function completeConfigSave(error?: string) {
+  return invoke<void>("complete_config_save", { error });
+}
+
+export async function updateGeoData(operationId?: string) {
+  return invoke<string>("update_geo_data", { operationId });
+}
+
+export async function updateGeoByCore(operationId?: string) {
+  return invoke<string>("update_geo_by_core", { operationId });
+}
+
+export async function cancelOperation(id: string) {
+  return invoke<boolean>("cancel_operation", { id });
+}
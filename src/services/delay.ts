@@ -1,4 +1,6 @@
-import { cmdGetProxyDelay } from "./cmds";
+import { listen } from "@tauri-apps/api/event";
+import { cmdTestGroupDelay, cmdTestProxyDelay } from "./cmds";
+import { awaitOperation } from "./operation";
 
 const hashKey = (name: string, group: string) => `${group ?? ""}::${name}`;
 
@@ -74,8 +76,9 @@ class DelayManager {
 
     try {
       const url = this.getUrl(group);
-      const result = await cmdGetProxyDelay(name, timeout, url);
-      delay = result.delay;
+      const result = await cmdTestProxyDelay(name, timeout, url);
+      // 超时按超时时间记录，显示为 Timeout
+      delay = result.timeout ? timeout : result.delay ?? 1e6;
     } catch {
       delay = 1e6; // error
     }
@@ -113,6 +116,43 @@ class DelayManager {
     });
   }
 
+  /// 由后端限制并发测试整个策略组，结果通过事件逐个返回
+  async checkGroupDelay(nameList: string[], group: string, timeout: number) {
+    const names = nameList.filter(Boolean);
+    names.forEach((name) => this.setDelay(name, group, -2));
+
+    const unlisten = await listen<IGroupDelayResult>(
+      "verge://delay-result",
+      ({ payload }) => {
+        if (payload.group !== group) return;
+        const delay = payload.timeout ? timeout : payload.delay ?? 1e6;
+        this.setDelay(payload.name, group, delay);
+      },
+    );
+
+    try {
+      const url = this.getUrl(group);
+      const res = await awaitOperation<IGroupDelaySummary>(() =>
+        cmdTestGroupDelay(group, timeout, url),
+      );
+      // 被新的测试取消时由新的测试更新结果
+      if (res.cancelled) return;
+      const summary = res.result;
+      const tested = new Set(summary.results.map((r) => r.name));
+      // 不在策略组中的节点按错误处理
+      names
+        .filter((name) => !tested.has(name))
+        .forEach((name) => this.setDelay(name, group, 1e6));
+      return summary;
+    } catch (err: any) {
+      names
+        .filter((name) => this.getDelay(name, group) === -2)
+        .forEach((name) => this.setDelay(name, group, 1e6));
+    } finally {
+      unlisten();
+    }
+  }
+
   formatDelay(delay: number, timeout = 10000) {
     if (delay <= 0) return "Error";
     if (delay > 1e5) return "Error";
@@ -0,0 +1,42 @@
+import { listen } from "@tauri-apps/api/event";
+
+interface IOperationFinished<T> {
+  id: string;
+  result?: T;
+  error?: string;
+  cancelled: boolean;
+}
+
+type Finished<T> = IOperationFinished<T>;
+
+// 启动后台任务并等待 operation::finished，出错时抛出，被取消时不视为错误
+// 先开始监听再启动，任务在返回 id 之前就结束也不会漏掉
+export async function awaitOperation<T>(
+  start: () => Promise<string>,
+): Promise<IOperationResult<T>> {
+  const finished = new Map<string, Finished<T>>();
+  let waiting: { id: string; resolve: (e: Finished<T>) => void } | null =
+    null;
+
+  const unlisten = await listen<Finished<T>>(
+    "operation::finished",
+    ({ payload }) => {
+      if (waiting?.id === payload.id) waiting.resolve(payload);
+      else finished.set(payload.id, payload);
+    },
+  );
+
+  try {
+    const id = await start();
+    const payload =
+      finished.get(id) ??
+      (await new Promise<Finished<T>>((resolve) => {
+        waiting = { id, resolve };
+      }));
+    if (payload.cancelled) return { cancelled: true };
+    if (payload.error != null) throw new Error(payload.error);
+    return { cancelled: false, result: payload.result as T };
+  } finally {
+    unlisten();
+  }
+}
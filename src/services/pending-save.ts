@@ -0,0 +1,24 @@
+import { completeConfigSave } from "@/services/cmds";
+
+type Saver = () => Promise<void> | void;
+
+// 未提交的修改，收到 config::request_save 时统一保存
+const savers = new Set<Saver>();
+
+export const registerPendingSave = (saver: Saver) => {
+  savers.add(saver);
+  return () => {
+    savers.delete(saver);
+  };
+};
+
+export const flushPendingSave = async () => {
+  try {
+    for (const saver of savers) {
+      await saver();
+    }
+    await completeConfigSave();
+  } catch (err: any) {
+    await completeConfigSave(err?.message || err.toString());
+  }
+};
@@ -19,6 +19,16 @@ declare const OS_PLATFORM: Platform;
 /**
  * Some interface for clash api
  */
+type ISubscriptionName = "traffic" | "memory" | "logs";
+
+// 后台任务的结果，被取消时没有 result
+type IOperationResult<T> =
+  | { cancelled: true }
+  | { cancelled: false; result: T };
+
+// 等待重连时为 disconnected，内核被主动停止时为 stopped
+type ISubscriptionState = "connected" | "disconnected" | "stopped";
+
 interface IConfigData {
   port: number;
   mode: string;
@@ -71,6 +81,24 @@ interface IProxyItem {
   fixed?: string; // 记录固定(优先)的节点
 }
 
+interface IProxyDelay {
+  name: string;
+  delay: number | null;
+  timeout: boolean; // 超时或测试失败
+}
+
+interface IGroupDelayResult extends IProxyDelay {
+  group: string;
+}
+
+interface IGroupDelaySummary {
+  group: string;
+  total: number;
+  alive: number;
+  timeout: number;
+  results: IProxyDelay[];
+}
+
 type IProxyGroupItem = Omit<IProxyItem, "all"> & {
   all: IProxyItem[];
 };
@@ -89,6 +117,51 @@ interface IProxyProviderItem {
   };
 }
 
+// 当前订阅和 proxy provider 的流量信息，expire 为秒级时间戳
+interface ISubscriptionUsage {
+  source: "profile" | "provider";
+  name: string;
+  upload: number;
+  download: number;
+  total: number;
+  remaining: number | null;
+  expire: number | null;
+}
+
+interface IProviderUpdateResult {
+  name: string;
+  ok: boolean;
+  error: string | null;
+}
+
+interface IDnsRecord {
+  name: string;
+  type: string;
+  ttl: number;
+  data: string;
+}
+
+interface IDnsQueryResult {
+  name: string;
+  qtype: string;
+  status: "ok" | "nx_domain" | "timeout" | "failed";
+  rcode: number | null;
+  answers: IDnsRecord[];
+  error: string | null;
+}
+
+type ICoreUpgradeError =
+  | { kind: "service_mode" }
+  | { kind: "no_permission"; path: string }
+  | { kind: "unsupported" }
+  | { kind: "already_latest"; detail: string }
+  | { kind: "binary_unchanged"; path: string }
+  | { kind: "failed"; detail: string };
+
+type ICoreUpgradeResult =
+  | { Ok: { old_version: string | null; new_version: string | null } }
+  | { Err: ICoreUpgradeError };
+
 interface IRuleProviderItem {
   name: string;
   behavior: string;
@@ -140,6 +213,20 @@ interface IConnections {
   connections: IConnectionsItem[];
 }
 
+interface IConnectionsPage extends IConnections {
+  // 筛选后、截断前的连接数
+  total: number;
+  truncated: boolean;
+}
+
+interface IRulesPage {
+  // 全部规则数和筛选后的规则数
+  total: number;
+  filtered: number;
+  // index 为规则在完整列表中的位置，从 1 开始
+  rules: (IRuleItem & { index: number })[];
+}
+
 /**
  * Some interface for command
  */
@@ -183,6 +270,11 @@ interface IProfileOption {
   self_proxy?: boolean;
   update_interval?: number;
   danger_accept_invalid_certs?: boolean;
+  custom_ca_pem?: string;
+  client_cert?: string;
+  client_key?: string;
+  insecure_skip_verify?: boolean;
+  insecure_skip_verify_confirmed?: boolean;
   merge?: string;
   script?: string;
   rules?: string;
@@ -190,6 +282,168 @@ interface IProfileOption {
   groups?: string;
 }
 
+interface IValidationError {
+  file: string | null;
+  line: number | null;
+  message: string;
+  kind: "yaml" | "parse" | "provider" | "process" | "other";
+}
+
+interface ICoreLogEntry {
+  time?: string | null;
+  level: "debug" | "info" | "warning" | "error" | "fatal";
+  message: string;
+}
+
+interface IValidationReport {
+  valid: boolean;
+  errors: IValidationError[];
+  raw: string;
+  entries: ICoreLogEntry[];
+  error?: IValidateErrorCode;
+}
+
+interface IChainItemReport {
+  item_uid: string;
+  ok: boolean;
+  skipped: boolean;
+  error?: IValidateErrorCode;
+}
+
+type IValidateErrorCode =
+  | { code: "file_not_found"; path: string }
+  | { code: "file_read_failed"; path: string; detail: string }
+  | { code: "invalid_yaml"; detail: string }
+  | { code: "subscription_forbidden_keys"; keys: string[] }
+  | { code: "core_rejected"; detail: string }
+  | { code: "validate_process_exited"; exit_code: number }
+  | { code: "validate_process_killed" }
+  | { code: "validate_timeout"; secs: number }
+  | { code: "script_read_failed"; detail: string }
+  | { code: "script_syntax_error"; detail: string }
+  | { code: "script_missing_main" }
+  | { code: "script_runtime_error"; detail: string }
+  | { code: "script_returned_nothing" }
+  | { code: "script_returned_non_object"; type_name: string }
+  | { code: "script_timeout"; secs: number };
+
+type ICoreVersion =
+  | { status: "installed"; version: string; raw: string }
+  | { status: "not_installed" }
+  | { status: "broken"; reason: string };
+
+type CoreState = "starting" | "running" | "stopping" | "stopped" | "crashed";
+
+interface ICoreStateInfo {
+  state: CoreState;
+  reason: string | null;
+}
+
+interface ICoreValidation {
+  core: string;
+  error?: IValidateErrorCode | null;
+  message?: string | null;
+}
+
+interface ICoreComparison {
+  current: ICoreValidation;
+  target: ICoreValidation;
+}
+
+interface ICoreUsage {
+  pid: number;
+  rss: number;
+  cpu_percent: number;
+  sampled_at: number;
+}
+
+interface IHotkeyStatus {
+  hotkey: string;
+  func: string;
+  active: boolean;
+  // 已排队等待注册，还没有生效
+  pending: boolean;
+  error: string | null;
+  kind:
+    | "invalid"
+    | "already_registered"
+    | "conflict"
+    | "unsupported"
+    | "unavailable"
+    | null;
+}
+
+interface IHotkeyVerdict {
+  valid: boolean;
+  func_error: string | null;
+  key_error: string | null;
+  conflict: string | null;
+  os_error: string | null;
+}
+
+interface ICoreEvent {
+  timestamp: number;
+  event:
+    | "start"
+    | "start_failed"
+    | "stop"
+    | "stop_failed"
+    | "restart"
+    | "crash"
+    | "change_core"
+    | "validation_failed";
+  detail: string;
+}
+
+interface ICoreStatus {
+  core_name: string | null;
+  binary_path: string | null;
+  pid: number | null;
+  run_mode: "service" | "sidecar" | null;
+  /** unix timestamp in seconds */
+  started_at: number | null;
+  restart_count: number;
+  last_restart_reason: string | null;
+  api_version: string | null;
+  meta: boolean | null;
+}
+
+interface ITunInterfaceStats {
+  rx_packets: number;
+  tx_packets: number;
+  rx_bytes: number;
+  tx_bytes: number;
+  rx_errors: number;
+  tx_errors: number;
+}
+
+interface IReplayReport {
+  total: number;
+  matched: number;
+  unsupported: number;
+  mismatches: {
+    host: string;
+    destination: string;
+    recorded_rule: string;
+    recorded_payload: string;
+    simulated_rule: string;
+    simulated_payload: string;
+    simulated_proxy: string;
+  }[];
+}
+
+interface IRuleEntry {
+  type: string;
+  payload: string;
+  proxy: string;
+}
+
+interface IDnsConfigWarning {
+  field_path: string;
+  message: string;
+  severity: "info" | "warning" | "error";
+}
+
 interface IProfilesConfig {
   current?: string;
   valid?: string[];
@@ -756,6 +1010,27 @@ interface IVergeConfig {
   webdav_url?: string;
   webdav_username?: string;
   webdav_password?: string;
+  core_store_selected?: boolean;
+  core_store_fake_ip?: boolean;
+  prefer_selection_replay?: boolean;
+  upstream_proxy?: string;
+  core_startup_timeout?: number;
+  core_validate_timeout?: number;
+  config_update_debounce?: number;
+  enable_profile_hot_reload?: boolean;
+  orphan_core_action?: "restart" | "adopt";
+  port_conflict_action?: "fail" | "auto";
+  clash_core_extra_args?: string[];
+  clash_core_run_only_args?: string[];
+  enable_in_app_hotkey?: boolean;
+  enable_hotkey_notification?: boolean;
+  hotkey_debounce_ms?: number;
+  delay_test_concurrency?: number;
+  auto_flush_fakeip?: boolean;
+  external_controller_transport?: "tcp" | "socket";
+  external_controller_secret?: string;
+  auto_close_connection_scope?: "group" | "all";
+  force_full_core_restart?: boolean;
 }
 
 interface IWebDavFile {